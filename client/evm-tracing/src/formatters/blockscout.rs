@@ -4,7 +4,7 @@ use crate::{
     listeners::call_list::Listener,
     types::{
         single::{Call, TransactionTrace},
-        CallResult, CallType, CreateResult,
+        CallResult, CallType, CreateResult, GasBreakdown, PrecompileCall, StorageAccess,
     },
 };
 use codec::{Decode, Encode};
@@ -69,6 +69,15 @@ pub struct BlockscoutCall {
     pub gas: U256,
     /// Gas used by this context.
     pub gas_used: U256,
+    /// Breakdown of `gas_used`, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_breakdown: Option<GasBreakdown>,
+    /// Storage slots accessed by this context, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<StorageAccess>,
+    /// The precompile handling this context, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precompile: Option<PrecompileCall>,
     #[serde(flatten)]
     pub inner: BlockscoutCallInner,
 }
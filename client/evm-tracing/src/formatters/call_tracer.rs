@@ -2,7 +2,7 @@ use super::{blockscout::BlockscoutCallInner, serialization::*, Bytes};
 
 use crate::types::{
     single::{Call, TransactionTrace},
-    CallResult, CallType, CreateResult,
+    CallResult, CallType, CreateResult, GasBreakdown, PrecompileCall, StorageAccess,
 };
 
 use crate::listeners::call_list::Listener;
@@ -19,12 +19,15 @@ impl super::ResponseFormatter for Formatter {
     type Listener = Listener;
     type Response = Vec<TransactionTrace>;
 
-    fn format(mut listener: Listener) -> Option<Vec<TransactionTrace>> {
-        // Remove empty BTreeMaps pushed to `entries`.
-        // I.e. InvalidNonce or other pallet_evm::runner exits
-        listener.entries.retain(|x| !x.is_empty());
+    fn format(listener: Listener) -> Option<Vec<TransactionTrace>> {
         let mut traces = Vec::new();
-        for entry in listener.entries.iter() {
+        for (position, entry) in listener.entries.iter().enumerate() {
+            // Skip empty BTreeMaps pushed to `entries`.
+            // I.e. InvalidNonce or other pallet_evm::runner exits, or extrinsics not entering the
+            // EVM.
+            if entry.is_empty() {
+                continue
+            }
             let mut result: Vec<Call> = entry
                 .iter()
                 .map(|(_, it)| {
@@ -33,11 +36,17 @@ impl super::ResponseFormatter for Formatter {
                     let value = it.value;
                     let gas = it.gas;
                     let gas_used = it.gas_used;
+                    let gas_breakdown = it.gas_breakdown.clone();
+                    let storage = it.storage.clone();
+                    let precompile = it.precompile.clone();
                     let inner = it.inner.clone();
                     Call::CallTracer(CallTracerCall {
                         from,
                         gas,
                         gas_used,
+                        gas_breakdown,
+                        storage,
+                        precompile,
                         trace_address: Some(trace_address),
                         inner: match inner {
                             BlockscoutCallInner::Call { input, to, res, call_type } => {
@@ -194,7 +203,10 @@ impl super::ResponseFormatter for Formatter {
                 *trace_address = None;
             }
             if result.len() == 1 {
-                traces.push(TransactionTrace::CallListNested(Box::new(result.pop().unwrap())));
+                traces.push(
+                    TransactionTrace::CallListNested(Box::new(result.pop().unwrap()))
+                        .with_extrinsic_index(listener.extrinsic_entries.get(&position).copied()),
+                );
             }
         }
         if traces.is_empty() {
@@ -218,6 +230,15 @@ pub struct CallTracerCall {
     pub gas: U256,
     /// Gas used by this context.
     pub gas_used: U256,
+    /// Breakdown of `gas_used`, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_breakdown: Option<GasBreakdown>,
+    /// Storage slots accessed by this context, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<StorageAccess>,
+    /// The precompile handling this context, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precompile: Option<PrecompileCall>,
 
     #[serde(flatten)]
     pub inner: CallTracerInner,
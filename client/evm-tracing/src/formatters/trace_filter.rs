@@ -17,12 +17,21 @@ impl super::ResponseFormatter for Formatter {
     type Listener = Listener;
     type Response = Vec<TransactionTrace>;
 
-    fn format(mut listener: Listener) -> Option<Vec<TransactionTrace>> {
-        // Remove empty BTreeMaps pushed to `entries`.
-        // I.e. InvalidNonce or other pallet_evm::runner exits
-        listener.entries.retain(|x| !x.is_empty());
+    fn format(listener: Listener) -> Option<Vec<TransactionTrace>> {
         let mut traces = Vec::new();
-        for (eth_tx_index, entry) in listener.entries.iter().enumerate() {
+        // Empty BTreeMaps pushed to `entries` have no traces, i.e. InvalidNonce or other
+        // pallet_evm::runner exits, or extrinsics not entering the EVM.
+        let mut eth_tx_index = 0;
+        for (position, entry) in listener.entries.iter().enumerate() {
+            let extrinsic_index = listener.extrinsic_entries.get(&position).copied();
+            let transaction_position = match extrinsic_index {
+                // Not an Ethereum transaction.
+                Some(_) => None,
+                None => {
+                    eth_tx_index += 1;
+                    Some(eth_tx_index - 1)
+                },
+            };
             let mut tx_traces: Vec<_> = entry
                 .iter()
                 .map(|(_, trace)| match trace.inner.clone() {
@@ -52,7 +61,10 @@ impl super::ResponseFormatter for Formatter {
                         trace_address: trace.trace_address.clone(),
                         // Can't be known here, must be inserted upstream.
                         transaction_hash: H256::default(),
-                        transaction_position: eth_tx_index as u32,
+                        transaction_position,
+                        storage: trace.storage.clone(),
+                        precompile: trace.precompile.clone(),
+                        extrinsic_index,
                     },
                     CallInner::Create { init, res } => {
                         TransactionTrace {
@@ -86,7 +98,10 @@ impl super::ResponseFormatter for Formatter {
                             trace_address: trace.trace_address.clone(),
                             // Can't be known here, must be inserted upstream.
                             transaction_hash: H256::default(),
-                            transaction_position: eth_tx_index as u32,
+                            transaction_position,
+                            storage: trace.storage.clone(),
+                            precompile: trace.precompile.clone(),
+                            extrinsic_index,
                         }
                     },
                     CallInner::SelfDestruct { balance, to } => TransactionTrace {
@@ -104,7 +119,10 @@ impl super::ResponseFormatter for Formatter {
                         trace_address: trace.trace_address.clone(),
                         // Can't be known here, must be inserted upstream.
                         transaction_hash: H256::default(),
-                        transaction_position: eth_tx_index as u32,
+                        transaction_position,
+                        storage: trace.storage.clone(),
+                        precompile: trace.precompile.clone(),
+                        extrinsic_index,
                     },
                 })
                 .collect();
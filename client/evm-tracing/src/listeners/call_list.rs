@@ -1,13 +1,17 @@
 use crate::{
     formatters::blockscout::{BlockscoutCall as Call, BlockscoutCallInner as CallInner},
-    types::{CallResult, CallType, ContextType, CreateResult},
+    types::{
+        CallResult, CallType, ContextType, CreateResult, GasBreakdown, PrecompileCall,
+        PrecompileDispatch, StorageAccess, StorageWrite, SubstrateEvent,
+    },
 };
-use ethereum_types::{H160, U256};
+use ethereum_types::{H160, H256, U256};
 use primitives_evm_tracing_events::{
+    gasometer::Snapshot,
     runtime::{Capture, ExitError, ExitReason, ExitSucceed},
     Event, EvmEvent, GasometerEvent, Listener as ListenerT, RuntimeEvent, StepEventFilter,
 };
-use std::{collections::btree_map::BTreeMap, vec, vec::Vec};
+use std::{collections::btree_map::BTreeMap, time::Instant, vec, vec::Vec};
 
 /// Enum of the different "modes" of tracer for multiple runtime versions and
 /// the kind of EVM events that are emitted.
@@ -34,6 +38,10 @@ pub struct Listener {
 
     // Final logs.
     pub entries: Vec<BTreeMap<u32, Call>>,
+    /// When tracing a block, the index in the block of the entries which are the trace of a
+    /// Substrate extrinsic instead of an Ethereum transaction, by position in `entries`. Such an
+    /// entry is empty if the extrinsic did not enter the EVM.
+    pub extrinsic_entries: BTreeMap<usize, u32>,
     // Next index to use.
     entries_next_index: u32,
     // Stack of contexts with data to keep between events.
@@ -59,13 +67,33 @@ pub struct Listener {
     step_result_entry: Option<(u32, Call)>,
 
     /// When tracing a block `Event::CallListNew` is emitted before each Ethereum transaction is
-    /// processed. Since we use that event to **finish** the transaction, we must ignore the first
-    /// one.
+    /// processed (`Event::CallListNewExtrinsic` for the other extrinsics). Since we use that event
+    /// to **finish** the transaction, we must ignore the first one.
     call_list_first_transaction: bool,
 
     /// True if only the `GasometerEvent::RecordTransaction` event has been received.
     /// Allow to correctly handle transactions that cannot pay for the tx data in Legacy mode.
     record_transaction_event_only: bool,
+
+    /// When to stop recording the traced runtime call.
+    deadline: Option<Instant>,
+    /// Whether the trace was stopped by its deadline, `error` being set.
+    pub timed_out: bool,
+
+    /// Maximum size of the entries kept in memory, in bytes.
+    memory_limit: usize,
+    memory_used: usize,
+    /// Why the trace stopped early, the following events being ignored.
+    pub error: Option<String>,
+
+    /// Whether to report the breakdown of the gas used by each call frame.
+    gas_breakdown: bool,
+
+    /// Whether to report the storage slots read and written by each call frame.
+    storage: bool,
+
+    /// The runtime events deposited by the traced transaction.
+    pub substrate_events: Vec<SubstrateEvent>,
 }
 
 #[derive(Debug)]
@@ -82,12 +110,31 @@ struct Context {
     gas: u64,
     start_gas: Option<u64>,
 
+    // Memory gas and refund counter of the gasometer of the context.
+    memory_gas: u64,
+    refunded_gas: i64,
+    // Refunds of the storage writes of the context itself.
+    storage_refund: i64,
+
+    // Storage slots accessed by the context, if reported.
+    storage: Option<StorageAccess>,
+
+    // Precompile handling the context, if any.
+    precompile: Option<PrecompileCall>,
+
     // input / data
     data: Vec<u8>,
     // to / create address
     to: H160,
 }
 
+impl Context {
+    fn record_snapshot(&mut self, snapshot: &Snapshot) {
+        self.memory_gas = snapshot.memory_gas;
+        self.refunded_gas = snapshot.refunded_gas;
+    }
+}
+
 impl Default for Listener {
     fn default() -> Self {
         Self {
@@ -95,6 +142,7 @@ impl Default for Listener {
             transaction_cost: 0,
 
             entries: vec![],
+            extrinsic_entries: BTreeMap::new(),
             entries_next_index: 0,
 
             context_stack: vec![],
@@ -104,11 +152,48 @@ impl Default for Listener {
             skip_next_context: false,
             call_list_first_transaction: true,
             record_transaction_event_only: false,
+
+            deadline: None,
+            timed_out: false,
+
+            memory_limit: usize::MAX,
+            memory_used: 0,
+            error: None,
+
+            gas_breakdown: false,
+
+            storage: false,
+
+            substrate_events: vec![],
         }
     }
 }
 
 impl Listener {
+    /// Stops recording the traced runtime call once `deadline` is passed, setting `timed_out`.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Stops the trace once the entries kept in memory exceed `memory_limit` bytes.
+    pub fn with_memory_limit(mut self, memory_limit: usize) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    /// Reports the breakdown of the gas used by each call frame, see [`GasBreakdown`].
+    pub fn with_gas_breakdown(mut self, gas_breakdown: bool) -> Self {
+        self.gas_breakdown = gas_breakdown;
+        self
+    }
+
+    /// Reports the storage slots read and written by each call frame, see [`StorageAccess`].
+    pub fn with_storage(mut self, storage: bool) -> Self {
+        self.storage = storage;
+        self
+    }
+
     pub fn using<R, F: FnOnce() -> R>(&mut self, f: F) -> R {
         primitives_evm_tracing_events::using(self, f)
     }
@@ -128,6 +213,7 @@ impl Listener {
             if context.entries_index == 0 {
                 gas_used += self.transaction_cost;
             }
+            let gas_breakdown = self.gas_breakdown(&context, gas_used);
 
             let entry = match context.context_type {
                 ContextType::Call(call_type) => {
@@ -142,6 +228,9 @@ impl Listener {
                         value: context.value,
                         gas: context.gas.into(),
                         gas_used: gas_used.into(),
+                        gas_breakdown: gas_breakdown.clone(),
+                        storage: context.storage.clone(),
+                        precompile: context.precompile.clone(),
                         inner: CallInner::Call {
                             call_type,
                             to: context.to,
@@ -161,6 +250,9 @@ impl Listener {
                         subtraces: context.subtraces,
                         gas: context.gas.into(),
                         gas_used: gas_used.into(),
+                        gas_breakdown: gas_breakdown.clone(),
+                        storage: context.storage.clone(),
+                        precompile: context.precompile.clone(),
                         from: context.from,
                         inner: CallInner::Create { init: context.data.into(), res },
                     }
@@ -187,6 +279,9 @@ impl Listener {
                 value: 0.into(),
                 gas: 0.into(),
                 gas_used: 0.into(),
+                gas_breakdown: None,
+                storage: None,
+                precompile: None,
                 inner: CallInner::Call {
                     call_type: CallType::Call,
                     to: H160::repeat_byte(0),
@@ -200,16 +295,55 @@ impl Listener {
         }
     }
 
+    /// Starts the entry of the next transaction of a block, or of the Substrate extrinsic at
+    /// `extrinsic_index`.
+    fn new_transaction(&mut self, extrinsic_index: Option<u32>) {
+        if !self.call_list_first_transaction {
+            self.finish_transaction();
+            self.skip_next_context = false;
+            self.entries.push(BTreeMap::new());
+        } else {
+            self.call_list_first_transaction = false;
+            // Pushed right away rather than with the first call, which an extrinsic may never
+            // make, for the positions of the entries to match the transactions.
+            if self.entries.is_empty() {
+                self.entries.push(BTreeMap::new());
+            }
+        }
+        if let Some(index) = extrinsic_index {
+            self.extrinsic_entries.insert(self.entries.len() - 1, index);
+        }
+    }
+
     pub fn gasometer_event(&mut self, event: GasometerEvent) {
         match event {
             GasometerEvent::RecordCost { snapshot, .. } |
-            GasometerEvent::RecordDynamicCost { snapshot, .. } |
             GasometerEvent::RecordStipend { snapshot, .. } => {
                 if let Some(context) = self.context_stack.last_mut() {
                     if context.start_gas.is_none() {
                         context.start_gas = Some(snapshot.gas());
                     }
                     context.gas = snapshot.gas();
+                    context.record_snapshot(&snapshot);
+                }
+            },
+            GasometerEvent::RecordDynamicCost { memory_gas, gas_refund, snapshot, .. } => {
+                if let Some(context) = self.context_stack.last_mut() {
+                    if context.start_gas.is_none() {
+                        context.start_gas = Some(snapshot.gas());
+                    }
+                    context.gas = snapshot.gas();
+                    // The snapshot is taken before recording the cost.
+                    context.memory_gas = snapshot.memory_gas.max(memory_gas);
+                    context.refunded_gas = snapshot.refunded_gas + gas_refund;
+                    context.storage_refund += gas_refund;
+                }
+            },
+            GasometerEvent::RecordRefund { refund, snapshot } => {
+                if let Some(context) = self.context_stack.last_mut() {
+                    // The snapshot is taken before recording the refund.
+                    context.memory_gas = snapshot.memory_gas;
+                    context.refunded_gas = snapshot.refunded_gas + refund;
                 }
             },
             GasometerEvent::RecordTransaction { cost, .. } => {
@@ -247,12 +381,44 @@ impl Listener {
                     }
                 }
             },
+            RuntimeEvent::SLoad { address, index, value } if self.storage => {
+                if let Some(context) = self.context_stack.last_mut() {
+                    context
+                        .storage
+                        .get_or_insert_with(|| StorageAccess::new(address))
+                        .reads
+                        .entry(index)
+                        .or_insert(value);
+                }
+            },
+            RuntimeEvent::SStore { address, index, value } if self.storage => {
+                self.storage_write(address, index, value, None);
+            },
+            RuntimeEvent::SStoreWithPrevious { address, index, value, previous }
+                if self.storage =>
+            {
+                self.storage_write(address, index, value, Some(previous));
+            },
             // We ignore other kinds of message if any (new ones may be added in the future).
             #[allow(unreachable_patterns)]
             _ => (),
         }
     }
 
+    /// Records the write of the storage `index` of `address` by the current frame, over the
+    /// `previous` value if known. Only the first value overwritten by the frame is kept.
+    fn storage_write(&mut self, address: H160, index: H256, value: H256, previous: Option<H256>) {
+        if let Some(context) = self.context_stack.last_mut() {
+            context
+                .storage
+                .get_or_insert_with(|| StorageAccess::new(address))
+                .writes
+                .entry(index)
+                .and_modify(|write| write.to = value)
+                .or_insert(StorageWrite { from: previous, to: value });
+        }
+    }
+
     pub fn evm_event(&mut self, event: EvmEvent) {
         match event {
             EvmEvent::TransactCall { caller, address, value, data, .. } => {
@@ -271,6 +437,14 @@ impl Listener {
                     gas: 0,
                     start_gas: None,
 
+                    memory_gas: 0,
+                    refunded_gas: 0,
+                    storage_refund: 0,
+
+                    storage: None,
+
+                    precompile: None,
+
                     data,
                     to: address,
                 });
@@ -295,6 +469,14 @@ impl Listener {
                     gas: 0,
                     start_gas: None,
 
+                    memory_gas: 0,
+                    refunded_gas: 0,
+                    storage_refund: 0,
+
+                    storage: None,
+
+                    precompile: None,
+
                     data: init_code,
                     to: address,
                 });
@@ -319,6 +501,14 @@ impl Listener {
                     gas: 0,
                     start_gas: None,
 
+                    memory_gas: 0,
+                    refunded_gas: 0,
+                    storage_refund: 0,
+
+                    storage: None,
+
+                    precompile: None,
+
                     data: init_code,
                     to: address,
                 });
@@ -359,6 +549,14 @@ impl Listener {
                         gas: 0,
                         start_gas: None,
 
+                        memory_gas: 0,
+                        refunded_gas: 0,
+                        storage_refund: 0,
+
+                        storage: None,
+
+                        precompile: None,
+
                         data: input.to_vec(),
                         to: context.address,
                     });
@@ -402,6 +600,14 @@ impl Listener {
                         gas: 0,
                         start_gas: None,
 
+                        memory_gas: 0,
+                        refunded_gas: 0,
+                        storage_refund: 0,
+
+                        storage: None,
+
+                        precompile: None,
+
                         data: init_code.to_vec(),
                         to: address,
                     });
@@ -421,10 +627,7 @@ impl Listener {
                     vec![]
                 };
 
-                if self.entries.is_empty() {
-                    self.entries.push(BTreeMap::new());
-                }
-                self.entries.last_mut().unwrap().insert(
+                self.insert_entry(
                     self.entries_next_index,
                     Call {
                         from: address, // this contract is self destructing
@@ -433,6 +636,9 @@ impl Listener {
                         value: 0.into(),
                         gas: 0.into(),
                         gas_used: 0.into(),
+                        gas_breakdown: None,
+                        storage: None,
+                        precompile: None,
                         inner: CallInner::SelfDestruct { to: target, balance },
                     },
                 );
@@ -452,13 +658,77 @@ impl Listener {
                     self.insert_entry(key, entry);
                 }
             },
+            EvmEvent::PrecompileSubcall { code_address, is_static, context, .. } => {
+                // The precompile executes no opcode, so the type of its subcall is not known from
+                // a trap and must be set before the `Call` event following this one.
+                self.call_type = Some(if code_address != context.address {
+                    CallType::DelegateCall
+                } else if is_static {
+                    CallType::StaticCall
+                } else {
+                    CallType::Call
+                });
+            },
+            EvmEvent::Precompile { code_address } => {
+                if let Some(context) = self.context_stack.last_mut() {
+                    context.precompile = Some(PrecompileCall::new(code_address));
+                }
+            },
+            EvmEvent::PrecompileFunction { signature } => {
+                if let Some(precompile) =
+                    self.context_stack.last_mut().and_then(|context| context.precompile.as_mut())
+                {
+                    precompile.selector = Some(signature);
+                }
+            },
+            EvmEvent::PrecompileDispatch { pallet, call, gas_cost, error } => {
+                if let Some(precompile) =
+                    self.context_stack.last_mut().and_then(|context| context.precompile.as_mut())
+                {
+                    precompile.dispatches.push(PrecompileDispatch {
+                        pallet,
+                        call,
+                        gas_cost: gas_cost.map(Into::into),
+                        error,
+                    });
+                }
+            },
             // We ignore other kinds of message if any (new ones may be added in the future).
             #[allow(unreachable_patterns)]
             _ => (),
         }
     }
 
+    fn gas_breakdown(&self, context: &Context, gas_used: u64) -> Option<GasBreakdown> {
+        if !self.gas_breakdown {
+            return None
+        }
+        let root = context.entries_index == 0;
+        let intrinsic_gas = if root { self.transaction_cost } else { 0 };
+        Some(GasBreakdown {
+            intrinsic_gas: root.then(|| intrinsic_gas.into()),
+            execution_gas: gas_used
+                .saturating_sub(intrinsic_gas)
+                .saturating_sub(context.memory_gas)
+                .into(),
+            memory_gas: context.memory_gas.into(),
+            storage_refund: context.storage_refund,
+            refund: context.refunded_gas,
+            // EIP-3529: the refund is capped to a fifth of the gas used by the transaction.
+            refund_cap: root.then(|| (gas_used / 5).into()),
+        })
+    }
+
     fn insert_entry(&mut self, key: u32, entry: Call) {
+        self.memory_used = self.memory_used.saturating_add(call_size(&entry));
+        if self.memory_used > self.memory_limit {
+            self.error =
+                Some(format!("The trace exceeds the memory limit of {} bytes", self.memory_limit));
+            // Free the memory right away, the trace is dropped anyway.
+            self.entries = vec![];
+            return
+        }
+
         if self.entries.is_empty() {
             self.entries.push(BTreeMap::new());
         }
@@ -476,6 +746,7 @@ impl Listener {
             if context.entries_index == 0 {
                 gas_used += self.transaction_cost;
             }
+            let gas_breakdown = self.gas_breakdown(&context, gas_used);
 
             Some((
                 context.entries_index,
@@ -501,6 +772,9 @@ impl Listener {
                             value: context.value,
                             gas: context.gas.into(),
                             gas_used: gas_used.into(),
+                            gas_breakdown: gas_breakdown.clone(),
+                            storage: context.storage.clone(),
+                            precompile: context.precompile.clone(),
                             inner: CallInner::Call {
                                 call_type,
                                 to: context.to,
@@ -530,6 +804,9 @@ impl Listener {
                             subtraces: context.subtraces,
                             gas: context.gas.into(),
                             gas_used: gas_used.into(),
+                            gas_breakdown: gas_breakdown.clone(),
+                            storage: context.storage.clone(),
+                            precompile: context.precompile.clone(),
                             from: context.from,
                             inner: CallInner::Create { init: context.data.into(), res },
                         }
@@ -562,21 +839,41 @@ fn error_message(error: &ExitError) -> Vec<u8> {
     .to_vec()
 }
 
+/// The approximate size of a call entry in memory, in bytes.
+pub fn call_size(call: &Call) -> usize {
+    let data = match &call.inner {
+        CallInner::Call { input, res: CallResult::Output(output), .. } => {
+            input.0.len() + output.0.len()
+        },
+        CallInner::Call { input, res: CallResult::Error(error), .. } => input.0.len() + error.len(),
+        CallInner::Create { init, res: CreateResult::Success { created_contract_code, .. } } => {
+            init.0.len() + created_contract_code.0.len()
+        },
+        CallInner::Create { init, res: CreateResult::Error { error } } => {
+            init.0.len() + error.len()
+        },
+        CallInner::SelfDestruct { .. } => 0,
+    };
+    let storage = call
+        .storage
+        .as_ref()
+        .map_or(0, |storage| storage.reads.len() * 64 + storage.writes.len() * 96);
+    std::mem::size_of::<Call>() + call.trace_address.len() * 4 + data + storage
+}
+
 impl ListenerT for Listener {
     fn event(&mut self, event: Event) {
+        super::check_deadline(self.deadline, &mut self.error, &mut self.timed_out);
+        if self.error.is_some() {
+            return
+        }
         match event {
             Event::Gasometer(gasometer_event) => self.gasometer_event(gasometer_event),
             Event::Runtime(runtime_event) => self.runtime_event(runtime_event),
             Event::Evm(evm_event) => self.evm_event(evm_event),
-            Event::CallListNew() => {
-                if !self.call_list_first_transaction {
-                    self.finish_transaction();
-                    self.skip_next_context = false;
-                    self.entries.push(BTreeMap::new());
-                } else {
-                    self.call_list_first_transaction = false;
-                }
-            },
+            Event::CallListNew() => self.new_transaction(None),
+            Event::CallListNewExtrinsic(index) => self.new_transaction(Some(index)),
+            Event::Substrate(substrate_event) => self.substrate_events.push(substrate_event.into()),
         };
     }
 
@@ -1034,4 +1331,304 @@ mod tests {
         // nested.
         assert_eq!(listener.entries[0].len(), (depth * (subdepth + 1)) + 1);
     }
+
+    #[test]
+    fn gas_breakdown() {
+        let snapshot = |used_gas, memory_gas, refunded_gas| Snapshot {
+            gas_limit: 100_000,
+            memory_gas,
+            used_gas,
+            refunded_gas,
+        };
+        let mut listener = Listener::default().with_gas_breakdown(true);
+        do_transact_call_event(&mut listener);
+        listener.gasometer_event(GasometerEvent::RecordTransaction {
+            cost: 21_000,
+            snapshot: test_snapshot(),
+        });
+        listener.gasometer_event(GasometerEvent::RecordCost {
+            cost: 3,
+            snapshot: snapshot(21_000, 0, 0),
+        });
+        listener.gasometer_event(GasometerEvent::RecordDynamicCost {
+            gas_cost: 5_000,
+            memory_gas: 6,
+            gas_refund: 4_800,
+            snapshot: snapshot(21_003, 0, 0),
+        });
+        listener.gasometer_event(GasometerEvent::RecordCost {
+            cost: 3,
+            snapshot: snapshot(26_003, 6, 4_800),
+        });
+        listener.evm_event(test_emit_evm_event(
+            TestEvmEvent::Exit,
+            false,
+            Some(ExitReason::Succeed(ExitSucceed::Stopped)),
+        ));
+        listener.finish_transaction();
+
+        let entry = &listener.entries[0][&0];
+        assert_eq!(entry.gas_used, 26_009.into());
+        assert_eq!(
+            entry.gas_breakdown,
+            Some(GasBreakdown {
+                intrinsic_gas: Some(21_000.into()),
+                execution_gas: 5_003.into(),
+                memory_gas: 6.into(),
+                storage_refund: 4_800,
+                refund: 4_800,
+                refund_cap: Some(5_201.into()),
+            })
+        );
+    }
+
+    #[test]
+    fn memory_limit_stops_the_trace() {
+        let mut listener = Listener::default().with_memory_limit(1);
+        do_transact_call_event(&mut listener);
+        do_gasometer_event(&mut listener);
+        do_evm_call_event(&mut listener);
+        do_exit_event(&mut listener);
+        listener.finish_transaction();
+        assert!(listener.error.is_some());
+        assert!(listener.entries.is_empty());
+    }
+
+    #[test]
+    fn deadline_stops_the_trace() {
+        let mut listener = Listener::default().with_deadline(Some(Instant::now()));
+        do_transact_call_event(&mut listener);
+        do_gasometer_event(&mut listener);
+        do_evm_call_event(&mut listener);
+        do_exit_event(&mut listener);
+        listener.finish_transaction();
+        assert!(listener.timed_out);
+        assert!(listener.error.is_some());
+        assert!(listener.entries.is_empty());
+    }
+
+    #[test]
+    fn storage_access() {
+        let slot = H256::repeat_byte(1);
+        let value = |byte| H256::repeat_byte(byte);
+        let mut listener = Listener::default().with_storage(true);
+        do_transact_call_event(&mut listener);
+        do_gasometer_event(&mut listener);
+        // The call of the transaction, in the context of `TransactCall`.
+        do_evm_call_event(&mut listener);
+        listener.runtime_event(RuntimeEvent::SLoad {
+            address: H160::default(),
+            index: slot,
+            value: value(0xa),
+        });
+        // A failed subcall overwriting the slot.
+        do_evm_call_event(&mut listener);
+        listener.runtime_event(RuntimeEvent::SStoreWithPrevious {
+            address: H160::default(),
+            index: slot,
+            value: value(0xb),
+            previous: value(0xa),
+        });
+        listener.evm_event(test_emit_evm_event(
+            TestEvmEvent::Exit,
+            false,
+            Some(ExitReason::Error(ExitError::OutOfGas)),
+        ));
+        // The write of the subcall is reverted, the first write of the frame is reported.
+        listener.runtime_event(RuntimeEvent::SStoreWithPrevious {
+            address: H160::default(),
+            index: slot,
+            value: value(0xc),
+            previous: value(0xa),
+        });
+        listener.runtime_event(RuntimeEvent::SStoreWithPrevious {
+            address: H160::default(),
+            index: slot,
+            value: value(0xd),
+            previous: value(0xc),
+        });
+        listener.evm_event(test_emit_evm_event(
+            TestEvmEvent::Exit,
+            false,
+            Some(ExitReason::Succeed(ExitSucceed::Stopped)),
+        ));
+        listener.finish_transaction();
+
+        let storage = |index| listener.entries[0][&index].storage.clone().unwrap();
+        assert_eq!(storage(0).reads.get(&slot), Some(&value(0xa)));
+        assert_eq!(
+            storage(0).writes.get(&slot),
+            Some(&StorageWrite { from: Some(value(0xa)), to: value(0xd) })
+        );
+        assert!(storage(1).reads.is_empty());
+        assert_eq!(
+            storage(1).writes.get(&slot),
+            Some(&StorageWrite { from: Some(value(0xa)), to: value(0xb) })
+        );
+    }
+
+    #[test]
+    fn precompile_call() {
+        let precompile = H160::from_low_u64_be(1024);
+        let input = vec![0xa9, 0x05, 0x9c, 0xbb, 0x01];
+        let mut listener = Listener::default();
+        do_transact_call_event(&mut listener);
+        do_gasometer_event(&mut listener);
+        do_evm_call_event(&mut listener);
+        // A call of the precompile.
+        listener.evm_event(EvmEvent::Call {
+            code_address: precompile,
+            transfer: None,
+            input: input.clone(),
+            target_gas: None,
+            is_static: false,
+            context: EvmContext { address: precompile, ..test_context() },
+        });
+        listener.evm_event(EvmEvent::Precompile { code_address: precompile });
+        listener.evm_event(EvmEvent::PrecompileFunction {
+            signature: b"transfer(address,uint256)".to_vec(),
+        });
+        listener.evm_event(EvmEvent::PrecompileDispatch {
+            pallet: b"Assets".to_vec(),
+            call: b"transfer".to_vec(),
+            gas_cost: None,
+            error: Some(b"BalanceLow".to_vec()),
+        });
+        listener.evm_event(EvmEvent::PrecompileDispatch {
+            pallet: b"Assets".to_vec(),
+            call: b"transfer".to_vec(),
+            gas_cost: Some(100),
+            error: None,
+        });
+        // A delegate call of the precompile.
+        listener.evm_event(EvmEvent::PrecompileSubcall {
+            code_address: H160::repeat_byte(0xc),
+            transfer: None,
+            input: vec![],
+            target_gas: None,
+            is_static: false,
+            context: EvmContext { address: precompile, ..test_context() },
+        });
+        do_evm_call_event(&mut listener);
+        for _ in 0..3 {
+            listener.evm_event(test_emit_evm_event(
+                TestEvmEvent::Exit,
+                false,
+                Some(ExitReason::Succeed(ExitSucceed::Returned)),
+            ));
+        }
+        listener.finish_transaction();
+
+        let entries = &listener.entries[0];
+        assert_eq!(entries[&0].precompile, None);
+        assert_eq!(
+            entries[&1].precompile,
+            Some(PrecompileCall {
+                address: precompile,
+                selector: Some(b"transfer(address,uint256)".to_vec()),
+                dispatches: vec![
+                    PrecompileDispatch {
+                        pallet: b"Assets".to_vec(),
+                        call: b"transfer".to_vec(),
+                        gas_cost: None,
+                        error: Some(b"BalanceLow".to_vec()),
+                    },
+                    PrecompileDispatch {
+                        pallet: b"Assets".to_vec(),
+                        call: b"transfer".to_vec(),
+                        gas_cost: Some(100.into()),
+                        error: None,
+                    },
+                ],
+            })
+        );
+        assert_eq!(entries[&2].precompile, None);
+        assert!(matches!(
+            entries[&2].inner,
+            CallInner::Call { call_type: CallType::DelegateCall, .. }
+        ));
+    }
+
+    #[test]
+    fn substrate_events() {
+        let who = H160::repeat_byte(0xa);
+        let mut listener = Listener::default();
+        listener.event(Event::Substrate(primitives_evm_tracing_events::SubstrateEvent::Withdraw {
+            who,
+            amount: 21_000.into(),
+        }));
+        listener.event(Event::Substrate(primitives_evm_tracing_events::SubstrateEvent::Other {
+            pallet_index: 7,
+            data: vec![1, 2],
+        }));
+
+        assert_eq!(
+            listener.substrate_events,
+            vec![
+                SubstrateEvent::Withdraw { who, amount: 21_000.into() },
+                SubstrateEvent::Other { pallet_index: 7, data: vec![1, 2].into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn block_extrinsics() {
+        let call = |listener: &mut Listener| {
+            do_transact_call_event(listener);
+            do_gasometer_event(listener);
+            do_evm_call_event(listener);
+            do_exit_event(listener);
+        };
+        let mut listener = Listener::default();
+        // An extrinsic which does not enter the EVM, i.e. the timestamp.
+        listener.event(Event::CallListNewExtrinsic(0));
+        listener.event(Event::CallListNew());
+        call(&mut listener);
+        // I.e. `pallet_evm::call` through `Privilege::sudo`.
+        listener.event(Event::CallListNewExtrinsic(2));
+        call(&mut listener);
+        listener.finish_transaction();
+
+        assert_eq!(listener.entries.len(), 3);
+        assert!(listener.entries[0].is_empty());
+        assert_eq!(listener.entries[1].len(), 1);
+        assert_eq!(listener.entries[2].len(), 1);
+        assert_eq!(listener.extrinsic_entries, BTreeMap::from([(0, 0), (2, 2)]));
+
+        use crate::{
+            formatters::{CallTracer, ResponseFormatter},
+            types::single::TransactionTrace,
+        };
+        let traces = CallTracer::format(listener).unwrap();
+        assert_eq!(traces.len(), 2);
+        assert!(matches!(traces[0], TransactionTrace::CallListNested(_)));
+        assert!(matches!(traces[1], TransactionTrace::Extrinsic { extrinsic_index: 2, .. }));
+    }
+
+    #[test]
+    fn block_extrinsics_transaction_positions() {
+        let call = |listener: &mut Listener| {
+            do_transact_call_event(listener);
+            do_gasometer_event(listener);
+            do_evm_call_event(listener);
+            do_exit_event(listener);
+        };
+        let mut listener = Listener::default();
+        listener.event(Event::CallListNew());
+        call(&mut listener);
+        // I.e. `pallet_evm::call` through `Privilege::sudo`, between two Ethereum transactions.
+        listener.event(Event::CallListNewExtrinsic(1));
+        call(&mut listener);
+        listener.event(Event::CallListNew());
+        call(&mut listener);
+        listener.finish_transaction();
+
+        use crate::formatters::{ResponseFormatter, TraceFilter};
+        let traces = TraceFilter::format(listener).unwrap();
+        let positions: Vec<_> = traces
+            .iter()
+            .map(|trace| (trace.transaction_position, trace.extrinsic_index))
+            .collect();
+        assert_eq!(positions, vec![(Some(0), None), (None, Some(1)), (Some(1), None)]);
+    }
 }
@@ -3,3 +3,20 @@ pub mod raw;
 
 pub use call_list::Listener as CallList;
 pub use raw::Listener as Raw;
+
+use std::time::Instant;
+
+/// Whether `deadline` is set and passed.
+pub fn deadline_exceeded(deadline: Option<Instant>) -> bool {
+    deadline.map_or(false, |deadline| Instant::now() >= deadline)
+}
+
+/// Stops recording a trace once `deadline` is passed, setting its `error` and `timed_out`. The
+/// traced runtime call can't be aborted from the host function the event is received in, it runs
+/// to its end without being recorded.
+fn check_deadline(deadline: Option<Instant>, error: &mut Option<String>, timed_out: &mut bool) {
+    if error.is_none() && deadline_exceeded(deadline) {
+        *error = Some("The trace exceeds its timeout".into());
+        *timed_out = true;
+    }
+}
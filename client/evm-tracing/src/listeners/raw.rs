@@ -1,13 +1,30 @@
 use ethereum_types::{H160, H256};
-use std::{collections::btree_map::BTreeMap, vec, vec::Vec};
+use std::{collections::btree_map::BTreeMap, time::Instant, vec, vec::Vec};
 
-use crate::types::{convert_memory, single::RawStepLog, ContextType};
+use crate::types::{
+    convert_memory,
+    single::{RawStepLog, TransactionTrace},
+    ContextType, SubstrateEvent,
+};
 use primitives_evm_tracing_events::{
     runtime::{Capture, ExitReason},
     Event, GasometerEvent, Listener as ListenerT, RuntimeEvent, StepEventFilter,
 };
 
-#[derive(Debug)]
+/// Receives the step logs and the results of the transactions as the listener produces them,
+/// instead of the listener keeping them in memory.
+pub trait RawSink {
+    /// A step log of the current transaction. An error stops the trace.
+    fn step_log(&mut self, step_log: RawStepLog) -> Result<(), String>;
+
+    /// The end of the current transaction of a block. An error stops the trace.
+    fn transaction_result(&mut self, gas: u64, return_value: Vec<u8>) -> Result<(), String>;
+
+    /// The start of the next transaction of a block, or of the Substrate extrinsic at
+    /// `extrinsic_index` which has no result if it does not enter the EVM.
+    fn new_transaction(&mut self, _extrinsic_index: Option<u32>) {}
+}
+
 pub struct Listener {
     disable_storage: bool,
     disable_memory: bool,
@@ -19,6 +36,45 @@ pub struct Listener {
     pub step_logs: Vec<RawStepLog>,
     pub return_value: Vec<u8>,
     pub final_gas: u64,
+
+    /// The traces of the finished transactions when tracing a block.
+    pub traces: Vec<TransactionTrace>,
+    /// When tracing a block `Event::CallListNew` is emitted before each Ethereum transaction,
+    /// finishing the previous one if any.
+    block_transaction: bool,
+    /// The index of the Substrate extrinsic being traced instead of an Ethereum transaction, see
+    /// `Event::CallListNewExtrinsic`.
+    extrinsic_index: Option<u32>,
+    /// Whether the current transaction entered the EVM, which an extrinsic may not do.
+    entered_evm: bool,
+
+    /// Where the step logs and the transaction results go, if streamed.
+    sink: Option<Box<dyn RawSink>>,
+    /// Maximum size of the step logs kept in memory, in bytes.
+    memory_limit: usize,
+    memory_used: usize,
+    /// Why the trace stopped early, the following events being ignored.
+    pub error: Option<String>,
+    /// When to stop recording the traced runtime call.
+    deadline: Option<Instant>,
+    /// Whether the trace was stopped by its deadline, `error` being set.
+    pub timed_out: bool,
+
+    /// The runtime events deposited by the traced transaction.
+    pub substrate_events: Vec<SubstrateEvent>,
+}
+
+impl std::fmt::Debug for Listener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Listener")
+            .field("step_logs", &self.step_logs.len())
+            .field("traces", &self.traces.len())
+            .field("streamed", &self.sink.is_some())
+            .field("memory_used", &self.memory_used)
+            .field("error", &self.error)
+            .field("timed_out", &self.timed_out)
+            .finish()
+    }
 }
 
 #[derive(Debug)]
@@ -60,6 +116,104 @@ impl Listener {
 
             new_context: false,
             context_stack: vec![],
+
+            traces: vec![],
+            block_transaction: false,
+            extrinsic_index: None,
+            entered_evm: false,
+
+            sink: None,
+            memory_limit: usize::MAX,
+            memory_used: 0,
+            error: None,
+            deadline: None,
+            timed_out: false,
+
+            substrate_events: vec![],
+        }
+    }
+
+    /// Stops the trace once the step logs kept in memory exceed `memory_limit` bytes.
+    pub fn with_memory_limit(mut self, memory_limit: usize) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    /// Stops recording the traced runtime call once `deadline` is passed, setting `timed_out`.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Streams the step logs and the transaction results to `sink` instead of keeping them.
+    pub fn with_sink(mut self, sink: Box<dyn RawSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Called at the end of a block trace, to finish its last transaction.
+    pub fn finish_transaction(&mut self) {
+        if !self.block_transaction || self.error.is_some() {
+            return
+        }
+        self.block_transaction = false;
+        self.new_context = false;
+        self.context_stack.clear();
+        let extrinsic_index = self.extrinsic_index.take();
+        if !std::mem::take(&mut self.entered_evm) && extrinsic_index.is_some() {
+            // Nothing to report for an extrinsic which did not enter the EVM.
+            return
+        }
+
+        let gas = self.final_gas;
+        let return_value = std::mem::take(&mut self.return_value);
+        let step_logs = std::mem::take(&mut self.step_logs);
+        self.final_gas = 0;
+        match &mut self.sink {
+            Some(sink) => {
+                if let Err(error) = sink.transaction_result(gas, return_value) {
+                    self.error = Some(error);
+                }
+            },
+            None => self.traces.push(
+                TransactionTrace::Raw { gas: gas.into(), return_value, step_logs }
+                    .with_extrinsic_index(extrinsic_index),
+            ),
+        }
+    }
+
+    /// Starts the trace of the next transaction of a block, or of the Substrate extrinsic at
+    /// `extrinsic_index`.
+    fn new_transaction(&mut self, extrinsic_index: Option<u32>) {
+        self.finish_transaction();
+        self.block_transaction = true;
+        self.extrinsic_index = extrinsic_index;
+        if let Some(sink) = &mut self.sink {
+            sink.new_transaction(extrinsic_index);
+        }
+    }
+
+    fn push_step_log(&mut self, step_log: RawStepLog) {
+        match &mut self.sink {
+            Some(sink) => {
+                if let Err(error) = sink.step_log(step_log) {
+                    self.error = Some(error);
+                }
+            },
+            None => {
+                self.memory_used = self.memory_used.saturating_add(step_log_size(&step_log));
+                if self.memory_used > self.memory_limit {
+                    self.error = Some(format!(
+                        "The trace exceeds the memory limit of {} bytes",
+                        self.memory_limit
+                    ));
+                    // Free the memory right away, the trace is dropped anyway.
+                    self.step_logs = vec![];
+                    self.traces = vec![];
+                } else {
+                    self.step_logs.push(step_log);
+                }
+            },
         }
     }
 
@@ -73,6 +227,7 @@ impl Listener {
                 // First event of a transaction.
                 // Next step will be the first context.
                 self.new_context = true;
+                self.entered_evm = true;
                 self.final_gas = cost;
             },
             GasometerEvent::RecordCost { cost, snapshot } => {
@@ -159,7 +314,7 @@ impl Listener {
                             Some(context.storage_cache.clone())
                         };
 
-                        self.step_logs.push(RawStepLog {
+                        self.push_step_log(RawStepLog {
                             depth: depth.into(),
                             gas: gas.into(),
                             gas_cost: gas_cost.into(),
@@ -226,7 +381,8 @@ impl Listener {
                 }
             },
             RuntimeEvent::SLoad { address: _, index, value } |
-            RuntimeEvent::SStore { address: _, index, value } => {
+            RuntimeEvent::SStore { address: _, index, value } |
+            RuntimeEvent::SStoreWithPrevious { address: _, index, value, .. } => {
                 if let Some(context) = self.context_stack.last_mut() {
                     if !self.disable_storage {
                         context.storage_cache.insert(index, value);
@@ -240,11 +396,27 @@ impl Listener {
     }
 }
 
+/// The approximate size of a step log in memory, in bytes.
+pub fn step_log_size(step_log: &RawStepLog) -> usize {
+    std::mem::size_of::<RawStepLog>() +
+        step_log.op.len() +
+        step_log.memory.as_ref().map_or(0, |memory| memory.len() * 32) +
+        step_log.stack.as_ref().map_or(0, |stack| stack.len() * 32) +
+        step_log.storage.as_ref().map_or(0, |storage| storage.len() * 64)
+}
+
 impl ListenerT for Listener {
     fn event(&mut self, event: Event) {
+        super::check_deadline(self.deadline, &mut self.error, &mut self.timed_out);
+        if self.error.is_some() {
+            return
+        }
         match event {
             Event::Gasometer(e) => self.gasometer_event(e),
             Event::Runtime(e) => self.runtime_event(e),
+            Event::CallListNew() => self.new_transaction(None),
+            Event::CallListNewExtrinsic(index) => self.new_transaction(Some(index)),
+            Event::Substrate(e) => self.substrate_events.push(e.into()),
             _ => {},
         };
     }
@@ -253,3 +425,132 @@ impl ListenerT for Listener {
         StepEventFilter { enable_memory: !self.disable_memory, enable_stack: !self.disable_stack }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives_evm_tracing_events::{gasometer::Snapshot, Context as EvmContext};
+    use std::{cell::RefCell, rc::Rc};
+
+    fn record_transaction() -> Event {
+        Event::Gasometer(GasometerEvent::RecordTransaction {
+            cost: 21_000,
+            snapshot: Snapshot { gas_limit: 0, memory_gas: 0, used_gas: 0, refunded_gas: 0 },
+        })
+    }
+
+    fn step() -> Event {
+        Event::Runtime(RuntimeEvent::Step {
+            context: EvmContext {
+                address: H160::default(),
+                caller: H160::default(),
+                apparent_value: Default::default(),
+            },
+            opcode: b"Stop".to_vec(),
+            position: Ok(0),
+            stack: None,
+            memory: None,
+        })
+    }
+
+    fn step_result() -> Event {
+        Event::Runtime(RuntimeEvent::StepResult { result: Ok(()), return_value: vec![] })
+    }
+
+    fn trace_block(listener: &mut Listener, steps: &[usize]) {
+        for steps in steps {
+            listener.event(Event::CallListNew());
+            listener.event(record_transaction());
+            for _ in 0..*steps {
+                listener.event(step());
+                listener.event(step_result());
+            }
+        }
+        listener.finish_transaction();
+    }
+
+    #[test]
+    fn block_trace_is_split_by_transaction() {
+        let mut listener = Listener::new(true, true, true);
+        trace_block(&mut listener, &[2, 0, 1]);
+        assert!(listener.error.is_none());
+        let steps = listener
+            .traces
+            .iter()
+            .map(|trace| match trace {
+                TransactionTrace::Raw { step_logs, .. } => step_logs.len(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(steps, vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn block_trace_labels_the_extrinsics() {
+        let mut listener = Listener::new(true, true, true);
+        // An extrinsic which does not enter the EVM, i.e. the timestamp.
+        listener.event(Event::CallListNewExtrinsic(0));
+        trace_block(&mut listener, &[1]);
+        listener.event(Event::CallListNewExtrinsic(2));
+        listener.event(record_transaction());
+        listener.event(step());
+        listener.event(step_result());
+        listener.finish_transaction();
+        assert!(listener.error.is_none());
+        assert_eq!(listener.traces.len(), 2);
+        assert!(matches!(listener.traces[0], TransactionTrace::Raw { .. }));
+        match &listener.traces[1] {
+            TransactionTrace::Extrinsic { extrinsic_index: 2, trace } => match &**trace {
+                TransactionTrace::Raw { step_logs, .. } => assert_eq!(step_logs.len(), 1),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn memory_limit_stops_the_trace() {
+        let mut listener = Listener::new(true, true, true).with_memory_limit(1);
+        trace_block(&mut listener, &[2, 1]);
+        assert!(listener.error.is_some());
+        assert!(listener.step_logs.is_empty());
+        assert!(listener.traces.is_empty());
+    }
+
+    struct TestSink(Rc<RefCell<Vec<String>>>);
+
+    impl RawSink for TestSink {
+        fn step_log(&mut self, _step_log: RawStepLog) -> Result<(), String> {
+            self.0.borrow_mut().push("step".into());
+            Ok(())
+        }
+
+        fn transaction_result(&mut self, gas: u64, _return_value: Vec<u8>) -> Result<(), String> {
+            self.0.borrow_mut().push(format!("result {}", gas));
+            if self.0.borrow().len() > 3 {
+                return Err("closed".into())
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sink_receives_the_trace_as_it_is_produced() {
+        let received = Rc::new(RefCell::new(vec![]));
+        let mut listener =
+            Listener::new(true, true, true).with_sink(Box::new(TestSink(received.clone())));
+        trace_block(&mut listener, &[1, 1, 1]);
+        assert_eq!(*received.borrow(), vec!["step", "result 21000", "step", "result 21000"]);
+        assert_eq!(listener.error, Some("closed".into()));
+        assert!(listener.traces.is_empty());
+    }
+
+    #[test]
+    fn deadline_stops_the_trace() {
+        let mut listener = Listener::new(true, true, true).with_deadline(Some(Instant::now()));
+        trace_block(&mut listener, &[1, 1]);
+        assert!(listener.timed_out);
+        assert!(listener.error.is_some());
+        assert!(listener.traces.is_empty());
+    }
+}
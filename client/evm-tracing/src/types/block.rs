@@ -20,7 +20,19 @@ pub struct TransactionTrace {
     pub trace_address: Vec<u32>,
     #[serde(serialize_with = "h256_0x_serialize")]
     pub transaction_hash: H256,
-    pub transaction_position: u32,
+    /// The position of the Ethereum transaction in the block, `null` for the calls of the
+    /// Substrate extrinsics.
+    pub transaction_position: Option<u32>,
+    /// Storage slots accessed by the call, if recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<super::StorageAccess>,
+    /// The precompile handling the call, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precompile: Option<super::PrecompileCall>,
+    /// The index in the block of the Substrate extrinsic of the call, if it is not an Ethereum
+    /// transaction, in which case `transactionHash` is zero.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extrinsic_index: Option<u32>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, Serialize)]
@@ -1,7 +1,8 @@
 //! Runtime API allowing to debug/trace Ethereum
 
 use codec::{Decode, Encode};
-use ethereum_types::{H160, H256};
+use ethereum_types::{H160, H256, U256};
+use std::collections::BTreeMap;
 
 pub mod block;
 pub mod replay;
@@ -38,6 +39,157 @@ pub enum CreateResult {
     },
 }
 
+/// The breakdown of the gas used by a call frame, `gasUsed` being the sum of the intrinsic, the
+/// execution and the memory gas.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Encode, Decode, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasBreakdown {
+    /// Cost of the transaction itself, on the root frame only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intrinsic_gas: Option<U256>,
+    /// Gas charged by the opcodes of the frame, including the gas used by its subcalls.
+    pub execution_gas: U256,
+    /// Gas charged by the memory expansion of the frame.
+    pub memory_gas: U256,
+    /// Refunds of the storage writes of the frame, which may be negative.
+    pub storage_refund: i64,
+    /// Refund counter of the frame when it exits, including the refunds of its successful
+    /// subcalls.
+    pub refund: i64,
+    /// Maximum refund of the transaction (EIP-3529), on the root frame only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund_cap: Option<U256>,
+}
+
+/// The storage slots read and written by a call frame.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageAccess {
+    /// The account whose storage is accessed, the caller for a delegate call.
+    pub address: H160,
+    /// The slots read, with the value first read by the frame.
+    pub reads: BTreeMap<H256, H256>,
+    /// The slots written.
+    pub writes: BTreeMap<H256, StorageWrite>,
+}
+
+impl StorageAccess {
+    pub fn new(address: H160) -> Self {
+        Self { address, reads: BTreeMap::new(), writes: BTreeMap::new() }
+    }
+}
+
+/// The value of a storage slot before and after the writes of a call frame.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, Serialize)]
+pub struct StorageWrite {
+    /// The value before the first write of the frame, reported by the runtime, only unknown for
+    /// runtimes which do not report it.
+    pub from: Option<H256>,
+    /// The value of the last write of the frame.
+    pub to: H256,
+}
+
+/// A call frame handled by a precompile of the runtime, whose gas charged is the `gasUsed` of the
+/// frame.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrecompileCall {
+    /// The address of the precompile, which is not the `to` of a delegate call.
+    pub address: H160,
+    /// The Solidity signature of the function called, decoded from the selector of the input by
+    /// the precompile, if it has one and the precompile knows its functions.
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "option_string_serialize")]
+    pub selector: Option<Vec<u8>>,
+    /// The Substrate calls dispatched by the precompile, in order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dispatches: Vec<PrecompileDispatch>,
+}
+
+impl PrecompileCall {
+    pub fn new(address: H160) -> Self {
+        Self { address, selector: None, dispatches: vec![] }
+    }
+}
+
+/// A Substrate call dispatched by a precompile.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrecompileDispatch {
+    #[serde(serialize_with = "string_serialize")]
+    pub pallet: Vec<u8>,
+    #[serde(serialize_with = "string_serialize")]
+    pub call: Vec<u8>,
+    /// Gas charged for the weight of the call, none if it failed or could not be charged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_cost: Option<U256>,
+    /// The error of the call, if it failed.
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "option_string_serialize")]
+    pub error: Option<Vec<u8>>,
+}
+
+/// A runtime event deposited by a traced transaction, see
+/// [`primitives_evm_tracing_events::SubstrateEvent`].
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, Serialize)]
+#[serde(tag = "event")]
+pub enum SubstrateEvent {
+    #[serde(rename_all = "camelCase")]
+    Endowed {
+        account: H160,
+        free_balance: U256,
+    },
+    DustLost {
+        account: H160,
+        amount: U256,
+    },
+    Transfer {
+        from: H160,
+        to: H160,
+        amount: U256,
+    },
+    Reserved {
+        who: H160,
+        amount: U256,
+    },
+    Unreserved {
+        who: H160,
+        amount: U256,
+    },
+    Deposit {
+        who: H160,
+        amount: U256,
+    },
+    Withdraw {
+        who: H160,
+        amount: U256,
+    },
+    Slashed {
+        who: H160,
+        amount: U256,
+    },
+    #[serde(rename_all = "camelCase")]
+    Other {
+        pallet_index: u8,
+        data: Bytes,
+    },
+}
+
+impl From<primitives_evm_tracing_events::SubstrateEvent> for SubstrateEvent {
+    fn from(event: primitives_evm_tracing_events::SubstrateEvent) -> Self {
+        use primitives_evm_tracing_events::SubstrateEvent as Event;
+        match event {
+            Event::Endowed { account, free_balance } => Self::Endowed { account, free_balance },
+            Event::DustLost { account, amount } => Self::DustLost { account, amount },
+            Event::Transfer { from, to, amount } => Self::Transfer { from, to, amount },
+            Event::Reserved { who, amount } => Self::Reserved { who, amount },
+            Event::Unreserved { who, amount } => Self::Unreserved { who, amount },
+            Event::Deposit { who, amount } => Self::Deposit { who, amount },
+            Event::Withdraw { who, amount } => Self::Withdraw { who, amount },
+            Event::Slashed { who, amount } => Self::Slashed { who, amount },
+            Event::Other { pallet_index, data } => Self::Other { pallet_index, data: data.into() },
+        }
+    }
+}
+
 #[derive(Clone, Copy, Eq, PartialEq, Debug, Encode, Decode, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CallType {
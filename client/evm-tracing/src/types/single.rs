@@ -44,6 +44,36 @@ pub enum TransactionTrace {
     CallList(Vec<Call>),
     /// Used by Geth's callTracer.
     CallListNested(Box<Call>),
+    /// A trace along with the runtime events deposited by the transaction.
+    #[serde(rename_all = "camelCase")]
+    WithSubstrateEvents {
+        trace: Box<TransactionTrace>,
+        substrate_events: Vec<super::SubstrateEvent>,
+    },
+    /// The trace of a Substrate extrinsic of a block which is not an Ethereum transaction but
+    /// entered the EVM, with its index in the block.
+    #[serde(rename_all = "camelCase")]
+    Extrinsic { extrinsic_index: u32, trace: Box<TransactionTrace> },
+}
+
+impl TransactionTrace {
+    /// Labels the trace with the index of its Substrate extrinsic, if it is not the trace of an
+    /// Ethereum transaction.
+    pub fn with_extrinsic_index(self, extrinsic_index: Option<u32>) -> Self {
+        match extrinsic_index {
+            Some(extrinsic_index) => Self::Extrinsic { extrinsic_index, trace: Box::new(self) },
+            None => self,
+        }
+    }
+}
+
+/// What a trace of a block traces: an Ethereum transaction, or a Substrate extrinsic which
+/// entered the EVM.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceTarget {
+    TransactionHash(H256),
+    ExtrinsicIndex(u32),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Encode, Decode, Serialize)]
@@ -74,3 +104,34 @@ pub struct RawStepLog {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub storage: Option<BTreeMap<H256, H256>>,
 }
+
+/// A part of a block trace streamed to the client as the tracing goes.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum TraceChunk {
+    /// Step logs of a transaction, following its previous ones.
+    #[serde(rename_all = "camelCase")]
+    StepLogs {
+        #[serde(flatten)]
+        target: TraceTarget,
+        step_logs: Vec<RawStepLog>,
+    },
+    /// The end of the raw trace of a transaction.
+    #[serde(rename_all = "camelCase")]
+    Result {
+        #[serde(flatten)]
+        target: TraceTarget,
+        gas: U256,
+        #[serde(with = "hex")]
+        return_value: Vec<u8>,
+    },
+    /// The whole trace of a transaction, for the call list tracers.
+    #[serde(rename_all = "camelCase")]
+    Trace {
+        #[serde(flatten)]
+        target: TraceTarget,
+        result: TransactionTrace,
+    },
+    /// The trace stopped early, it is the last chunk.
+    Error { message: String },
+}
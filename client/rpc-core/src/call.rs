@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use ethereum_types::{H160, H256, U256};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::Deserialize;
+
+// Frontier
+use fc_rpc_core::types::{BlockNumber, Bytes, CallRequest};
+
+/// Override of the state of an account, for the duration of a call.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct StateOverride {
+    /// Fake balance to set for the account before executing the call.
+    pub balance: Option<U256>,
+    /// Fake nonce to set for the account before executing the call.
+    pub nonce: Option<U256>,
+    /// Fake EVM bytecode to inject into the account before executing the call.
+    pub code: Option<Bytes>,
+    /// Fake key-value mapping to override all slots in the account storage.
+    pub state: Option<BTreeMap<H256, H256>>,
+    /// Fake key-value mapping to override individual slots in the account storage.
+    pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// The state overrides of a call, by account.
+pub type StateOverrides = BTreeMap<H160, StateOverride>;
+
+/// Override of the block a call is executed in.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlockOverrides {
+    pub number: Option<U256>,
+    /// Timestamp in seconds.
+    #[serde(alias = "timestamp")]
+    pub time: Option<U256>,
+    #[serde(alias = "feeRecipient")]
+    pub coinbase: Option<H160>,
+    #[serde(alias = "baseFeePerGas")]
+    pub base_fee: Option<U256>,
+}
+
+/// `eth_call` and `eth_estimateGas` supporting the Geth overrides, replacing the Frontier ones.
+#[rpc(server)]
+pub trait EthCallApi {
+    /// Call contract, returning the output data.
+    ///
+    /// For details, see [eth_call](https://geth.ethereum.org/docs/rpc/ns-eth#eth_call)
+    #[method(name = "eth_call")]
+    fn call(
+        &self,
+        request: CallRequest,
+        number: Option<BlockNumber>,
+        state_overrides: Option<StateOverrides>,
+        block_overrides: Option<BlockOverrides>,
+    ) -> RpcResult<Bytes>;
+
+    /// Estimate the gas needed for execution of the call.
+    #[method(name = "eth_estimateGas")]
+    fn estimate_gas(
+        &self,
+        request: CallRequest,
+        number: Option<BlockNumber>,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<U256>;
+}
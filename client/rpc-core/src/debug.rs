@@ -15,6 +15,19 @@ pub struct TraceParams {
     /// Javascript tracer (we just check if it's Blockscout tracer string)
     pub tracer: Option<String>,
     pub timeout: Option<String>,
+    /// Options of the call list tracers.
+    pub tracer_config: Option<TracerConfig>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TracerConfig {
+    /// Report the breakdown of the gas used by each call frame.
+    pub with_gas_breakdown: bool,
+    /// Report the storage slots read and written by each call frame.
+    pub with_storage: bool,
+    /// Report the runtime events deposited by a traced transaction along with its trace.
+    pub with_substrate_events: bool,
 }
 
 #[rpc(server)]
@@ -52,4 +65,15 @@ pub trait DebugApi {
         transaction_hash: H256,
         params: Option<TraceParams>,
     ) -> RpcResult<single::TransactionTrace>;
+
+    /// Same as `debug_traceBlockByNumber`, but the trace is sent in chunks as the block is traced
+    /// instead of buffering the whole of it: the step logs of the raw tracer by batches, followed
+    /// by the result of each transaction, or the trace of each transaction for the call list
+    /// tracers. An `error` chunk ends the trace early.
+    #[subscription(
+        name = "debug_subscribeTraceBlock" => "debug_traceBlockChunk",
+        unsubscribe = "debug_unsubscribeTraceBlock",
+        item = single::TraceChunk
+    )]
+    fn subscribe_trace_block(&self, id: RequestBlockId, params: Option<TraceParams>);
 }
@@ -0,0 +1,63 @@
+use ethereum_types::{H160, H256, U256};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+// Frontier
+use fc_rpc_core::types::Bytes;
+
+/// Hardhat/Anvil compatible methods to control a development node.
+///
+/// For details, see [Hardhat network methods](https://hardhat.org/hardhat-network/docs/reference#hardhat-network-methods)
+/// and [Anvil custom methods](https://book.getfoundry.sh/reference/anvil/#custom-methods).
+#[rpc(server)]
+#[async_trait]
+pub trait DevApi {
+    /// Seal a new block, with the given timestamp (in seconds) if any.
+    #[method(name = "evm_mine")]
+    async fn mine(&self, timestamp: Option<U256>) -> RpcResult<String>;
+
+    /// Increase the timestamp of the next blocks by the given number of seconds.
+    /// Returns the total time offset in seconds.
+    #[method(name = "evm_increaseTime")]
+    fn increase_time(&self, seconds: U256) -> RpcResult<U256>;
+
+    /// Set the timestamp (in seconds) of the next block, the following blocks being relative to
+    /// it.
+    #[method(name = "evm_setNextBlockTimestamp")]
+    fn set_next_block_timestamp(&self, timestamp: U256) -> RpcResult<()>;
+
+    /// Snapshot the state of the chain at the current block. Returns the id of the snapshot.
+    #[method(name = "evm_snapshot")]
+    fn snapshot(&self) -> RpcResult<U256>;
+
+    /// Revert the chain to the given snapshot, which is removed as well as the later ones.
+    /// Returns `false` if the snapshot doesn't exist.
+    ///
+    /// The transactions of the reverted blocks are dropped, and the filters and subscriptions
+    /// created after the snapshot are not reverted.
+    #[method(name = "evm_revert")]
+    fn revert(&self, id: U256) -> RpcResult<bool>;
+
+    /// Set the balance of the given account.
+    #[method(name = "anvil_setBalance", aliases = ["hardhat_setBalance"])]
+    async fn set_balance(&self, address: H160, balance: U256) -> RpcResult<()>;
+
+    /// Set the code of the given account.
+    #[method(name = "anvil_setCode", aliases = ["hardhat_setCode"])]
+    async fn set_code(&self, address: H160, code: Bytes) -> RpcResult<()>;
+
+    /// Set a storage slot of the given account.
+    #[method(name = "anvil_setStorageAt", aliases = ["hardhat_setStorageAt"])]
+    async fn set_storage_at(&self, address: H160, slot: U256, value: H256) -> RpcResult<bool>;
+
+    /// Allow to send transactions from the given account without its key, using
+    /// `eth_sendTransaction`.
+    #[method(name = "anvil_impersonateAccount", aliases = ["hardhat_impersonateAccount"])]
+    fn impersonate_account(&self, address: H160) -> RpcResult<()>;
+
+    /// Stop impersonating the given account.
+    #[method(
+        name = "anvil_stopImpersonatingAccount",
+        aliases = ["hardhat_stopImpersonatingAccount"]
+    )]
+    fn stop_impersonating_account(&self, address: H160) -> RpcResult<()>;
+}
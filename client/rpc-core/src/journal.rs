@@ -0,0 +1,13 @@
+use ethereum_types::H256;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+// Frontier
+use fc_rpc_core::types::Bytes;
+
+/// `eth_sendRawTransaction`, recording the submitted transactions in the local journal.
+#[rpc(server)]
+pub trait EthJournalApi {
+    /// Sends a signed transaction, journaled until it is included in a block.
+    #[method(name = "eth_sendRawTransaction")]
+    async fn send_raw_transaction(&self, bytes: Bytes) -> RpcResult<H256>;
+}
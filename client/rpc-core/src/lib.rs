@@ -1,9 +1,19 @@
+mod call;
 mod debug;
+mod dev;
+mod journal;
+mod proof;
+mod simulate;
 mod tracing;
 mod txpool;
 mod types;
 
+pub use call::*;
 pub use debug::*;
+pub use dev::*;
+pub use journal::*;
+pub use proof::*;
+pub use simulate::*;
 pub use tracing::*;
 pub use txpool::*;
 pub use types::*;
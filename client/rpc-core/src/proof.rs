@@ -0,0 +1,62 @@
+use ethereum_types::{H160, H256, U256};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::Serialize;
+
+// Frontier
+use fc_rpc_core::types::{BlockNumber, Bytes};
+
+/// The proof of an EVM account, against the Substrate state root of a block.
+///
+/// The EVM state is not stored in Ethereum account and storage tries but in the Substrate state
+/// trie, so the proofs are Substrate read proofs: the trie nodes on the paths of the storage keys
+/// of `frame_system::Account`, `pallet_evm::AccountCodes` and `pallet_evm::AccountStorages`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub address: H160,
+    pub balance: U256,
+    pub nonce: U256,
+    /// The keccak hash of the code of the account.
+    pub code_hash: H256,
+    /// The state root of the block, all the storage items share the Substrate state trie.
+    pub storage_hash: H256,
+    /// The trie nodes proving the `frame_system::Account` and `pallet_evm::AccountCodes` entries.
+    pub account_proof: Vec<Bytes>,
+    pub storage_proof: Vec<StorageProof>,
+    /// The Substrate hash of the block the proofs are against.
+    pub block_hash: H256,
+    /// The Substrate state root of the block.
+    pub state_root: H256,
+    /// The storage key of the `frame_system::Account` entry.
+    pub account_key: Bytes,
+    /// The storage key of the `pallet_evm::AccountCodes` entry.
+    pub code_key: Bytes,
+}
+
+/// The proof of a storage slot of an EVM account.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageProof {
+    pub key: H256,
+    pub value: H256,
+    /// The trie nodes proving the `pallet_evm::AccountStorages` entry.
+    pub proof: Vec<Bytes>,
+    /// The storage key of the `pallet_evm::AccountStorages` entry.
+    pub storage_key: Bytes,
+}
+
+/// EIP-1186 proofs of the EVM state.
+#[rpc(server)]
+pub trait EthProofApi {
+    /// Returns the account and storage values of the specified account including the Substrate
+    /// read proofs.
+    ///
+    /// For details, see [EIP-1186](https://eips.ethereum.org/EIPS/eip-1186)
+    #[method(name = "eth_getProof")]
+    fn get_proof(
+        &self,
+        address: H160,
+        storage_keys: Vec<H256>,
+        number: Option<BlockNumber>,
+    ) -> RpcResult<AccountProof>;
+}
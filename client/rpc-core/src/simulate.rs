@@ -0,0 +1,89 @@
+use ethereum_types::{H160, H256, U256, U64};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
+
+// Frontier
+use fc_rpc_core::types::{BlockNumber, Bytes, CallRequest, Log};
+
+use crate::{BlockOverrides, StateOverrides};
+
+/// The `eth_simulateV1` request.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SimulatePayload {
+    /// The simulated blocks, executed in order on top of each other.
+    pub block_state_calls: Vec<SimulateBlock>,
+    /// Add a log for each ether transfer, as an ERC-20 `Transfer` of the
+    /// `0xeeee...eeee` address.
+    #[serde(default)]
+    pub trace_transfers: bool,
+    /// Check the nonces, balances and fees as for a transaction.
+    #[serde(default)]
+    pub validation: bool,
+    /// Not supported, the simulated blocks have no transactions.
+    #[serde(default)]
+    pub return_full_transactions: bool,
+}
+
+/// A simulated block.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SimulateBlock {
+    pub block_overrides: Option<BlockOverrides>,
+    /// Applied before the calls of the block.
+    pub state_overrides: Option<StateOverrides>,
+    #[serde(default)]
+    pub calls: Vec<CallRequest>,
+}
+
+/// The result of a simulated block.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBlock {
+    pub number: U256,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub timestamp: U256,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub fee_recipient: H160,
+    pub base_fee_per_gas: U256,
+    pub calls: Vec<SimulatedCallResult>,
+}
+
+/// The result of a simulated call.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedCallResult {
+    pub return_data: Bytes,
+    pub logs: Vec<Log>,
+    pub gas_used: U256,
+    /// `1` on success, `0` on failure.
+    pub status: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<SimulateCallError>,
+}
+
+/// The error of a failed simulated call.
+#[derive(Clone, Debug, Serialize)]
+pub struct SimulateCallError {
+    /// `3` for a revert, `-32015` for another EVM error.
+    pub code: i32,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
+}
+
+/// Multi-call simulation across virtual blocks.
+#[rpc(server)]
+pub trait EthSimulateApi {
+    /// Simulate the calls of the given blocks on top of the state at `number`.
+    ///
+    /// For details, see [eth_simulateV1](https://github.com/ethereum/execution-apis/pull/484)
+    #[method(name = "eth_simulateV1")]
+    fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        number: Option<BlockNumber>,
+    ) -> RpcResult<Vec<SimulatedBlock>>;
+}
@@ -1,5 +1,5 @@
-use ethereum::{TransactionAction, TransactionV2 as EthereumTransaction};
-use ethereum_types::{H160, H256, U256};
+use ethereum::{AccessListItem, TransactionAction, TransactionV2 as EthereumTransaction};
+use ethereum_types::{H160, H256, U256, U64};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use serde::{Deserialize, Serialize, Serializer};
 
@@ -26,11 +26,12 @@ pub trait Get {
 }
 
 /// The exact details of the transaction currently pending for inclusion in the next block(s).
+///
+/// The fields of the typed transactions are only set for their types, as Geth does.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Content {
     /// Block hash
-    #[serde(serialize_with = "block_hash_serialize")]
     pub block_hash: Option<H256>,
     /// Block number
     pub block_number: Option<U256>,
@@ -40,8 +41,7 @@ pub struct Content {
     pub transaction_index: Option<U256>,
     /// Sender
     pub from: H160,
-    /// Recipient
-    #[serde(serialize_with = "to_serialize")]
+    /// Recipient, `None` for a contract creation
     pub to: Option<H160>,
     /// Nonce
     pub nonce: U256,
@@ -49,76 +49,82 @@ pub struct Content {
     pub value: U256,
     /// Gas
     pub gas: U256,
-    /// Gas price
+    /// Gas price, the max fee per gas of an EIP-1559 transaction
     pub gas_price: U256,
+    /// Max fee per gas of an EIP-1559 transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    /// Max priority fee per gas of an EIP-1559 transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
     /// Data
     pub input: Bytes,
-}
-
-fn block_hash_serialize<S>(hash: &Option<H256>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    serializer.serialize_str(&format!("0x{:x}", hash.unwrap_or_default()))
-}
-
-fn to_serialize<S>(hash: &Option<H160>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    serializer.serialize_str(&format!("0x{:x}", hash.unwrap_or_default()))
+    /// Transaction type: 0 for Legacy, 1 for EIP-2930 and 2 for EIP-1559
+    #[serde(rename = "type")]
+    pub transaction_type: U64,
+    /// Access list of an EIP-2930 or EIP-1559 transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<Vec<AccessListItem>>,
+    /// Chain id, `None` for a Legacy transaction without EIP-155 replay protection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<U64>,
+    /// The V field of the signature, the y parity of a typed transaction
+    pub v: U64,
+    /// The R field of the signature
+    pub r: U256,
+    /// The S field of the signature
+    pub s: U256,
 }
 
 impl Get for Content {
     fn get(hash: H256, from: H160, txn: &EthereumTransaction) -> Self {
+        let content = Self { hash, from, ..Default::default() };
         match txn {
             EthereumTransaction::Legacy(txn) => Self {
-                block_hash: None,
-                block_number: None,
-                hash,
-                transaction_index: None,
-                from,
-                to: match txn.action {
-                    TransactionAction::Call(to) => Some(to),
-                    _ => None,
-                },
+                to: action_to(&txn.action),
                 nonce: txn.nonce,
                 value: txn.value,
                 gas: txn.gas_limit,
                 gas_price: txn.gas_price,
                 input: Bytes(txn.input.clone()),
+                transaction_type: U64::zero(),
+                chain_id: txn.signature.chain_id().map(U64::from),
+                v: U64::from(txn.signature.v()),
+                r: U256::from_big_endian(txn.signature.r().as_bytes()),
+                s: U256::from_big_endian(txn.signature.s().as_bytes()),
+                ..content
             },
             EthereumTransaction::EIP2930(txn) => Self {
-                block_hash: None,
-                block_number: None,
-                hash,
-                transaction_index: None,
-                from,
-                to: match txn.action {
-                    TransactionAction::Call(to) => Some(to),
-                    _ => None,
-                },
+                to: action_to(&txn.action),
                 nonce: txn.nonce,
                 value: txn.value,
                 gas: txn.gas_limit,
                 gas_price: txn.gas_price,
                 input: Bytes(txn.input.clone()),
+                transaction_type: U64::one(),
+                access_list: Some(txn.access_list.clone()),
+                chain_id: Some(U64::from(txn.chain_id)),
+                v: U64::from(txn.odd_y_parity as u64),
+                r: U256::from_big_endian(txn.r.as_bytes()),
+                s: U256::from_big_endian(txn.s.as_bytes()),
+                ..content
             },
             EthereumTransaction::EIP1559(txn) => Self {
-                block_hash: None,
-                block_number: None,
-                hash,
-                transaction_index: None,
-                from,
-                to: match txn.action {
-                    TransactionAction::Call(to) => Some(to),
-                    _ => None,
-                },
+                to: action_to(&txn.action),
                 nonce: txn.nonce,
                 value: txn.value,
                 gas: txn.gas_limit,
                 gas_price: txn.max_fee_per_gas,
+                max_fee_per_gas: Some(txn.max_fee_per_gas),
+                max_priority_fee_per_gas: Some(txn.max_priority_fee_per_gas),
                 input: Bytes(txn.input.clone()),
+                transaction_type: U64::from(2),
+                access_list: Some(txn.access_list.clone()),
+                chain_id: Some(U64::from(txn.chain_id)),
+                v: U64::from(txn.odd_y_parity as u64),
+                r: U256::from_big_endian(txn.r.as_bytes()),
+                s: U256::from_big_endian(txn.s.as_bytes()),
+                ..content
             },
         }
     }
@@ -132,6 +138,37 @@ impl Get for Content {
     }
 }
 
+fn action_to(action: &TransactionAction) -> Option<H160> {
+    match action {
+        TransactionAction::Call(to) => Some(*to),
+        TransactionAction::Create => None,
+    }
+}
+
+/// A change of the Ethereum transactions of the pool.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolTransactionEvent {
+    pub kind: PoolEventKind,
+    pub transaction: Content,
+    /// The hash of the replacing transaction of a `replaced` transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<H256>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PoolEventKind {
+    /// The transaction was added to the pool.
+    Added,
+    /// The transaction was replaced by another one with the same sender and nonce.
+    Replaced,
+    /// The transaction was removed from the pool after its nonce was used by a block.
+    Mined,
+    /// The transaction was removed from the pool without being mined, e.g. invalid or evicted.
+    Dropped,
+}
+
 /// The textual summary of the transaction currently pending for inclusion in the next block(s).
 #[derive(Clone, Debug)]
 pub struct Inspect {
@@ -217,6 +254,17 @@ pub trait TxPoolApi {
     #[method(name = "txpool_content")]
     fn content(&self) -> RpcResult<TxPoolResult<TransactionMap<Content>>>;
 
+    /// The contentFrom inspection property can be queried to list the exact details of all the
+    /// transactions currently pending for inclusion in the next block(s), as well as the ones that
+    /// are being scheduled for future execution only, sent by the given address.
+    ///
+    /// The result is an object with two fields pending and queued. Each of these fields are
+    /// associative arrays, mapping nonces with actual transactions.
+    ///
+    /// For details, see [txpool_contentFrom](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_contentfrom)
+    #[method(name = "txpool_contentFrom")]
+    fn content_from(&self, address: H160) -> RpcResult<TxPoolResult<HashMap<U256, Content>>>;
+
     /// The inspect inspection property can be queried to list a textual summary of all the
     /// transactions currently pending for inclusion in the next block(s), as well as the ones that
     /// are being scheduled for future execution only. This is a method specifically tailored to
@@ -241,4 +289,13 @@ pub trait TxPoolApi {
     /// For details, see [txpool_status](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_status)
     #[method(name = "txpool_status")]
     fn status(&self) -> RpcResult<TxPoolResult<U256>>;
+
+    /// Subscribe to the Ethereum transactions added to, replaced in and removed from the pool,
+    /// optionally only the ones sent by `address`.
+    #[subscription(
+        name = "txpool_subscribe" => "txpool_subscription",
+        unsubscribe = "txpool_unsubscribe",
+        item = PoolTransactionEvent
+    )]
+    fn subscribe(&self, address: Option<H160>);
 }
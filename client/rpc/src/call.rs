@@ -0,0 +1,401 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use ethereum_types::{H160, H256, U256};
+use jsonrpsee::core::RpcResult;
+
+// Substrate
+use sp_api::{ApiRef, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+// Frontier
+use fc_rpc::{err, frontier_backend_client, internal_err};
+use fc_rpc_core::{
+    types::{BlockNumber, Bytes, CallRequest},
+    EthApiServer,
+};
+use fp_evm::{ExitError, ExitReason};
+use fp_rpc::EthereumRuntimeRPCApi;
+
+// Local
+pub use amax_eva_rpc_core::{BlockOverrides, EthCallApiServer, StateOverride, StateOverrides};
+pub use primitives_rpc::call::EthCallRuntimeApi;
+
+/// The gas cost of the cheapest transaction.
+const MIN_GAS_PER_TX: u64 = 21_000;
+/// The error of a request with overrides at a block whose runtime has no `EthCallRuntimeApi`.
+const OVERRIDES_UNSUPPORTED: &str = "state and block overrides are not supported at this block";
+/// The error code of a reverted execution, whose data is the revert data, as Geth and Frontier.
+const EXECUTION_ERROR: i32 = 3;
+
+/// `eth_call` and `eth_estimateGas` with Geth state and block overrides.
+///
+/// The blocks whose runtime has no `EthCallRuntimeApi` are served by Frontier's `eth_call` and
+/// `eth_estimateGas` of `fallback`, without overrides.
+pub struct EthCall<B: BlockT, C, E> {
+    client: Arc<C>,
+    backend: Arc<fc_db::Backend<B>>,
+    execute_gas_limit_multiplier: u64,
+    fallback: E,
+    _marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C, E> EthCall<B, C, E> {
+    pub fn new(
+        client: Arc<C>,
+        backend: Arc<fc_db::Backend<B>>,
+        execute_gas_limit_multiplier: u64,
+        fallback: E,
+    ) -> Self {
+        Self { client, backend, execute_gas_limit_multiplier, fallback, _marker: PhantomData }
+    }
+}
+
+/// The outcome of a call or a create.
+struct Execution {
+    exit_reason: ExitReason,
+    value: Vec<u8>,
+    used_gas: U256,
+}
+
+/// The call parameters, resolved from the request.
+pub(crate) struct CallParams {
+    pub from: H160,
+    pub to: Option<H160>,
+    pub data: Vec<u8>,
+    pub value: U256,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub nonce: Option<U256>,
+    pub access_list: Option<Vec<(H160, Vec<H256>)>>,
+}
+
+impl<B, C, E> EthCall<B, C, E>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<B> + EthCallRuntimeApi<B>,
+{
+    fn block_id(&self, number: Option<BlockNumber>) -> RpcResult<BlockId<B>> {
+        frontier_backend_client::native_block_id::<B, C>(
+            self.client.as_ref(),
+            self.backend.as_ref(),
+            number,
+        )?
+        .ok_or_else(|| internal_err("header not found"))
+    }
+
+    /// Whether the runtime of the block can apply the overrides, else Frontier's path is used.
+    fn has_call_api(&self, id: &BlockId<B>) -> RpcResult<bool> {
+        self.client
+            .runtime_api()
+            .has_api::<dyn EthCallRuntimeApi<B>>(id)
+            .map_err(|err| internal_err(format!("runtime error: {:?}", err)))
+    }
+
+    /// The maximum gas limit of a call, which is a multiple of the block gas limit.
+    fn max_gas_limit(&self, id: &BlockId<B>) -> RpcResult<U256> {
+        let block = self
+            .client
+            .runtime_api()
+            .current_block(id)
+            .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+            .ok_or_else(|| internal_err("block unavailable, cannot query gas limit"))?;
+        Ok(block.header.gas_limit.saturating_mul(self.execute_gas_limit_multiplier.into()))
+    }
+
+    /// Execute the call in a fresh runtime api instance, so that the overrides are discarded.
+    fn execute(
+        api: ApiRef<'_, C::Api>,
+        id: &BlockId<B>,
+        params: &CallParams,
+        gas_limit: U256,
+        estimate: bool,
+        state_overrides: &[primitives_rpc::call::StateOverride],
+        block_overrides: &primitives_rpc::call::BlockOverrides,
+    ) -> RpcResult<Execution> {
+        let execution = match params.to {
+            Some(to) => {
+                let info = api
+                    .call(
+                        id,
+                        params.from,
+                        to,
+                        params.data.clone(),
+                        params.value,
+                        gas_limit,
+                        params.max_fee_per_gas,
+                        params.max_priority_fee_per_gas,
+                        params.nonce,
+                        estimate,
+                        params.access_list.clone(),
+                        state_overrides.to_vec(),
+                        block_overrides.clone(),
+                    )
+                    .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+                    .map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+                Execution {
+                    exit_reason: info.exit_reason,
+                    value: info.value,
+                    used_gas: info.used_gas,
+                }
+            },
+            None => {
+                let info = api
+                    .create(
+                        id,
+                        params.from,
+                        params.data.clone(),
+                        params.value,
+                        gas_limit,
+                        params.max_fee_per_gas,
+                        params.max_priority_fee_per_gas,
+                        params.nonce,
+                        estimate,
+                        params.access_list.clone(),
+                        state_overrides.to_vec(),
+                        block_overrides.clone(),
+                    )
+                    .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+                    .map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+                Execution {
+                    exit_reason: info.exit_reason,
+                    value: info.value.as_bytes().to_vec(),
+                    used_gas: info.used_gas,
+                }
+            },
+        };
+        Ok(execution)
+    }
+
+    /// Binary search the lowest gas limit the call succeeds with, starting from the gas used in
+    /// estimate mode, as Frontier does with `rpc_binary_search_estimate`.
+    fn binary_search_gas(
+        &self,
+        id: &BlockId<B>,
+        params: &CallParams,
+        highest: U256,
+        state_overrides: &[primitives_rpc::call::StateOverride],
+    ) -> RpcResult<U256> {
+        let block_overrides = primitives_rpc::call::BlockOverrides::default();
+        let execution = Self::execute(
+            self.client.runtime_api(),
+            id,
+            params,
+            highest,
+            true,
+            state_overrides,
+            &block_overrides,
+        )?;
+        error_on_execution_failure(&execution.exit_reason, &execution.value)?;
+
+        let mut highest = highest;
+        let mut lowest = U256::from(MIN_GAS_PER_TX);
+        let mut mid = std::cmp::min(execution.used_gas * 3, (highest + lowest) / 2);
+        let mut previous_highest = highest;
+        while highest - lowest > U256::one() {
+            let execution = Self::execute(
+                self.client.runtime_api(),
+                id,
+                params,
+                mid,
+                true,
+                state_overrides,
+                &block_overrides,
+            )?;
+            match execution.exit_reason {
+                ExitReason::Succeed(_) => {
+                    highest = mid;
+                    // Stop once the search space is reduced by less than 10%.
+                    if (previous_highest - highest) * 10 / previous_highest < U256::one() {
+                        return Ok(highest)
+                    }
+                    previous_highest = highest;
+                },
+                ExitReason::Revert(_) | ExitReason::Error(ExitError::OutOfGas) => {
+                    lowest = mid;
+                },
+                other => error_on_execution_failure(&other, &execution.value)?,
+            }
+            mid = (highest + lowest) / 2;
+        }
+        Ok(highest)
+    }
+}
+
+impl<B, C, E> EthCallApiServer for EthCall<B, C, E>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<B> + EthCallRuntimeApi<B>,
+    E: EthApiServer,
+{
+    fn call(
+        &self,
+        request: CallRequest,
+        number: Option<BlockNumber>,
+        state_overrides: Option<StateOverrides>,
+        block_overrides: Option<BlockOverrides>,
+    ) -> RpcResult<Bytes> {
+        let id = self.block_id(number.clone())?;
+        if !self.has_call_api(&id)? {
+            if state_overrides.is_some() || block_overrides.is_some() {
+                return Err(internal_err(OVERRIDES_UNSUPPORTED))
+            }
+            return self.fallback.call(request, number)
+        }
+        let max_gas_limit = self.max_gas_limit(&id)?;
+        let gas_limit = gas_limit(request.gas, max_gas_limit, self.execute_gas_limit_multiplier)?;
+        let params = call_params(request)?;
+        let state_overrides = convert_state_overrides(state_overrides)?;
+        let block_overrides = convert_block_overrides(block_overrides);
+
+        let execution = Self::execute(
+            self.client.runtime_api(),
+            &id,
+            &params,
+            gas_limit,
+            false,
+            &state_overrides,
+            &block_overrides,
+        )?;
+        error_on_execution_failure(&execution.exit_reason, &execution.value)?;
+        Ok(Bytes(execution.value))
+    }
+
+    fn estimate_gas(
+        &self,
+        request: CallRequest,
+        number: Option<BlockNumber>,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<U256> {
+        let id = self.block_id(number.clone())?;
+        if !self.has_call_api(&id)? {
+            if state_overrides.is_some() {
+                return Err(internal_err(OVERRIDES_UNSUPPORTED))
+            }
+            return self.fallback.estimate_gas(request, number)
+        }
+        let max_gas_limit = self.max_gas_limit(&id)?;
+        let gas_limit = gas_limit(request.gas, max_gas_limit, self.execute_gas_limit_multiplier)?;
+        let params = call_params(request)?;
+        let state_overrides = convert_state_overrides(state_overrides)?;
+
+        self.binary_search_gas(&id, &params, gas_limit, &state_overrides)
+    }
+}
+
+fn gas_limit(gas: Option<U256>, max_gas_limit: U256, multiplier: u64) -> RpcResult<U256> {
+    match gas {
+        Some(gas) if gas > max_gas_limit => Err(internal_err(format!(
+            "provided gas limit is too high (can be up to {}x the block gas limit)",
+            multiplier
+        ))),
+        Some(gas) => Ok(gas),
+        None => Ok(max_gas_limit),
+    }
+}
+
+pub(crate) fn call_params(request: CallRequest) -> RpcResult<CallParams> {
+    // A zero gas price is regarded as no gas price, as most tools send it for calls.
+    let gas_price = request.gas_price.filter(|gas_price| !gas_price.is_zero());
+    let (max_fee_per_gas, max_priority_fee_per_gas) =
+        match (gas_price, request.max_fee_per_gas, request.max_priority_fee_per_gas) {
+            (gas_price, None, None) => (gas_price, gas_price),
+            (None, Some(max_fee), max_priority_fee) => {
+                if matches!(max_priority_fee, Some(priority_fee) if priority_fee > max_fee) {
+                    return Err(internal_err(
+                        "maxPriorityFeePerGas cannot be higher than maxFeePerGas",
+                    ))
+                }
+                (Some(max_fee), max_priority_fee)
+            },
+            (None, None, Some(max_priority_fee)) => (None, Some(max_priority_fee)),
+            (Some(_), _, _) => {
+                return Err(internal_err(
+                    "both gasPrice and (maxFeePerGas or maxPriorityFeePerGas) specified",
+                ))
+            },
+        };
+
+    Ok(CallParams {
+        from: request.from.unwrap_or_default(),
+        to: request.to,
+        data: request.data.map(|data| data.0).unwrap_or_default(),
+        value: request.value.unwrap_or_default(),
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        nonce: request.nonce,
+        access_list: request
+            .access_list
+            .map(|list| list.into_iter().map(|item| (item.address, item.storage_keys)).collect()),
+    })
+}
+
+pub(crate) fn convert_state_overrides(
+    state_overrides: Option<StateOverrides>,
+) -> RpcResult<Vec<primitives_rpc::call::StateOverride>> {
+    state_overrides
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(address, account)| {
+            if account.state.is_some() && account.state_diff.is_some() {
+                return Err(internal_err(format!(
+                    "account {:?} has both 'state' and 'stateDiff'",
+                    address
+                )))
+            }
+            Ok(primitives_rpc::call::StateOverride {
+                address,
+                balance: account.balance,
+                nonce: account.nonce,
+                code: account.code.map(|code| code.0),
+                state: account.state.map(|state| state.into_iter().collect()),
+                state_diff: account.state_diff.unwrap_or_default().into_iter().collect(),
+            })
+        })
+        .collect()
+}
+
+fn convert_block_overrides(
+    block_overrides: Option<BlockOverrides>,
+) -> primitives_rpc::call::BlockOverrides {
+    let block_overrides = block_overrides.unwrap_or_default();
+    primitives_rpc::call::BlockOverrides {
+        number: block_overrides.number,
+        timestamp: block_overrides.time,
+        coinbase: block_overrides.coinbase,
+        base_fee: block_overrides.base_fee,
+    }
+}
+
+/// Map a failed execution to an rpc error, decoding the revert reason as Frontier does.
+fn error_on_execution_failure(reason: &ExitReason, data: &[u8]) -> RpcResult<()> {
+    match reason {
+        ExitReason::Succeed(_) => Ok(()),
+        ExitReason::Error(ExitError::OutOfGas) => Err(internal_err("out of gas")),
+        ExitReason::Error(err) => Err(internal_err(format!("evm error: {:?}", err))),
+        ExitReason::Revert(_) => Err(err(EXECUTION_ERROR, revert_message(data), Some(data))),
+        ExitReason::Fatal(err) => Err(internal_err(format!("evm fatal: {:?}", err))),
+    }
+}
+
+/// The message of a reverted execution, with the reason of an `Error(string)` revert.
+pub(crate) fn revert_message(data: &[u8]) -> String {
+    // A `Error(string)` revert: selector, offset, length and the message.
+    const LEN_START: usize = 36;
+    const MESSAGE_START: usize = 68;
+
+    let mut message = "execution reverted".to_string();
+    if data.len() > MESSAGE_START {
+        let message_len = U256::from(&data[LEN_START..MESSAGE_START]).low_u64() as usize;
+        let message_end = MESSAGE_START.saturating_add(message_len);
+        if data.len() >= message_end {
+            let body = &data[MESSAGE_START..message_end];
+            if let Ok(reason) = std::str::from_utf8(body) {
+                message = format!("{}: {}", message, reason);
+            }
+        }
+    }
+    message
+}
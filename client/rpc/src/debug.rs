@@ -1,9 +1,18 @@
-use std::{future::Future, marker::PhantomData, sync::Arc};
+use std::{
+    future::Future,
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use ethereum_types::H256;
-use futures::{SinkExt, StreamExt};
-use jsonrpsee::core::{async_trait, RpcResult};
-use tokio::sync::{oneshot, Semaphore};
+use futures::{FutureExt, SinkExt, StreamExt};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::SubscriptionResult,
+    SubscriptionSink,
+};
+use tokio::sync::{mpsc, oneshot};
 
 // Substrate
 use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
@@ -12,24 +21,63 @@ use sp_api::{BlockId, Core, HeaderT, ProvideRuntimeApi};
 use sp_blockchain::{
     Backend as BlockchainBackend, Error as BlockChainError, HeaderBackend, HeaderMetadata,
 };
+use sp_core::traits::SpawnNamed;
 use sp_runtime::traits::{BlakeTwo256, Block as BlockT, UniqueSaturatedInto};
 
 // Frontier
 use fc_rpc::{frontier_backend_client, internal_err, OverrideHandle};
 
 // Local
-use amax_eva_client_evm_tracing::formatters::ResponseFormatter;
-pub use amax_eva_rpc_core::{single, DebugApiServer, RequestBlockId, RequestBlockTag, TraceParams};
+use crate::{
+    tracing::{block_enters_evm, Priority, TracingScheduler},
+    MetricsLink,
+};
+use amax_eva_client_evm_tracing::{
+    formatters::ResponseFormatter,
+    listeners::raw::{step_log_size, RawSink},
+    types::{
+        single::{RawStepLog, TraceChunk, TraceTarget},
+        SubstrateEvent,
+    },
+};
+pub use amax_eva_rpc_core::{
+    single, DebugApiServer, RequestBlockId, RequestBlockTag, TraceParams, TracerConfig,
+};
 pub use primitives_rpc::debug::{DebugRuntimeApi, TracerInput};
 
+/// Size of the step logs sent in a single chunk by the streamed block traces, in bytes.
+const STREAM_CHUNK_SIZE: usize = 1024 * 1024;
+/// How long a streamed block trace waits for the client to make room for a chunk before it is
+/// aborted, releasing its permit.
+const STREAM_SEND_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often a streamed block trace retries to send a chunk to a client not keeping up.
+const STREAM_SEND_RETRY: Duration = Duration::from_millis(10);
+
 pub enum RequesterInput {
     Transaction(H256),
     Block(RequestBlockId),
+    BlockStream(RequestBlockId),
 }
 
 pub enum Response {
     Single(single::TransactionTrace),
     Block(Vec<single::TransactionTrace>),
+    /// The chunks of a block trace, sent as soon as the tracing is scheduled.
+    Stream(mpsc::Receiver<TraceChunk>),
+    /// The block trace was sent to the stream.
+    Streamed,
+}
+
+/// The limits of the `debug` traces, for the whole node.
+#[derive(Clone, Copy, Debug)]
+pub struct TraceLimits {
+    /// Maximum size of a buffered trace, or of the chunks of a streamed trace waiting for the
+    /// client, in bytes.
+    pub memory: usize,
+    /// Timeout of the traces not setting one.
+    pub timeout: Duration,
+    /// Maximum timeout a trace can set.
+    pub max_timeout: Duration,
 }
 
 pub type Responder = oneshot::Sender<RpcResult<Response>>;
@@ -38,11 +86,17 @@ pub type DebugRequester =
 
 pub struct Debug {
     pub requester: DebugRequester,
+    subscriptions: Arc<dyn SpawnNamed>,
+    metrics: MetricsLink,
 }
 
 impl Debug {
-    pub fn new(requester: DebugRequester) -> Self {
-        Self { requester }
+    pub fn new(
+        requester: DebugRequester,
+        subscriptions: Arc<dyn SpawnNamed>,
+        metrics: MetricsLink,
+    ) -> Self {
+        Self { requester, subscriptions, metrics }
     }
 }
 
@@ -55,6 +109,7 @@ impl DebugApiServer for Debug {
         transaction_hash: H256,
         params: Option<TraceParams>,
     ) -> RpcResult<single::TransactionTrace> {
+        let _timer = self.metrics.trace_request_timer("debug_traceTransaction");
         let mut requester = self.requester.clone();
 
         let (tx, rx) = oneshot::channel();
@@ -80,6 +135,7 @@ impl DebugApiServer for Debug {
         id: RequestBlockId,
         params: Option<TraceParams>,
     ) -> RpcResult<Vec<single::TransactionTrace>> {
+        let _timer = self.metrics.trace_request_timer("debug_traceBlock");
         let mut requester = self.requester.clone();
 
         let (tx, rx) = oneshot::channel();
@@ -89,12 +145,164 @@ impl DebugApiServer for Debug {
         })?;
 
         // Receive a message from the service level task and send the rpc response.
-        rx.await
+        let traces = rx
+            .await
             .map_err(|err| internal_err(format!("debug service dropped the channel : {:?}", err)))?
             .map(|res| match res {
                 Response::Block(res) => res,
                 _ => unreachable!(),
-            })
+            })?;
+        self.metrics.report(|metrics| metrics.traced_blocks.inc());
+        Ok(traces)
+    }
+
+    fn subscribe_trace_block(
+        &self,
+        mut sink: SubscriptionSink,
+        id: RequestBlockId,
+        params: Option<TraceParams>,
+    ) -> SubscriptionResult {
+        sink.accept()?;
+
+        let mut requester = self.requester.clone();
+        let metrics = self.metrics.clone();
+        let fut = async move {
+            // The subscription is timed until the last chunk is sent.
+            let _timer = metrics.trace_request_timer("debug_subscribeTraceBlock");
+            let (tx, rx) = oneshot::channel();
+            // Send a message from the rpc handler to the service level task.
+            let response =
+                match requester.send(((RequesterInput::BlockStream(id), params), tx)).await {
+                    Ok(()) => rx
+                        .await
+                        .map_err(|err| {
+                            internal_err(format!("debug service dropped the channel : {:?}", err))
+                        })
+                        .and_then(|res| res),
+                    Err(err) => Err(internal_err(format!(
+                        "failed to send request to debug service : {:?}",
+                        err
+                    ))),
+                };
+
+            let chunks = match response {
+                Ok(Response::Stream(chunks)) => {
+                    metrics.report(|metrics| metrics.traced_blocks.inc());
+                    futures::stream::unfold(chunks, |mut chunks| async move {
+                        chunks.recv().await.map(|chunk| (chunk, chunks))
+                    })
+                    .boxed()
+                },
+                Ok(_) => unreachable!(),
+                Err(err) => futures::stream::once(futures::future::ready(TraceChunk::Error {
+                    message: err.to_string(),
+                }))
+                .boxed(),
+            };
+            sink.pipe_from_stream(chunks).await;
+        };
+        self.subscriptions
+            .spawn("debug-trace-block-subscription", Some("rpc"), fut.boxed());
+        Ok(())
+    }
+}
+
+/// Sends the step logs of a raw block trace to the subscription by chunks, the channel holding at
+/// most the memory limit of the traces.
+struct ChunkSink {
+    chunks: mpsc::Sender<TraceChunk>,
+    /// The hashes of the Ethereum transactions of the block, in order.
+    transaction_hashes: Vec<H256>,
+    /// Index of the transaction being traced.
+    index: usize,
+    /// Index of the Substrate extrinsic being traced instead, if any.
+    extrinsic_index: Option<u32>,
+    step_logs: Vec<RawStepLog>,
+    size: usize,
+}
+
+impl ChunkSink {
+    fn new(chunks: mpsc::Sender<TraceChunk>, transaction_hashes: Vec<H256>) -> Self {
+        Self {
+            chunks,
+            transaction_hashes,
+            index: 0,
+            extrinsic_index: None,
+            step_logs: vec![],
+            size: 0,
+        }
+    }
+
+    fn target(&self) -> TraceTarget {
+        match self.extrinsic_index {
+            Some(extrinsic_index) => TraceTarget::ExtrinsicIndex(extrinsic_index),
+            None => TraceTarget::TransactionHash(
+                self.transaction_hashes.get(self.index).copied().unwrap_or_default(),
+            ),
+        }
+    }
+
+    fn send(&self, chunk: TraceChunk) -> Result<(), String> {
+        send_chunk(&self.chunks, chunk)
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        if self.step_logs.is_empty() {
+            return Ok(())
+        }
+        self.size = 0;
+        self.send(TraceChunk::StepLogs {
+            target: self.target(),
+            step_logs: std::mem::take(&mut self.step_logs),
+        })
+    }
+}
+
+impl RawSink for ChunkSink {
+    fn step_log(&mut self, step_log: RawStepLog) -> Result<(), String> {
+        self.size += step_log_size(&step_log);
+        self.step_logs.push(step_log);
+        if self.size >= STREAM_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn transaction_result(&mut self, gas: u64, return_value: Vec<u8>) -> Result<(), String> {
+        self.flush()?;
+        self.send(TraceChunk::Result { target: self.target(), gas: gas.into(), return_value })?;
+        if self.extrinsic_index.is_none() {
+            self.index += 1;
+        }
+        Ok(())
+    }
+
+    fn new_transaction(&mut self, extrinsic_index: Option<u32>) {
+        self.extrinsic_index = extrinsic_index;
+    }
+}
+
+/// Sends a chunk of a streamed block trace from the blocking task tracing it, waiting at most
+/// `STREAM_SEND_TIMEOUT` for the client to catch up.
+fn send_chunk(chunks: &mpsc::Sender<TraceChunk>, mut chunk: TraceChunk) -> Result<(), String> {
+    let started = Instant::now();
+    loop {
+        match chunks.try_send(chunk) {
+            Ok(()) => return Ok(()),
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                return Err("The subscription was closed".to_string())
+            },
+            Err(mpsc::error::TrySendError::Full(_)) if started.elapsed() >= STREAM_SEND_TIMEOUT => {
+                return Err(format!(
+                    "The client did not keep up with the trace for {}s",
+                    STREAM_SEND_TIMEOUT.as_secs()
+                ))
+            },
+            Err(mpsc::error::TrySendError::Full(returned)) => {
+                chunk = returned;
+                std::thread::sleep(STREAM_SEND_RETRY);
+            },
+        }
     }
 }
 
@@ -113,13 +321,14 @@ where
     BE::State: StateBackend<BlakeTwo256>,
 {
     /// Task spawned at service level that listens for messages on the rpc channel and spawns
-    /// blocking tasks using a permit pool.
+    /// blocking tasks scheduled ahead of the `trace_filter` backfills.
     pub fn task(
         client: Arc<C>,
         backend: Arc<BE>,
         frontier_backend: Arc<fc_db::Backend<B>>,
-        permit_pool: Arc<Semaphore>,
+        scheduler: TracingScheduler,
         overrides: Arc<OverrideHandle<B>>,
+        limits: TraceLimits,
     ) -> (impl Future<Output = ()>, DebugRequester) {
         let (tx, mut rx): (DebugRequester, _) =
             sc_utils::mpsc::tracing_unbounded("debug-requester");
@@ -134,13 +343,13 @@ where
                         let client = client.clone();
                         let backend = backend.clone();
                         let frontier_backend = frontier_backend.clone();
-                        let permit_pool = permit_pool.clone();
+                        let scheduler = scheduler.clone();
                         let overrides = overrides.clone();
 
                         tokio::task::spawn(async move {
                             let _ = response_tx.send(
                                 async {
-                                    let _permit = permit_pool.acquire().await;
+                                    let _permit = scheduler.acquire(Priority::Interactive).await;
                                     tokio::task::spawn_blocking(move || {
                                         Self::handle_transaction_request(
                                             client.clone(),
@@ -149,6 +358,7 @@ where
                                             transaction_hash,
                                             params,
                                             overrides,
+                                            limits,
                                         )
                                     })
                                     .await
@@ -167,13 +377,13 @@ where
                         let client = client.clone();
                         let backend = backend.clone();
                         let frontier_backend = frontier_backend.clone();
-                        let permit_pool = permit_pool.clone();
+                        let scheduler = scheduler.clone();
                         let overrides = overrides.clone();
 
                         tokio::task::spawn(async move {
                             let _ = response_tx.send(
                                 async {
-                                    let _permit = permit_pool.acquire().await;
+                                    let _permit = scheduler.acquire(Priority::Interactive).await;
 
                                     tokio::task::spawn_blocking(move || {
                                         Self::handle_block_request(
@@ -183,6 +393,8 @@ where
                                             request_block_id,
                                             params,
                                             overrides,
+                                            limits,
+                                            None,
                                         )
                                     })
                                     .await
@@ -197,6 +409,52 @@ where
                             );
                         });
                     },
+                    Some((
+                        (RequesterInput::BlockStream(request_block_id), params),
+                        response_tx,
+                    )) => {
+                        let client = client.clone();
+                        let backend = backend.clone();
+                        let frontier_backend = frontier_backend.clone();
+                        let scheduler = scheduler.clone();
+                        let overrides = overrides.clone();
+
+                        // The chunks waiting for the client hold at most the memory limit.
+                        let (chunks_tx, chunks_rx) =
+                            mpsc::channel((limits.memory / STREAM_CHUNK_SIZE).max(1));
+                        if response_tx.send(Ok(Response::Stream(chunks_rx))).is_err() {
+                            continue
+                        }
+
+                        tokio::task::spawn(async move {
+                            let permit = scheduler.acquire(Priority::Interactive).await;
+                            let errors = chunks_tx.clone();
+                            let result = tokio::task::spawn_blocking(move || {
+                                Self::handle_block_request(
+                                    client.clone(),
+                                    backend.clone(),
+                                    frontier_backend.clone(),
+                                    request_block_id,
+                                    params,
+                                    overrides,
+                                    limits,
+                                    Some(chunks_tx),
+                                )
+                            })
+                            .await
+                            .map_err(|e| {
+                                internal_err(format!("Internal error on spawned task : {:?}", e))
+                            })
+                            .and_then(|res| res);
+                            // Not held while waiting for the client.
+                            drop(permit);
+                            if let Err(err) = result {
+                                let _ = errors
+                                    .send(TraceChunk::Error { message: err.to_string() })
+                                    .await;
+                            }
+                        });
+                    },
                     _ => {},
                 }
             }
@@ -204,6 +462,46 @@ where
         (fut, tx)
     }
 
+    /// The timeout of a trace, the one of `params` if any, parsed like geth does (e.g. "10s" or
+    /// "500ms") and capped to the maximum.
+    fn handle_timeout(params: Option<&TraceParams>, limits: &TraceLimits) -> RpcResult<Duration> {
+        match params.and_then(|params| params.timeout.as_ref()) {
+            Some(timeout) => Ok(parse_duration(timeout)
+                .map_err(|e| internal_err(format!("invalid timeout: {}", e)))?
+                .min(limits.max_timeout)),
+            None => Ok(limits.timeout),
+        }
+    }
+
+    fn handle_tracer_config(params: Option<&TraceParams>) -> TracerConfig {
+        params.and_then(|params| params.tracer_config.clone()).unwrap_or_default()
+    }
+
+    /// The error of a trace its listener stopped early, a timeout once its deadline passed.
+    fn handle_listener_error(error: Option<String>, timed_out: bool) -> RpcResult<()> {
+        match error {
+            Some(_) if timed_out => Err(internal_err("execution timeout")),
+            Some(error) => Err(internal_err(error)),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds the runtime events deposited by the traced transaction to its trace, if requested.
+    fn with_substrate_events(
+        trace: single::TransactionTrace,
+        substrate_events: Vec<SubstrateEvent>,
+        tracer_config: &TracerConfig,
+    ) -> single::TransactionTrace {
+        if tracer_config.with_substrate_events {
+            single::TransactionTrace::WithSubstrateEvents {
+                trace: Box::new(trace),
+                substrate_events,
+            }
+        } else {
+            trace
+        }
+    }
+
     fn handle_params(params: Option<TraceParams>) -> RpcResult<(TracerInput, single::TraceType)> {
         // Set trace input and type
         match params {
@@ -256,7 +554,11 @@ where
         request_block_id: RequestBlockId,
         params: Option<TraceParams>,
         overrides: Arc<OverrideHandle<B>>,
+        limits: TraceLimits,
+        stream: Option<mpsc::Sender<TraceChunk>>,
     ) -> RpcResult<Response> {
+        let deadline = Some(Instant::now() + Self::handle_timeout(params.as_ref(), &limits)?);
+        let tracer_config = Self::handle_tracer_config(params.as_ref());
         let (tracer_input, trace_type) = Self::handle_params(params)?;
 
         let reference_id: BlockId<B> = match request_block_id {
@@ -307,9 +609,14 @@ where
         // Known ethereum transaction hashes.
         let eth_tx_hashes: Vec<_> = statuses.unwrap().iter().map(|t| t.transaction_hash).collect();
 
-        // If there are no ethereum transactions in the block return empty trace right away.
-        if eth_tx_hashes.is_empty() {
-            return Ok(Response::Block(vec![]))
+        // If nothing in the block enters the EVM return empty trace right away.
+        if !block_enters_evm(client.as_ref(), &reference_id, eth_tx_hashes.len())
+            .map_err(internal_err)?
+        {
+            return Ok(match stream {
+                Some(_) => Response::Streamed,
+                None => Response::Block(vec![]),
+            })
         }
 
         // Get the extrinsics.
@@ -320,7 +627,7 @@ where
             api.initialize_block(&parent_block_id, &header)
                 .map_err(|e| internal_err(format!("Runtime api access error: {:?}", e)))?;
 
-            api.trace_block(&parent_block_id, ext, eth_tx_hashes)
+            api.trace_block(&parent_block_id, ext, eth_tx_hashes.clone())
                 .map_err(|e| {
                     internal_err(format!(
                         "Blockchain error when replaying block {} : {:?}",
@@ -337,20 +644,68 @@ where
         };
 
         return match trace_type {
+            single::TraceType::Raw { disable_storage, disable_memory, disable_stack } => {
+                let proxy = amax_eva_client_evm_tracing::listeners::Raw::new(
+                    disable_storage,
+                    disable_memory,
+                    disable_stack,
+                )
+                .with_deadline(deadline);
+                let mut proxy = match &stream {
+                    Some(stream) => {
+                        proxy.with_sink(Box::new(ChunkSink::new(stream.clone(), eth_tx_hashes)))
+                    },
+                    None => proxy.with_memory_limit(limits.memory),
+                };
+                proxy.using(f)?;
+                proxy.finish_transaction();
+                Self::handle_listener_error(proxy.error.take(), proxy.timed_out)?;
+
+                Ok(match stream {
+                    Some(_) => Response::Streamed,
+                    None => Response::Block(proxy.traces),
+                })
+            },
             single::TraceType::CallList => {
-                let mut proxy = amax_eva_client_evm_tracing::listeners::CallList::default();
+                let mut proxy = amax_eva_client_evm_tracing::listeners::CallList::default()
+                    .with_memory_limit(limits.memory)
+                    .with_deadline(deadline)
+                    .with_gas_breakdown(tracer_config.with_gas_breakdown)
+                    .with_storage(tracer_config.with_storage);
                 proxy.using(f)?;
                 proxy.finish_transaction();
+                Self::handle_listener_error(proxy.error.take(), proxy.timed_out)?;
                 let response = match tracer_input {
+                    // A block without any transaction entering the EVM has an empty trace.
                     TracerInput::CallTracer => {
-                        amax_eva_client_evm_tracing::formatters::CallTracer::format(proxy)
-                            .ok_or("Trace result is empty.")
-                            .map_err(|e| internal_err(format!("{:?}", e)))
+                        Ok(amax_eva_client_evm_tracing::formatters::CallTracer::format(proxy)
+                            .unwrap_or_default())
                     },
                     _ => Err(internal_err("Bug: failed to resolve the tracer format.".to_string())),
                 }?;
 
-                Ok(Response::Block(response))
+                match stream {
+                    Some(stream) => {
+                        let mut eth_tx_hashes = eth_tx_hashes.into_iter();
+                        for result in response {
+                            let (target, result) = match result {
+                                single::TransactionTrace::Extrinsic { extrinsic_index, trace } => {
+                                    (TraceTarget::ExtrinsicIndex(extrinsic_index), *trace)
+                                },
+                                result => (
+                                    TraceTarget::TransactionHash(
+                                        eth_tx_hashes.next().unwrap_or_default(),
+                                    ),
+                                    result,
+                                ),
+                            };
+                            send_chunk(&stream, TraceChunk::Trace { target, result })
+                                .map_err(internal_err)?;
+                        }
+                        Ok(Response::Streamed)
+                    },
+                    None => Ok(Response::Block(response)),
+                }
             },
             not_supported => Err(internal_err(format!(
                 "Bug: `handle_block_request` does not support {:?}.",
@@ -373,7 +728,10 @@ where
         transaction_hash: H256,
         params: Option<TraceParams>,
         overrides: Arc<OverrideHandle<B>>,
+        limits: TraceLimits,
     ) -> RpcResult<Response> {
+        let deadline = Some(Instant::now() + Self::handle_timeout(params.as_ref(), &limits)?);
+        let tracer_config = Self::handle_tracer_config(params.as_ref());
         let (tracer_input, trace_type) = Self::handle_params(params)?;
 
         let (hash, index) = match frontier_backend_client::load_transactions::<B, C>(
@@ -441,16 +799,30 @@ where
                             disable_storage,
                             disable_memory,
                             disable_stack,
-                        );
+                        )
+                        .with_memory_limit(limits.memory)
+                        .with_deadline(deadline);
                         proxy.using(f)?;
-                        Ok(Response::Single(
-                            amax_eva_client_evm_tracing::formatters::Raw::format(proxy).unwrap(),
-                        ))
+                        Self::handle_listener_error(proxy.error.take(), proxy.timed_out)?;
+                        let substrate_events = std::mem::take(&mut proxy.substrate_events);
+                        let trace =
+                            amax_eva_client_evm_tracing::formatters::Raw::format(proxy).unwrap();
+                        Ok(Response::Single(Self::with_substrate_events(
+                            trace,
+                            substrate_events,
+                            &tracer_config,
+                        )))
                     },
                     single::TraceType::CallList => {
-                        let mut proxy = amax_eva_client_evm_tracing::listeners::CallList::default();
+                        let mut proxy = amax_eva_client_evm_tracing::listeners::CallList::default()
+                            .with_memory_limit(limits.memory)
+                            .with_deadline(deadline)
+                            .with_gas_breakdown(tracer_config.with_gas_breakdown)
+                            .with_storage(tracer_config.with_storage);
                         proxy.using(f)?;
                         proxy.finish_transaction();
+                        Self::handle_listener_error(proxy.error.take(), proxy.timed_out)?;
+                        let substrate_events = std::mem::take(&mut proxy.substrate_events);
                         let response = match tracer_input {
                             TracerInput::Blockscout => {
                                 amax_eva_client_evm_tracing::formatters::Blockscout::format(proxy)
@@ -470,7 +842,11 @@ where
                                 "Bug: failed to resolve the tracer format.".to_string(),
                             )),
                         }?;
-                        Ok(Response::Single(response))
+                        Ok(Response::Single(Self::with_substrate_events(
+                            response,
+                            substrate_events,
+                            &tracer_config,
+                        )))
                     },
                     not_supported => Err(internal_err(format!(
                         "Bug: `handle_transaction_request` does not support {:?}.",
@@ -482,3 +858,40 @@ where
         Err(internal_err("Runtime block call failed".to_string()))
     }
 }
+
+/// Parses a duration like geth does: a sequence of decimal numbers, each with an optional fraction
+/// and a unit suffix among "ns", "us" (or "µs"), "ms", "s", "m" and "h", e.g. "1.5s" or "1m30s".
+fn parse_duration(input: &str) -> Result<Duration, String> {
+    if input == "0" {
+        return Ok(Duration::ZERO)
+    }
+    let invalid = || format!("{:?} is not a duration", input);
+    if input.is_empty() {
+        return Err(invalid())
+    }
+
+    let is_number = |c: char| c.is_ascii_digit() || c == '.';
+    let mut rest = input;
+    let mut seconds = 0f64;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c| !is_number(c)).unwrap_or(rest.len());
+        let number = rest[..number_end].parse::<f64>().map_err(|_| invalid())?;
+        rest = &rest[number_end..];
+        let unit_end = rest.find(is_number).unwrap_or(rest.len());
+        let unit = match &rest[..unit_end] {
+            "ns" => 1e-9,
+            "us" | "µs" | "μs" => 1e-6,
+            "ms" => 1e-3,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return Err(invalid()),
+        };
+        rest = &rest[unit_end..];
+        seconds += number * unit;
+    }
+    if !seconds.is_finite() || seconds >= u64::MAX as f64 {
+        return Err(invalid())
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
@@ -0,0 +1,484 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    marker::PhantomData,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ethereum::TransactionV2 as EthereumTransaction;
+use ethereum_types::{H160, H256, U256};
+use jsonrpsee::core::{async_trait, RpcResult};
+
+// Substrate
+use sc_network::{ExHashT, NetworkService};
+use sc_transaction_pool_api::{
+    error::{Error as PoolError, IntoPoolError},
+    TransactionPool, TransactionSource,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::bytes::{from_hex, to_hex};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+// Frontier
+use fc_rpc::internal_err;
+use fc_rpc_core::types::Bytes;
+use fp_rpc::{ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi};
+
+// Local
+pub use amax_eva_rpc_core::EthJournalApiServer;
+
+use crate::txpool::hash_and_sender;
+
+/// The most transactions of a sender the journal holds.
+const MAX_PER_SENDER: usize = 64;
+/// How far ahead of the nonce of its sender a journaled transaction can be.
+const MAX_NONCE_GAP: u64 = 64;
+
+/// A transaction of the journal.
+#[derive(Clone)]
+struct Journaled {
+    hash: H256,
+    from: H160,
+    nonce: U256,
+    transaction: EthereumTransaction,
+}
+
+impl From<EthereumTransaction> for Journaled {
+    fn from(transaction: EthereumTransaction) -> Self {
+        let (_, from) = hash_and_sender(&transaction);
+        let nonce = match &transaction {
+            EthereumTransaction::Legacy(t) => t.nonce,
+            EthereumTransaction::EIP2930(t) => t.nonce,
+            EthereumTransaction::EIP1559(t) => t.nonce,
+        };
+        Self { hash: transaction.hash(), from, nonce, transaction }
+    }
+}
+
+/// The Ethereum transactions submitted to the RPC of the node, saved to disk until they are
+/// included in a block, like the `--txpool.journal` of geth.
+///
+/// The file holds one hex encoded EIP-2718 transaction per line, appended when the transaction
+/// enters the pool and rewritten without the included and invalid transactions at the rebroadcasts
+/// dropping some. A sender can journal at most `MAX_PER_SENDER` transactions, up to
+/// `MAX_NONCE_GAP` nonces ahead of its account.
+pub struct TransactionJournal {
+    path: PathBuf,
+    transactions: Mutex<Vec<Journaled>>,
+}
+
+impl TransactionJournal {
+    /// Opens the journal at `path`, loading the transactions it already holds.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let mut transactions = Vec::<Journaled>::new();
+        match File::open(&path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line?;
+                    match from_hex(line.trim()).ok().and_then(|bytes| decode_transaction(&bytes)) {
+                        Some(transaction) => {
+                            let journaled = Journaled::from(transaction);
+                            if !transactions.iter().any(|t| t.hash == journaled.hash) {
+                                transactions.push(journaled);
+                            }
+                        },
+                        // A line cut by a crash, the ones after it are still valid.
+                        None => tracing::warn!(
+                            target: "journal",
+                            "Skipping an undecodable transaction of the journal {}",
+                            path.display(),
+                        ),
+                    }
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+            },
+            Err(err) => return Err(err),
+        }
+
+        tracing::info!(
+            target: "journal",
+            "Loaded {} transactions from the journal {}",
+            transactions.len(),
+            path.display(),
+        );
+        Ok(Self { path, transactions: Mutex::new(transactions) })
+    }
+
+    /// Checks a transaction can be journaled, given the nonce of the account of its sender.
+    fn check(&self, journaled: &Journaled, account_nonce: U256) -> Result<(), String> {
+        if journaled.nonce > account_nonce.saturating_add(MAX_NONCE_GAP.into()) {
+            return Err(format!(
+                "nonce too far ahead: the account nonce is {}, the journal accepts at most {} ahead",
+                account_nonce, MAX_NONCE_GAP
+            ))
+        }
+
+        let transactions = self.transactions.lock().expect("journal lock poisoned");
+        let replaces = transactions
+            .iter()
+            .any(|t| t.from == journaled.from && t.nonce == journaled.nonce);
+        let journaled_by_sender = transactions.iter().filter(|t| t.from == journaled.from).count();
+        if !replaces && journaled_by_sender >= MAX_PER_SENDER {
+            return Err(format!(
+                "too many transactions of the sender: the journal accepts at most {}",
+                MAX_PER_SENDER
+            ))
+        }
+        Ok(())
+    }
+
+    /// Adds a transaction accepted by the pool, replacing the journaled one of the same sender and
+    /// nonce, if any.
+    fn insert(&self, journaled: Journaled) -> io::Result<()> {
+        let mut transactions = self.transactions.lock().expect("journal lock poisoned");
+        if transactions.iter().any(|t| t.hash == journaled.hash) {
+            return Ok(())
+        }
+
+        let replaces = |t: &Journaled| t.from == journaled.from && t.nonce == journaled.nonce;
+        if transactions.iter().any(replaces) {
+            // The replaced transaction is only removed from the file by a rewrite.
+            transactions.retain(|t| !replaces(t));
+            transactions.push(journaled);
+            return self.write(&transactions)
+        }
+
+        let line = encode_transaction(&journaled.transaction);
+        transactions.push(journaled);
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// The journaled transactions, in submission order.
+    fn transactions(&self) -> Vec<Journaled> {
+        self.transactions.lock().expect("journal lock poisoned").clone()
+    }
+
+    /// Removes the transactions of `hashes`, rewriting the file if any was journaled.
+    fn remove(&self, hashes: &HashSet<H256>) -> io::Result<()> {
+        let mut transactions = self.transactions.lock().expect("journal lock poisoned");
+        let journaled = transactions.len();
+        transactions.retain(|t| !hashes.contains(&t.hash));
+        if transactions.len() == journaled {
+            return Ok(())
+        }
+        self.write(&transactions)
+    }
+
+    /// Replaces the file by one holding `transactions`.
+    fn write(&self, transactions: &[Journaled]) -> io::Result<()> {
+        let path = self.path.with_extension("new");
+        let mut file = File::create(&path)?;
+        for journaled in transactions {
+            writeln!(file, "{}", encode_transaction(&journaled.transaction))?;
+        }
+        file.sync_all()?;
+        fs::rename(path, &self.path)
+    }
+}
+
+/// Decodes a raw transaction, either a legacy RLP one or an EIP-2718 typed envelope.
+fn decode_transaction(bytes: &[u8]) -> Option<EthereumTransaction> {
+    match bytes.first()? {
+        first if *first > 0x7f && *first < 0xff => {
+            rlp::decode::<ethereum::LegacyTransaction>(bytes)
+                .ok()
+                .map(EthereumTransaction::Legacy)
+        },
+        _ => ethereum::EnvelopedDecodable::decode(bytes).ok(),
+    }
+}
+
+/// Encodes a transaction as its raw bytes, the EIP-2718 envelope of the typed ones.
+fn encode_transaction(transaction: &EthereumTransaction) -> String {
+    to_hex(&ethereum::EnvelopedEncodable::encode(transaction), false)
+}
+
+/// `eth_sendRawTransaction`, journaling the transactions the pool accepts.
+pub struct EthJournal<B: BlockT, C, P> {
+    client: Arc<C>,
+    pool: Arc<P>,
+    journal: Arc<TransactionJournal>,
+    _marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C, P> EthJournal<B, C, P> {
+    pub fn new(client: Arc<C>, pool: Arc<P>, journal: Arc<TransactionJournal>) -> Self {
+        Self { client, pool, journal, _marker: PhantomData }
+    }
+}
+
+#[async_trait]
+impl<B, C, P> EthJournalApiServer for EthJournal<B, C, P>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: ConvertTransactionRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+    P: TransactionPool<Block = B> + 'static,
+{
+    async fn send_raw_transaction(&self, bytes: Bytes) -> RpcResult<H256> {
+        if bytes.0.is_empty() {
+            return Err(internal_err("transaction data is empty"))
+        }
+        let transaction = decode_transaction(&bytes.0)
+            .ok_or_else(|| internal_err("decode transaction failed"))?;
+        let hash = transaction.hash();
+        let journaled = Journaled::from(transaction.clone());
+
+        let id = BlockId::Hash(self.client.info().best_hash);
+        let account = self
+            .client
+            .runtime_api()
+            .account_basic(&id, journaled.from)
+            .map_err(|err| internal_err(format!("cannot access runtime api: {:?}", err)))?;
+        self.journal.check(&journaled, account.nonce).map_err(internal_err)?;
+
+        let extrinsic = self
+            .client
+            .runtime_api()
+            .convert_transaction(&id, transaction)
+            .map_err(|err| internal_err(format!("cannot access runtime api: {:?}", err)))?;
+        self.pool
+            .submit_one(&id, TransactionSource::Local, extrinsic)
+            .await
+            .map_err(|err| internal_err(format!("submit transaction to pool failed: {:?}", err)))?;
+
+        let journal = self.journal.clone();
+        match tokio::task::spawn_blocking(move || journal.insert(journaled)).await {
+            Ok(Ok(())) => {},
+            Ok(Err(err)) => {
+                tracing::warn!(target: "journal", "Failed to journal the transaction {:?}: {}", hash, err)
+            },
+            Err(err) => {
+                tracing::warn!(target: "journal", "Failed to journal the transaction {:?}: {}", hash, err)
+            },
+        }
+        Ok(hash)
+    }
+}
+
+/// Resubmits the journaled transactions at startup, then rebroadcasts the ones still pending every
+/// `interval`: they are submitted again when they left the pool, and propagated again to the peers
+/// otherwise. The included transactions and the ones the pool rejects as invalid leave the journal.
+pub async fn journal_task<B, C, P>(
+    client: Arc<C>,
+    pool: Arc<P>,
+    network: Arc<NetworkService<B, B::Hash>>,
+    journal: Arc<TransactionJournal>,
+    interval: Duration,
+) where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    B::Hash: ExHashT,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: ConvertTransactionRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+    P: TransactionPool<Block = B, Hash = B::Hash> + 'static,
+{
+    // The first tick is immediate, resubmitting the transactions saved before the restart.
+    let mut ticks = tokio::time::interval(interval);
+    loop {
+        ticks.tick().await;
+        rebroadcast(&*client, &*pool, &*network, &journal).await;
+    }
+}
+
+async fn rebroadcast<B, C, P>(
+    client: &C,
+    pool: &P,
+    network: &NetworkService<B, B::Hash>,
+    journal: &Arc<TransactionJournal>,
+) where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    B::Hash: ExHashT,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: ConvertTransactionRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+    P: TransactionPool<Block = B, Hash = B::Hash> + 'static,
+{
+    let id = BlockId::Hash(client.info().best_hash);
+    let mut removed = HashSet::new();
+    for journaled in journal.transactions() {
+        let account = client.runtime_api().account_basic(&id, journaled.from);
+        match account {
+            Ok(account) if account.nonce > journaled.nonce => {
+                removed.insert(journaled.hash);
+                continue
+            },
+            Ok(_) => {},
+            Err(err) => {
+                tracing::warn!(target: "journal", "Failed to get the account nonces: {:?}", err);
+                return
+            },
+        }
+
+        let extrinsic = client.runtime_api().convert_transaction(&id, journaled.transaction);
+        let extrinsic = match extrinsic {
+            Ok(extrinsic) => extrinsic,
+            Err(err) => {
+                tracing::warn!(target: "journal", "Failed to convert a transaction: {:?}", err);
+                return
+            },
+        };
+        let pool_hash = pool.hash_of(&extrinsic);
+        match pool.submit_one(&id, TransactionSource::Local, extrinsic).await {
+            Ok(_) => tracing::debug!(
+                target: "journal",
+                "Resubmitted the transaction {:?}",
+                journaled.hash,
+            ),
+            Err(err) => match err.into_pool_error() {
+                Ok(PoolError::AlreadyImported(_)) => network.propagate_transaction(pool_hash),
+                Ok(PoolError::InvalidTransaction(err)) => {
+                    tracing::info!(
+                        target: "journal",
+                        "Dropping the invalid transaction {:?} from the journal: {:?}",
+                        journaled.hash,
+                        err,
+                    );
+                    removed.insert(journaled.hash);
+                },
+                err => tracing::debug!(
+                    target: "journal",
+                    "Failed to resubmit the transaction {:?}: {:?}",
+                    journaled.hash,
+                    err,
+                ),
+            },
+        }
+    }
+
+    let journal = journal.clone();
+    match tokio::task::spawn_blocking(move || journal.remove(&removed)).await {
+        Ok(Ok(())) => {},
+        Ok(Err(err)) => tracing::warn!(target: "journal", "Failed to rewrite the journal: {}", err),
+        Err(err) => tracing::warn!(target: "journal", "Failed to rewrite the journal: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::{ecdsa, Pair};
+
+    fn journal_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("eva-journal-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("transactions.journal")
+    }
+
+    fn transaction(signer: &ecdsa::Pair, nonce: u64, gas_price: u64) -> Journaled {
+        let message = ethereum::LegacyTransactionMessage {
+            nonce: nonce.into(),
+            gas_price: gas_price.into(),
+            gas_limit: 21_000.into(),
+            action: ethereum::TransactionAction::Call(H160::repeat_byte(1)),
+            value: U256::zero(),
+            input: vec![],
+            chain_id: None,
+        };
+        let signature = signer.sign_prehashed(&message.hash().0);
+        let signature = signature.as_ref();
+        let signature = ethereum::TransactionSignature::new(
+            27 + signature[64] as u64,
+            H256::from_slice(&signature[..32]),
+            H256::from_slice(&signature[32..64]),
+        )
+        .unwrap();
+        Journaled::from(EthereumTransaction::Legacy(ethereum::LegacyTransaction {
+            nonce: message.nonce,
+            gas_price: message.gas_price,
+            gas_limit: message.gas_limit,
+            action: message.action,
+            value: message.value,
+            input: message.input,
+            signature,
+        }))
+    }
+
+    fn lines(path: &PathBuf) -> usize {
+        fs::read_to_string(path).unwrap().lines().count()
+    }
+
+    #[test]
+    fn loads_the_journaled_transactions() {
+        let path = journal_path("load");
+        let signer = ecdsa::Pair::from_seed(&[1; 32]);
+        let journal = TransactionJournal::open(path.clone()).unwrap();
+        journal.insert(transaction(&signer, 0, 1)).unwrap();
+        journal.insert(transaction(&signer, 1, 1)).unwrap();
+        // A line cut by a crash.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "0xf86b").unwrap();
+
+        let hashes = |journal: &TransactionJournal| {
+            journal.transactions().iter().map(|t| t.hash).collect::<Vec<_>>()
+        };
+        let reopened = TransactionJournal::open(path).unwrap();
+        assert_eq!(hashes(&reopened), hashes(&journal));
+    }
+
+    #[test]
+    fn rewrites_the_file_when_transactions_leave() {
+        let path = journal_path("remove");
+        let signer = ecdsa::Pair::from_seed(&[1; 32]);
+        let journal = TransactionJournal::open(path.clone()).unwrap();
+        let included = transaction(&signer, 0, 1);
+        journal.insert(included.clone()).unwrap();
+        journal.insert(transaction(&signer, 1, 1)).unwrap();
+        assert_eq!(lines(&path), 2);
+
+        journal.remove(&HashSet::from([included.hash])).unwrap();
+        assert_eq!(lines(&path), 1);
+        assert_eq!(TransactionJournal::open(path).unwrap().transactions().len(), 1);
+    }
+
+    #[test]
+    fn rewrites_the_file_only_on_change() {
+        let path = journal_path("rewrite");
+        let signer = ecdsa::Pair::from_seed(&[1; 32]);
+        let journal = TransactionJournal::open(path.clone()).unwrap();
+        journal.insert(transaction(&signer, 0, 1)).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        journal.remove(&HashSet::from([H256::repeat_byte(1)])).unwrap();
+        assert!(!path.exists());
+
+        // A replacement rewrites the file without the replaced transaction.
+        journal.insert(transaction(&signer, 0, 2)).unwrap();
+        assert_eq!(lines(&path), 1);
+        assert_eq!(journal.transactions()[0].transaction, transaction(&signer, 0, 2).transaction);
+    }
+
+    #[test]
+    fn caps_the_transactions_of_a_sender() {
+        let signer = ecdsa::Pair::from_seed(&[1; 32]);
+        let journal = TransactionJournal::open(journal_path("cap")).unwrap();
+        for nonce in 0..MAX_PER_SENDER as u64 {
+            let journaled = transaction(&signer, nonce, 1);
+            journal.check(&journaled, U256::zero()).unwrap();
+            journal.insert(journaled).unwrap();
+        }
+
+        let next = transaction(&signer, MAX_PER_SENDER as u64, 1);
+        assert!(journal.check(&next, U256::zero()).is_err());
+        // A replacement is still accepted, and other senders too.
+        assert!(journal.check(&transaction(&signer, 0, 2), U256::zero()).is_ok());
+        let other = ecdsa::Pair::from_seed(&[2; 32]);
+        assert!(journal.check(&transaction(&other, 0, 1), U256::zero()).is_ok());
+    }
+
+    #[test]
+    fn rejects_the_transactions_too_far_ahead() {
+        let signer = ecdsa::Pair::from_seed(&[1; 32]);
+        let journal = TransactionJournal::open(journal_path("gap")).unwrap();
+        assert!(journal.check(&transaction(&signer, 10 + MAX_NONCE_GAP, 1), 10.into()).is_ok());
+        assert!(journal.check(&transaction(&signer, 11 + MAX_NONCE_GAP, 1), 10.into()).is_err());
+    }
+}
@@ -1,5 +1,14 @@
+mod call;
 mod debug;
+mod journal;
+mod metrics;
+mod proof;
+mod simulate;
 mod tracing;
 mod txpool;
 
-pub use self::{debug::*, tracing::*, txpool::*};
+pub use self::{
+    call::*, debug::*, journal::*, metrics::*, proof::*, simulate::*, tracing::*, txpool::*,
+};
+// The dev methods are implemented by the node, which knows the runtimes.
+pub use amax_eva_rpc_core::DevApiServer;
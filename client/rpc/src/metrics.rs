@@ -0,0 +1,214 @@
+//! Prometheus metrics of the tracing, `trace_filter` cache and transaction pool of the RPC.
+
+use std::sync::Arc;
+
+use prometheus_endpoint::{
+    exponential_buckets, prometheus::HistogramTimer, register, Counter, Gauge, GaugeVec, Histogram,
+    HistogramOpts, HistogramVec, Opts, PrometheusError, Registry, U64,
+};
+
+/// The metrics of the RPC, registered in the registry of the node.
+pub struct Metrics {
+    /// Duration of the trace requests, by RPC method.
+    pub trace_request_duration: HistogramVec,
+    /// Blocks of the `trace_filter` requests found in the cache.
+    pub trace_cache_hits: Counter<U64>,
+    /// Blocks of the `trace_filter` requests traced for the cache.
+    pub trace_cache_misses: Counter<U64>,
+    /// Time the tracing tasks waited for a permit, by priority.
+    pub tracing_permit_wait: HistogramVec,
+    /// Tracing tasks waiting for a permit, by priority.
+    pub tracing_queue_depth: GaugeVec<U64>,
+    /// Blocks traced, whose rate is the number of blocks traced per second.
+    pub traced_blocks: Counter<U64>,
+    /// Number of traces returned by the `trace_filter` requests.
+    pub trace_filter_result_size: Histogram,
+    /// Ethereum transactions in the ready queue of the pool.
+    pub txpool_ethereum_ready: Gauge<U64>,
+    /// Ethereum transactions in the future queue of the pool.
+    pub txpool_ethereum_future: Gauge<U64>,
+}
+
+impl Metrics {
+    pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+        Ok(Self {
+            trace_request_duration: register(
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "eva_rpc_trace_request_duration_seconds",
+                        "Duration of the trace requests",
+                    )
+                    .buckets(exponential_buckets(0.001, 4.0, 10)?),
+                    &["method"],
+                )?,
+                registry,
+            )?,
+            trace_cache_hits: register(
+                Counter::new(
+                    "eva_rpc_trace_cache_hits_total",
+                    "Blocks of the trace_filter requests found in the cache",
+                )?,
+                registry,
+            )?,
+            trace_cache_misses: register(
+                Counter::new(
+                    "eva_rpc_trace_cache_misses_total",
+                    "Blocks of the trace_filter requests traced for the cache",
+                )?,
+                registry,
+            )?,
+            tracing_permit_wait: register(
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "eva_rpc_tracing_permit_wait_seconds",
+                        "Time the tracing tasks waited for a permit",
+                    )
+                    .buckets(exponential_buckets(0.001, 4.0, 10)?),
+                    &["priority"],
+                )?,
+                registry,
+            )?,
+            tracing_queue_depth: register(
+                GaugeVec::new(
+                    Opts::new("eva_rpc_tracing_queue_depth", "Tracing tasks waiting for a permit"),
+                    &["priority"],
+                )?,
+                registry,
+            )?,
+            traced_blocks: register(
+                Counter::new("eva_rpc_traced_blocks_total", "Blocks traced")?,
+                registry,
+            )?,
+            trace_filter_result_size: register(
+                Histogram::with_opts(
+                    HistogramOpts::new(
+                        "eva_rpc_trace_filter_result_size",
+                        "Number of traces returned by the trace_filter requests",
+                    )
+                    .buckets(exponential_buckets(1.0, 4.0, 10)?),
+                )?,
+                registry,
+            )?,
+            txpool_ethereum_ready: register(
+                Gauge::new(
+                    "eva_rpc_txpool_ethereum_ready",
+                    "Ethereum transactions in the ready queue of the pool",
+                )?,
+                registry,
+            )?,
+            txpool_ethereum_future: register(
+                Gauge::new(
+                    "eva_rpc_txpool_ethereum_future",
+                    "Ethereum transactions in the future queue of the pool",
+                )?,
+                registry,
+            )?,
+        })
+    }
+}
+
+/// A handle to the metrics, which are not reported if the node has no Prometheus registry.
+#[derive(Clone, Default)]
+pub struct MetricsLink(Arc<Option<Metrics>>);
+
+impl MetricsLink {
+    pub fn new(registry: Option<&Registry>) -> Self {
+        Self(Arc::new(registry.and_then(|registry| {
+            Metrics::register(registry)
+                .map_err(|err| {
+                    tracing::warn!("Failed to register the RPC Prometheus metrics: {}", err);
+                })
+                .ok()
+        })))
+    }
+
+    pub fn report(&self, do_this: impl FnOnce(&Metrics)) {
+        if let Some(metrics) = self.0.as_ref() {
+            do_this(metrics);
+        }
+    }
+
+    /// Times a trace request of the RPC `method` until the timer is dropped.
+    pub fn trace_request_timer(&self, method: &str) -> Option<HistogramTimer> {
+        self.0.as_ref().as_ref().map(|metrics| {
+            metrics.trace_request_duration.with_label_values(&[method]).start_timer()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The samples of the metric `name` of the registry, by their labels.
+    fn samples(registry: &Registry, name: &str) -> Vec<(Vec<String>, f64)> {
+        let family = registry
+            .gather()
+            .into_iter()
+            .find(|family| family.get_name() == name)
+            .unwrap_or_else(|| panic!("{} is registered", name));
+        family
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                let labels =
+                    metric.get_label().iter().map(|label| label.get_value().to_owned()).collect();
+                let value = if metric.has_histogram() {
+                    metric.get_histogram().get_sample_count() as f64
+                } else if metric.has_counter() {
+                    metric.get_counter().get_value()
+                } else {
+                    metric.get_gauge().get_value()
+                };
+                (labels, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_to_the_registered_metrics() {
+        let registry = Registry::new();
+        let metrics = MetricsLink::new(Some(&registry));
+
+        drop(metrics.trace_request_timer("trace_filter"));
+        metrics.report(|metrics| {
+            metrics.trace_cache_hits.inc_by(2);
+            metrics.trace_cache_misses.inc();
+            metrics.tracing_queue_depth.with_label_values(&["backfill"]).set(3);
+            metrics.trace_filter_result_size.observe(10.0);
+            metrics.txpool_ethereum_ready.set(4);
+            metrics.txpool_ethereum_future.set(5);
+        });
+
+        assert_eq!(
+            samples(&registry, "eva_rpc_trace_request_duration_seconds"),
+            [(vec!["trace_filter".to_owned()], 1.0)]
+        );
+        assert_eq!(samples(&registry, "eva_rpc_trace_cache_hits_total"), [(vec![], 2.0)]);
+        assert_eq!(samples(&registry, "eva_rpc_trace_cache_misses_total"), [(vec![], 1.0)]);
+        assert_eq!(
+            samples(&registry, "eva_rpc_tracing_queue_depth"),
+            [(vec!["backfill".to_owned()], 3.0)]
+        );
+        assert_eq!(samples(&registry, "eva_rpc_trace_filter_result_size"), [(vec![], 1.0)]);
+        assert_eq!(samples(&registry, "eva_rpc_txpool_ethereum_ready"), [(vec![], 4.0)]);
+        assert_eq!(samples(&registry, "eva_rpc_txpool_ethereum_future"), [(vec![], 5.0)]);
+    }
+
+    #[test]
+    fn reports_nothing_without_registry() {
+        let metrics = MetricsLink::new(None);
+        assert!(metrics.trace_request_timer("trace_filter").is_none());
+        metrics.report(|_| panic!("No metrics are registered"));
+    }
+
+    #[test]
+    fn fails_to_register_the_metrics_twice() {
+        let registry = Registry::new();
+        assert!(Metrics::register(&registry).is_ok());
+        assert!(Metrics::register(&registry).is_err());
+        // A link whose metrics fail to register reports nothing.
+        let metrics = MetricsLink::new(Some(&registry));
+        metrics.report(|_| panic!("The metrics failed to register"));
+    }
+}
@@ -0,0 +1,146 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use ethereum_types::{H160, H256, U256};
+use jsonrpsee::core::RpcResult;
+
+// Substrate
+use sc_client_api::ProofProvider;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::hashing::{blake2_128, keccak_256, twox_128};
+use sp_runtime::{
+    generic::BlockId,
+    traits::{Block as BlockT, Header as HeaderT},
+};
+
+// Frontier
+use fc_rpc::{frontier_backend_client, internal_err};
+use fc_rpc_core::types::{BlockNumber, Bytes};
+use fp_rpc::EthereumRuntimeRPCApi;
+
+// Local
+pub use amax_eva_rpc_core::{AccountProof, EthProofApiServer, StorageProof};
+
+/// `eth_getProof`, proving the EVM state with Substrate read proofs.
+pub struct EthProof<B: BlockT, C> {
+    client: Arc<C>,
+    backend: Arc<fc_db::Backend<B>>,
+    _marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C> EthProof<B, C> {
+    pub fn new(client: Arc<C>, backend: Arc<fc_db::Backend<B>>) -> Self {
+        Self { client, backend, _marker: PhantomData }
+    }
+}
+
+impl<B, C> EthProof<B, C>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + ProofProvider<B> + Send + Sync + 'static,
+{
+    /// The trie nodes proving the values of `keys`.
+    fn read_proof(&self, id: &BlockId<B>, keys: &[&[u8]]) -> RpcResult<Vec<Bytes>> {
+        let proof = self
+            .client
+            .read_proof(id, &mut keys.iter().copied())
+            .map_err(|err| internal_err(format!("failed to generate the proof: {:?}", err)))?;
+        Ok(proof.into_iter_nodes().map(Bytes).collect())
+    }
+}
+
+impl<B, C> EthProofApiServer for EthProof<B, C>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + ProofProvider<B> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<B>,
+{
+    fn get_proof(
+        &self,
+        address: H160,
+        storage_keys: Vec<H256>,
+        number: Option<BlockNumber>,
+    ) -> RpcResult<AccountProof> {
+        let id = frontier_backend_client::native_block_id::<B, C>(
+            self.client.as_ref(),
+            self.backend.as_ref(),
+            number,
+        )?
+        .ok_or_else(|| internal_err("header not found"))?;
+        let header = self
+            .client
+            .header(id)
+            .map_err(|err| internal_err(format!("failed to get the header: {:?}", err)))?
+            .ok_or_else(|| internal_err("header not found"))?;
+
+        let api = self.client.runtime_api();
+        let basic = api
+            .account_basic(&id, address)
+            .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+        let code = api
+            .account_code_at(&id, address)
+            .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+        let account_key = account_key(address);
+        let code_key = code_key(address);
+        let account_proof = self.read_proof(&id, &[&account_key[..], &code_key[..]])?;
+
+        let storage_proof = storage_keys
+            .into_iter()
+            .map(|key| {
+                let value = api
+                    .storage_at(&id, address, U256::from_big_endian(key.as_bytes()))
+                    .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+                let storage_key = storage_key(address, key);
+                Ok(StorageProof {
+                    key,
+                    value,
+                    proof: self.read_proof(&id, &[&storage_key[..]])?,
+                    storage_key: Bytes(storage_key),
+                })
+            })
+            .collect::<RpcResult<Vec<_>>>()?;
+
+        Ok(AccountProof {
+            address,
+            balance: basic.balance,
+            nonce: basic.nonce,
+            code_hash: H256(keccak_256(&code)),
+            storage_hash: *header.state_root(),
+            account_proof,
+            storage_proof,
+            block_hash: header.hash(),
+            state_root: *header.state_root(),
+            account_key: Bytes(account_key),
+            code_key: Bytes(code_key),
+        })
+    }
+}
+
+fn storage_prefix(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+    [twox_128(pallet), twox_128(item)].concat()
+}
+
+fn blake2_128_concat(data: &[u8]) -> Vec<u8> {
+    [&blake2_128(data)[..], data].concat()
+}
+
+/// The key of `frame_system::Account`, the account id being the address.
+fn account_key(address: H160) -> Vec<u8> {
+    [storage_prefix(b"System", b"Account"), blake2_128_concat(address.as_bytes())].concat()
+}
+
+/// The key of `pallet_evm::AccountCodes`.
+fn code_key(address: H160) -> Vec<u8> {
+    [storage_prefix(b"EVM", b"AccountCodes"), blake2_128_concat(address.as_bytes())].concat()
+}
+
+/// The key of `pallet_evm::AccountStorages`.
+fn storage_key(address: H160, index: H256) -> Vec<u8> {
+    [
+        storage_prefix(b"EVM", b"AccountStorages"),
+        blake2_128_concat(address.as_bytes()),
+        blake2_128_concat(index.as_bytes()),
+    ]
+    .concat()
+}
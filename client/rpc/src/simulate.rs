@@ -0,0 +1,367 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use ethereum::{Header, PartialHeader};
+use ethereum_types::{Bloom, BloomInput, H160, H256, H64, U256, U64};
+use jsonrpsee::core::RpcResult;
+
+// Substrate
+use sp_api::{ApiRef, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+// Frontier
+use fc_rpc::{frontier_backend_client, internal_err};
+use fc_rpc_core::types::{BlockNumber, Bytes, Log};
+use fp_evm::ExitReason;
+use fp_rpc::EthereumRuntimeRPCApi;
+
+// Local
+use amax_eva_client_evm_tracing::{
+    formatters::{
+        blockscout::{BlockscoutCall, BlockscoutCallInner},
+        Blockscout, ResponseFormatter,
+    },
+    listeners::CallList,
+    types::{
+        single::{Call, TransactionTrace},
+        CallResult, CallType, CreateResult,
+    },
+};
+pub use amax_eva_rpc_core::{
+    EthSimulateApiServer, SimulateBlock, SimulateCallError, SimulatePayload, SimulatedBlock,
+    SimulatedCallResult,
+};
+use primitives_rpc::call::{EthCallRuntimeApi, SimulateCall, SimulatedCall};
+
+use crate::call::{call_params, convert_state_overrides, revert_message};
+
+/// The maximum number of simulated blocks of a request.
+const MAX_SIMULATE_BLOCKS: usize = 256;
+/// The default timestamp increment of the simulated blocks in seconds, as Geth does.
+const TIMESTAMP_INCREMENT: u64 = 12;
+/// The address of the ether transfer logs.
+const TRANSFER_LOG_ADDRESS: H160 = H160([0xee; 20]);
+/// `keccak256("Transfer(address,address,uint256)")`
+const TRANSFER_TOPIC: H256 =
+    H256(hex_literal::hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"));
+
+/// Error codes of the failed calls, as Geth does.
+const REVERTED_ERROR_CODE: i32 = 3;
+const VM_ERROR_CODE: i32 = -32015;
+
+/// `eth_simulateV1`, executing the calls of the simulated blocks in a single runtime api instance.
+pub struct EthSimulate<B: BlockT, C> {
+    client: Arc<C>,
+    backend: Arc<fc_db::Backend<B>>,
+    _marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C> EthSimulate<B, C> {
+    pub fn new(client: Arc<C>, backend: Arc<fc_db::Backend<B>>) -> Self {
+        Self { client, backend, _marker: PhantomData }
+    }
+}
+
+impl<B, C> EthSimulate<B, C>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<B> + EthCallRuntimeApi<B>,
+{
+    fn block_id(&self, number: Option<BlockNumber>) -> RpcResult<BlockId<B>> {
+        frontier_backend_client::native_block_id::<B, C>(
+            self.client.as_ref(),
+            self.backend.as_ref(),
+            number,
+        )?
+        .ok_or_else(|| internal_err("header not found"))
+    }
+
+    /// Execute a call, collecting its ether transfers when `trace_transfers` is set.
+    #[allow(clippy::too_many_arguments)]
+    fn execute(
+        api: &ApiRef<'_, C::Api>,
+        id: &BlockId<B>,
+        call: SimulateCall,
+        validate: bool,
+        trace_transfers: bool,
+        state_overrides: Vec<primitives_rpc::call::StateOverride>,
+        block_overrides: primitives_rpc::call::BlockOverrides,
+    ) -> RpcResult<(SimulatedCall, Vec<(H160, H160, U256)>)> {
+        let f = || -> RpcResult<_> {
+            api.simulate_call(id, call, validate, trace_transfers, state_overrides, block_overrides)
+                .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+                .map_err(|err| internal_err(format!("execution fatal: {:?}", err)))
+        };
+
+        if trace_transfers {
+            let mut proxy = CallList::default();
+            let result = proxy.using(f)?;
+            proxy.finish_transaction();
+            Ok((result, transfers(proxy)))
+        } else {
+            Ok((f()?, Vec::new()))
+        }
+    }
+}
+
+impl<B, C> EthSimulateApiServer for EthSimulate<B, C>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<B> + EthCallRuntimeApi<B>,
+{
+    fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        number: Option<BlockNumber>,
+    ) -> RpcResult<Vec<SimulatedBlock>> {
+        if payload.block_state_calls.len() > MAX_SIMULATE_BLOCKS {
+            return Err(internal_err(format!(
+                "too many blocks, can be up to {}",
+                MAX_SIMULATE_BLOCKS
+            )))
+        }
+        if payload.return_full_transactions {
+            return Err(internal_err("returnFullTransactions is not supported"))
+        }
+
+        let id = self.block_id(number)?;
+        // All the calls are executed in this instance, on top of the changes of the previous ones.
+        let api = self.client.runtime_api();
+        let parent = api
+            .current_block(&id)
+            .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+            .ok_or_else(|| internal_err("block unavailable"))?;
+        // Without validation the calls are free, as for `eth_call`.
+        let default_base_fee = if payload.validation {
+            api.gas_price(&id)
+                .map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+        } else {
+            U256::zero()
+        };
+        let gas_limit = parent.header.gas_limit;
+
+        let mut parent_hash = parent.header.hash();
+        let mut number = parent.header.number;
+        let mut timestamp = U256::from(parent.header.timestamp / 1000);
+        // The state overrides of a block without calls are applied with the next call.
+        let mut state_overrides = Vec::new();
+        let mut blocks = Vec::with_capacity(payload.block_state_calls.len());
+        for block in payload.block_state_calls {
+            let overrides = block.block_overrides.unwrap_or_default();
+            let block_number = overrides.number.unwrap_or_else(|| number + 1);
+            if block_number <= number {
+                return Err(internal_err(format!(
+                    "block number {} is not greater than {}",
+                    block_number, number
+                )))
+            }
+            let block_timestamp = overrides.time.unwrap_or_else(|| timestamp + TIMESTAMP_INCREMENT);
+            if block_timestamp <= timestamp {
+                return Err(internal_err(format!(
+                    "block timestamp {} is not greater than {}",
+                    block_timestamp, timestamp
+                )))
+            }
+            let fee_recipient = overrides.coinbase.unwrap_or(parent.header.beneficiary);
+            let base_fee = overrides.base_fee.unwrap_or(default_base_fee);
+            let block_overrides = primitives_rpc::call::BlockOverrides {
+                number: Some(block_number),
+                timestamp: Some(block_timestamp),
+                coinbase: overrides.coinbase,
+                base_fee: Some(base_fee),
+            };
+            state_overrides.extend(convert_state_overrides(block.state_overrides)?);
+
+            let mut gas_used = U256::zero();
+            let mut log_index = 0u32;
+            let mut logs_bloom = Bloom::default();
+            let mut calls = Vec::with_capacity(block.calls.len());
+            for (index, request) in block.calls.into_iter().enumerate() {
+                let remaining_gas = gas_limit.saturating_sub(gas_used);
+                let call_gas_limit = request.gas.unwrap_or(remaining_gas);
+                if call_gas_limit > remaining_gas {
+                    return Err(internal_err(format!(
+                        "block gas limit reached by call {} of block {}",
+                        index, block_number
+                    )))
+                }
+                let params = call_params(request)?;
+                let call = SimulateCall {
+                    from: params.from,
+                    to: params.to,
+                    data: params.data,
+                    value: params.value,
+                    gas_limit: call_gas_limit,
+                    max_fee_per_gas: params.max_fee_per_gas,
+                    max_priority_fee_per_gas: params.max_priority_fee_per_gas,
+                    nonce: params.nonce,
+                    access_list: params.access_list.unwrap_or_default(),
+                };
+                let (result, transfers) = Self::execute(
+                    &api,
+                    &id,
+                    call,
+                    payload.validation,
+                    payload.trace_transfers,
+                    std::mem::take(&mut state_overrides),
+                    block_overrides.clone(),
+                )?;
+                gas_used = gas_used.saturating_add(result.used_gas);
+
+                let error = match result.exit_reason {
+                    ExitReason::Succeed(_) => None,
+                    ExitReason::Revert(_) => Some(SimulateCallError {
+                        code: REVERTED_ERROR_CODE,
+                        message: revert_message(&result.value),
+                        data: Some(Bytes(result.value.clone())),
+                    }),
+                    ExitReason::Error(err) => Some(SimulateCallError {
+                        code: VM_ERROR_CODE,
+                        message: format!("evm error: {:?}", err),
+                        data: None,
+                    }),
+                    ExitReason::Fatal(err) => {
+                        return Err(internal_err(format!("evm fatal: {:?}", err)))
+                    },
+                };
+
+                // The logs of a failed call are discarded, including its transfers.
+                let logs = if error.is_none() {
+                    transfers
+                        .into_iter()
+                        .map(|(from, to, value)| transfer_log(from, to, value))
+                        .chain(result.logs)
+                        .enumerate()
+                        .map(|(transaction_log_index, log)| {
+                            logs_bloom.accrue(BloomInput::Raw(&log.address[..]));
+                            for topic in &log.topics {
+                                logs_bloom.accrue(BloomInput::Raw(&topic[..]));
+                            }
+                            log_index += 1;
+                            Log {
+                                address: log.address,
+                                topics: log.topics,
+                                data: Bytes(log.data),
+                                block_hash: None,
+                                block_number: Some(block_number),
+                                transaction_hash: None,
+                                transaction_index: Some(U256::from(index)),
+                                log_index: Some(U256::from(log_index - 1)),
+                                transaction_log_index: Some(U256::from(transaction_log_index)),
+                                removed: false,
+                            }
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                calls.push(SimulatedCallResult {
+                    return_data: Bytes(result.value),
+                    logs,
+                    gas_used: result.used_gas,
+                    status: if error.is_none() { U64::one() } else { U64::zero() },
+                    error,
+                });
+            }
+
+            // The simulated blocks are not sealed, the hash only identifies them in the result.
+            let header = Header::new(
+                PartialHeader {
+                    parent_hash,
+                    beneficiary: fee_recipient,
+                    state_root: H256::zero(),
+                    receipts_root: H256::zero(),
+                    logs_bloom,
+                    difficulty: U256::zero(),
+                    number: block_number,
+                    gas_limit,
+                    gas_used,
+                    timestamp: block_timestamp.low_u64().saturating_mul(1000),
+                    extra_data: Vec::new(),
+                    mix_hash: H256::zero(),
+                    nonce: H64::zero(),
+                },
+                H256::zero(),
+                H256::zero(),
+            );
+            let hash = header.hash();
+            for log in calls.iter_mut().flat_map(|call| call.logs.iter_mut()) {
+                log.block_hash = Some(hash);
+            }
+
+            blocks.push(SimulatedBlock {
+                number: block_number,
+                hash,
+                parent_hash,
+                timestamp: block_timestamp,
+                gas_limit,
+                gas_used,
+                fee_recipient,
+                base_fee_per_gas: base_fee,
+                calls,
+            });
+            parent_hash = hash;
+            number = block_number;
+            timestamp = block_timestamp;
+        }
+        Ok(blocks)
+    }
+}
+
+/// The ether transfers of a traced call, in execution order, skipping the failed frames and their
+/// children.
+fn transfers(listener: CallList) -> Vec<(H160, H160, U256)> {
+    let frames: Vec<BlockscoutCall> = match Blockscout::format(listener) {
+        Some(TransactionTrace::CallList(calls)) => calls
+            .into_iter()
+            .filter_map(|call| match call {
+                Call::Blockscout(call) => Some(call),
+                _ => None,
+            })
+            .collect(),
+        _ => return Vec::new(),
+    };
+
+    let failed: Vec<&[u32]> = frames
+        .iter()
+        .filter(|frame| {
+            matches!(
+                frame.inner,
+                BlockscoutCallInner::Call { res: CallResult::Error(_), .. } |
+                    BlockscoutCallInner::Create { res: CreateResult::Error { .. }, .. }
+            )
+        })
+        .map(|frame| frame.trace_address.as_slice())
+        .collect();
+
+    frames
+        .iter()
+        .filter(|frame| !failed.iter().any(|failed| frame.trace_address.starts_with(failed)))
+        .filter_map(|frame| match &frame.inner {
+            BlockscoutCallInner::Call { call_type: CallType::Call, to, .. } => {
+                Some((frame.from, *to, frame.value))
+            },
+            BlockscoutCallInner::Create {
+                res: CreateResult::Success { created_contract_address_hash, .. },
+                ..
+            } => Some((frame.from, *created_contract_address_hash, frame.value)),
+            BlockscoutCallInner::SelfDestruct { balance, to } => Some((frame.from, *to, *balance)),
+            _ => None,
+        })
+        .filter(|(_, _, value)| !value.is_zero())
+        .collect()
+}
+
+/// An ether transfer, logged as an ERC-20 `Transfer`.
+fn transfer_log(from: H160, to: H160, value: U256) -> ethereum::Log {
+    let mut data = [0u8; 32];
+    value.to_big_endian(&mut data);
+    ethereum::Log {
+        address: TRANSFER_LOG_ADDRESS,
+        topics: vec![TRANSFER_TOPIC, H256::from(from), H256::from(to)],
+        data: data.to_vec(),
+    }
+}
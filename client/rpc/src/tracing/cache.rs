@@ -4,7 +4,7 @@ use ethereum_types::H256;
 use futures::{select, stream::FuturesUnordered, FutureExt, SinkExt, StreamExt};
 use jsonrpsee::core::RpcResult;
 use tokio::{
-    sync::{mpsc, oneshot, Semaphore},
+    sync::{mpsc, oneshot},
     time::sleep,
 };
 use tracing::{instrument, Instrument};
@@ -28,7 +28,8 @@ use amax_eva_client_evm_tracing::{
 pub use amax_eva_rpc_core::{FilterRequest, TraceServer};
 use primitives_rpc::debug::DebugRuntimeApi;
 
-use super::TxsTraceRes;
+use super::{block_enters_evm, Permit, Priority, Ticket, TracingScheduler, TxsTraceRes};
+use crate::MetricsLink;
 /// An opaque batch ID.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CacheBatchId(u64);
@@ -148,24 +149,32 @@ enum CacheBlockState {
         /// traced. They response channel is stored here, and the result will be
         /// sent in all of them when the tracing is finished.
         waiting_requests: Vec<oneshot::Sender<TxsTraceRes>>,
-        /// Channel used to unqueue a tracing that has not yet started.
+        /// Channel sending the tracing task its place in the queue of the scheduler, once the
+        /// block is prefetched and again when a request waits for it before it started.
         /// A tracing will be unqueued if it has not yet been started and the last batch
-        /// needing this block is ended (ignoring the expiration delay).
-        /// It is not used directly, but dropping will wake up the receiver.
-        #[allow(dead_code)]
-        unqueue_sender: oneshot::Sender<()>,
+        /// needing this block is ended (ignoring the expiration delay), dropping the channel.
+        ticket_sender: mpsc::UnboundedSender<Ticket>,
+        /// The priority of the last ticket sent to the tracing task, if any.
+        scheduled: Option<Priority>,
     },
     /// Tracing has completed and the result is available. No Runtime API call
     /// will be needed until this block cache is removed.
     Cached { traces: TxsTraceRes },
 }
 
+/// The blocks of a batch, in the order of the range.
+struct Batch {
+    blocks: Vec<H256>,
+    /// The number of blocks at the start of the batch which are scheduled for tracing.
+    prefetched: usize,
+}
+
 /// Tracing a block is done in a separate tokio blocking task to avoid clogging the async threads.
 /// For this reason a channel using this type is used by the blocking task to communicate with the
 /// main cache task.
 enum BlockingTaskMessage {
     /// Notify the tracing for this block has started as the blocking task got a permit from
-    /// the semaphore. This is used to prevent the deletion of a cache entry for a block that has
+    /// the scheduler. This is used to prevent the deletion of a cache entry for a block that has
     /// started being traced.
     Started { block_hash: H256 },
     /// The tracing is finished and the result is send to the main task.
@@ -176,10 +185,13 @@ enum BlockingTaskMessage {
 pub struct CacheTask<B, C, BE> {
     client: Arc<C>,
     backend: Arc<BE>,
-    blocking_permits: Arc<Semaphore>,
+    scheduler: TracingScheduler,
     cached_blocks: BTreeMap<H256, CacheBlock>,
-    batches: BTreeMap<u64, Vec<H256>>,
+    batches: BTreeMap<u64, Batch>,
     next_batch_id: u64,
+    /// Whether the traces include the storage slots accessed by the calls.
+    storage: bool,
+    metrics: MetricsLink,
     _phantom: PhantomData<B>,
 }
 
@@ -206,8 +218,10 @@ where
         client: Arc<C>,
         backend: Arc<BE>,
         cache_duration: Duration,
-        blocking_permits: Arc<Semaphore>,
+        scheduler: TracingScheduler,
         overrides: Arc<OverrideHandle<B>>,
+        storage: bool,
+        metrics: MetricsLink,
     ) -> (impl Future<Output = ()>, CacheRequester) {
         // Communication with the outside world :
         let (requester_tx, mut requester_rx) =
@@ -219,7 +233,7 @@ where
             // part of Self without introducing borrowing issues.
             let mut batch_expirations = FuturesUnordered::new();
             let (blocking_tx, mut blocking_rx) =
-                mpsc::channel(blocking_permits.available_permits() * 2);
+                mpsc::channel(scheduler.permits() * 2);
 
             // Contains the inner state of the cache task, excluding the pooled futures/channels.
             // Having this object allow to refactor each event into its own function, simplifying
@@ -227,10 +241,12 @@ where
             let mut inner = Self {
                 client,
                 backend,
-                blocking_permits,
+                scheduler,
                 cached_blocks: BTreeMap::new(),
                 batches: BTreeMap::new(),
                 next_batch_id: 0,
+                storage,
+                metrics,
                 _phantom: Default::default(),
             };
 
@@ -291,12 +307,14 @@ where
         overrides: Arc<OverrideHandle<B>>,
     ) {
         tracing::trace!("Starting batch {}", self.next_batch_id);
-        self.batches.insert(self.next_batch_id, blocks.clone());
+        self.batches
+            .insert(self.next_batch_id, Batch { blocks: blocks.clone(), prefetched: 0 });
 
         for block in blocks {
             // The block is already in the cache, awesome !
             if let Some(block_cache) = self.cached_blocks.get_mut(&block) {
                 block_cache.active_batch_count += 1;
+                self.metrics.report(|metrics| metrics.trace_cache_hits.inc());
                 tracing::trace!(
                     "Cache hit for block {}, now used by {} batches.",
                     block,
@@ -306,28 +324,30 @@ where
             // Otherwise we need to queue this block for tracing.
             else {
                 tracing::trace!("Cache miss for block {}, pooling it for tracing.", block);
+                self.metrics.report(|metrics| metrics.trace_cache_misses.inc());
 
-                let blocking_permits = Arc::clone(&self.blocking_permits);
-                let (unqueue_sender, unqueue_receiver) = oneshot::channel();
+                let (ticket_sender, ticket_receiver) = mpsc::unbounded_channel();
                 let client = Arc::clone(&self.client);
                 let backend = Arc::clone(&self.backend);
                 let blocking_tx = blocking_tx.clone();
                 let overrides = overrides.clone();
+                let storage = self.storage;
+                let metrics = self.metrics.clone();
 
                 // Spawn all block caching asynchronously.
-                // It will wait to obtain a permit, then spawn a blocking task.
+                // It will wait to be scheduled and obtain a permit, then spawn a blocking task.
                 // When the blocking task returns its result, it is send
                 // thought a channel to the main task loop.
                 tokio::spawn(
                     async move {
                         tracing::trace!("Waiting for blocking permit or task cancellation");
-                        let _permit = select!(
-                            _ = unqueue_receiver.fuse() => {
-                            tracing::trace!("Tracing of the block has been cancelled.");
-                                return;
+                        let _permit = match Self::block_permit(ticket_receiver).await {
+                            Some(permit) => permit,
+                            None => {
+                                tracing::trace!("Tracing of the block has been cancelled.");
+                                return
                             },
-                            permit = blocking_permits.acquire().fuse() => permit,
-                        );
+                        };
 
                         // Warn the main task that block tracing as started, and
                         // this block cache entry should not be removed.
@@ -340,7 +360,7 @@ where
                         // Perform block tracing in a tokio blocking task.
                         let result = async {
                             tokio::task::spawn_blocking(move || {
-                                Self::cache_block(client, backend, block, overrides)
+                                Self::cache_block(client, backend, block, overrides, storage)
                             })
                             .await
                             .map_err(|e| {
@@ -348,6 +368,9 @@ where
                             })?
                         }
                         .await;
+                        if result.is_ok() {
+                            metrics.report(|metrics| metrics.traced_blocks.inc());
+                        }
 
                         tracing::trace!("Block tracing finished, sending result to main task.");
 
@@ -367,13 +390,17 @@ where
                         state: CacheBlockState::Pooled {
                             started: false,
                             waiting_requests: vec![],
-                            unqueue_sender,
+                            ticket_sender,
+                            scheduled: None,
                         },
                     },
                 );
             }
         }
 
+        // Only the first blocks of the range are traced ahead of the request consuming it.
+        self.prefetch(self.next_batch_id, self.scheduler.permits());
+
         // Respond with the batch ID.
         let _ = sender.send(CacheBatchId(self.next_batch_id));
 
@@ -385,7 +412,9 @@ where
     /// - If the result is stored in the cache, it sends it immediatly.
     /// - If the block is currently being pooled, it is added in this block cache waiting list, and
     ///   all requests concerning this block will be satisfied when the tracing for this block is
-    ///   finished.
+    ///   finished. If its tracing has not started, it is traced ahead of the blocks prefetched for
+    ///   the batches.
+    /// - The batches containing this block prefetch the blocks following it.
     /// - If this block is missing from the cache, it means no batch asked for it. All requested
     ///   blocks should be contained in a batch beforehand, and thus an error is returned.
     #[instrument(skip(self))]
@@ -415,6 +444,72 @@ where
             );
             let _ = sender
                 .send(Err(format!("RPC request asked a block ({}) that was not batched", block)));
+            return
+        }
+
+        self.schedule(&block, Priority::Backfill);
+
+        // The request consumes the range in order, keep tracing the blocks following this one.
+        let depth = self.scheduler.permits();
+        let windows: Vec<_> = self
+            .batches
+            .iter()
+            .filter_map(|(id, batch)| {
+                let position = batch.blocks.iter().position(|hash| *hash == block)?;
+                Some((*id, position + 1 + depth))
+            })
+            .collect();
+        for (batch_id, until) in windows {
+            self.prefetch(batch_id, until);
+        }
+    }
+
+    /// Schedules the tracing of the blocks of a batch up to `until`, in the order of the batch.
+    fn prefetch(&mut self, batch_id: u64, until: usize) {
+        let blocks = match self.batches.get_mut(&batch_id) {
+            Some(batch) if batch.prefetched < until => {
+                let until = until.min(batch.blocks.len());
+                let blocks = batch.blocks[batch.prefetched.min(until)..until].to_vec();
+                batch.prefetched = batch.prefetched.max(until);
+                blocks
+            },
+            _ => return,
+        };
+        for block in blocks {
+            self.schedule(&block, Priority::Prefetch);
+        }
+    }
+
+    /// Sends a ticket of `priority` to the tracing task of a pooled block, unless it has not
+    /// started and already has one as good. The ticket is taken here rather than in the task so
+    /// the blocks are queued in the order they are scheduled.
+    fn schedule(&mut self, block: &H256, priority: Priority) {
+        if let Some(CacheBlock {
+            state: CacheBlockState::Pooled { started: false, ticket_sender, scheduled, .. },
+            ..
+        }) = self.cached_blocks.get_mut(block)
+        {
+            if scheduled.map_or(true, |scheduled| priority < scheduled) {
+                let _ = ticket_sender.send(self.scheduler.ticket(priority));
+                *scheduled = Some(priority);
+            }
+        }
+    }
+
+    /// Wait for the permit to trace a block of a batch, with the last ticket it was sent.
+    /// Returns `None` if the block is no longer needed before it got one.
+    async fn block_permit(mut tickets: mpsc::UnboundedReceiver<Ticket>) -> Option<Permit> {
+        let mut ticket = tickets.recv().await?;
+        loop {
+            let permit = ticket.acquire().fuse();
+            futures::pin_mut!(permit);
+            select!(
+                permit = permit => return Some(permit),
+                next = tickets.recv().fuse() => {
+                    tracing::trace!("A request waits for the block, tracing it ahead of the batch.");
+                    ticket = next?;
+                },
+            )
         }
     }
 
@@ -427,8 +522,10 @@ where
     #[instrument(skip(self))]
     fn request_stop_batch(&mut self, batch_id: CacheBatchId) {
         tracing::trace!("Stopping batch {}", batch_id.0);
-        if let Some(blocks) = self.batches.get(&batch_id.0) {
-            for block in blocks {
+        if let Some(batch) = self.batches.get_mut(&batch_id.0) {
+            // The batch no longer prefetches its blocks.
+            batch.prefetched = batch.blocks.len();
+            for block in &batch.blocks {
                 let mut remove = false;
 
                 // We remove early the block cache if this batch is the last
@@ -498,7 +595,7 @@ where
     #[instrument(skip(self))]
     fn expired_batch(&mut self, batch_id: CacheBatchId) {
         if let Some(batch) = self.batches.remove(&batch_id.0) {
-            for block in batch {
+            for block in batch.blocks {
                 // For each block of the batch, we remove it if it was the
                 // last batch containing it.
                 let mut remove = false;
@@ -524,6 +621,7 @@ where
         backend: Arc<BE>,
         substrate_hash: H256,
         overrides: Arc<OverrideHandle<B>>,
+        storage: bool,
     ) -> TxsTraceRes {
         let substrate_block_id = BlockId::Hash(substrate_hash);
 
@@ -562,7 +660,12 @@ where
         };
 
         let eth_block_hash = eth_block.header.hash();
-        let eth_tx_hashes = eth_transactions.iter().map(|t| t.transaction_hash).collect();
+        let eth_tx_hashes: Vec<_> = eth_transactions.iter().map(|t| t.transaction_hash).collect();
+
+        // If nothing in the block enters the EVM return empty trace right away.
+        if !block_enters_evm(client.as_ref(), &substrate_block_id, eth_tx_hashes.len())? {
+            return Ok(vec![])
+        }
 
         // Get extrinsics (containing Ethereum ones)
         let extrinsics = backend
@@ -591,7 +694,8 @@ where
             Ok(primitives_rpc::debug::Response::Block)
         };
 
-        let mut proxy = amax_eva_client_evm_tracing::listeners::CallList::default();
+        let mut proxy =
+            amax_eva_client_evm_tracing::listeners::CallList::default().with_storage(storage);
         proxy.using(f)?;
         let mut traces: Vec<_> =
             amax_eva_client_evm_tracing::formatters::TraceFilter::format(proxy).unwrap();
@@ -599,20 +703,23 @@ where
         for trace in traces.iter_mut() {
             trace.block_hash = eth_block_hash;
             trace.block_number = height;
-            trace.transaction_hash = eth_transactions
-                .get(trace.transaction_position as usize)
-                .ok_or_else(|| {
-                    tracing::warn!(
-                        "Bug: A transaction has been replayed while it shouldn't (in block {}).",
-                        height
-                    );
+            // The traces of the Substrate extrinsics have no Ethereum transaction.
+            if let Some(position) = trace.transaction_position {
+                trace.transaction_hash = eth_transactions
+                    .get(position as usize)
+                    .ok_or_else(|| {
+                        tracing::warn!(
+                            "Bug: A transaction has been replayed while it shouldn't (in block {}).",
+                            height
+                        );
 
-                    format!(
-                        "Bug: A transaction has been replayed while it shouldn't (in block {}).",
-                        height
-                    )
-                })?
-                .transaction_hash;
+                        format!(
+                            "Bug: A transaction has been replayed while it shouldn't (in block {}).",
+                            height
+                        )
+                    })?
+                    .transaction_hash;
+            }
 
             // Reformat error messages.
             if let block::TransactionTraceOutput::Error(ref mut error) = trace.output {
@@ -7,6 +7,8 @@
 //! - A main `CacheTask` managing the cache and the communication between tasks.
 //! - For each traced block an async task responsible to wait for a permit, spawn a blocking task
 //!   and waiting for the result, then send it to the main `CacheTask`.
+//! - A `TracingScheduler` granting the permits to the blocks of the ranges after the interactive
+//!   requests of the `debug` and `trace` modules, the block a request waits for first.
 
 use std::{marker::PhantomData, sync::Arc};
 
@@ -15,7 +17,7 @@ use futures::SinkExt;
 use jsonrpsee::core::RpcResult;
 use tokio::sync::oneshot;
 
-use sp_api::{BlockId, HeaderT};
+use sp_api::{ApiExt, BlockId, HeaderT, ProvideRuntimeApi};
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 use sp_runtime::traits::Block as BlockT;
 
@@ -26,15 +28,48 @@ use amax_eva_client_evm_tracing::types::{
     replay::{TraceResults, TraceResultsWithTransactionHash},
 };
 pub use amax_eva_rpc_core::{FilterRequest, RequestBlockId, RequestBlockTag, TraceServer};
+use primitives_rpc::debug::DebugRuntimeApi;
+
+use crate::MetricsLink;
 
 mod cache;
 pub use cache::{CacheRequester, CacheTask};
 
+mod scheduler;
+pub use scheduler::{Permit, Priority, Ticket, TracingScheduler};
+
 mod trace;
 pub use trace::{Requester as TraceRequester, TraceTask};
 
 type TxsTraceRes = Result<Vec<TransactionTrace>, String>;
 
+/// Whether replaying the block at `id` traces any call, i.e. it has Ethereum transactions or
+/// extrinsics entering the EVM. The runtimes before the version 2 of `DebugRuntimeApi` only trace
+/// the Ethereum transactions.
+pub(crate) fn block_enters_evm<B, C>(
+    client: &C,
+    id: &BlockId<B>,
+    eth_transactions: usize,
+) -> Result<bool, String>
+where
+    B: BlockT,
+    C: ProvideRuntimeApi<B>,
+    C::Api: DebugRuntimeApi<B>,
+{
+    if eth_transactions > 0 {
+        return Ok(true)
+    }
+    let api = client.runtime_api();
+    let runtime_error = |e| format!("Runtime api access error: {:?}", e);
+    if !api
+        .has_api_with::<dyn DebugRuntimeApi<B>, _>(id, |version| version >= 2)
+        .map_err(runtime_error)?
+    {
+        return Ok(false)
+    }
+    Ok(!api.extrinsics_entering_evm(id).map_err(runtime_error)?.is_empty())
+}
+
 /// RPC handler. Will communicate with a `CacheTask` through a `CacheRequester`.
 pub struct Trace<B, C> {
     _phantom: PhantomData<B>,
@@ -42,6 +77,7 @@ pub struct Trace<B, C> {
     trace_filter_requester: CacheRequester,
     trace_requester: TraceRequester,
     max_count: u32,
+    metrics: MetricsLink,
 }
 
 impl<B, C> Clone for Trace<B, C> {
@@ -52,6 +88,7 @@ impl<B, C> Clone for Trace<B, C> {
             trace_filter_requester: self.trace_filter_requester.clone(),
             trace_requester: self.trace_requester.clone(),
             max_count: self.max_count,
+            metrics: self.metrics.clone(),
         }
     }
 }
@@ -69,12 +106,14 @@ where
         trace_filter_requester: CacheRequester,
         trace_requester: TraceRequester,
         max_count: u32,
+        metrics: MetricsLink,
     ) -> Self {
         Self {
             client,
             trace_filter_requester,
             trace_requester,
             max_count,
+            metrics,
             _phantom: PhantomData::default(),
         }
     }
@@ -99,6 +138,7 @@ where
         self,
         transaction_hash: H256,
     ) -> RpcResult<Option<Vec<TransactionTrace>>> {
+        let _timer = self.metrics.trace_request_timer("trace_transaction");
         let mut trace_requester = self.trace_requester.clone();
         let (tx, rx) = oneshot::channel();
 
@@ -120,6 +160,7 @@ where
         self,
         number: RequestBlockId,
     ) -> RpcResult<Option<Vec<TransactionTrace>>> {
+        let _timer = self.metrics.trace_request_timer("trace_block");
         let mut trace_requester = self.trace_requester.clone();
         let (tx, rx) = oneshot::channel();
 
@@ -127,11 +168,14 @@ where
         trace_requester.send((trace::Request::Block(number), tx)).await?;
 
         // Receive a message from the service level task and send the rpc response.
-        rx.await
+        let traces = rx
+            .await
             .map_err(|err| internal_err(format!("trace service dropped the channel : {:?}", err)))?
             .map(|res| match res {
-                trace::Response::Traces(res) => Some(res),
-            })
+                trace::Response::Traces(res) => res,
+            })?;
+        self.metrics.report(|metrics| metrics.traced_blocks.inc());
+        Ok(Some(traces))
     }
 
     /// Executes the transaction with the given hash and returns a number of possible traces for it.
@@ -151,6 +195,7 @@ where
 
     /// `trace_filter` endpoint (wrapped in the trait implementation with futures compatibilty)
     async fn filter(self, req: FilterRequest) -> RpcResult<Vec<TransactionTrace>> {
+        let _timer = self.metrics.trace_request_timer("trace_filter");
         let from_block = self.block_id(req.from_block)?;
         let to_block = self.block_id(req.to_block)?;
         let block_heights = from_block..=to_block;
@@ -199,6 +244,10 @@ where
         // start the expiration delay.
         self.trace_filter_requester.stop_batch(batch_id).await;
 
+        if let Ok(traces) = &res {
+            self.metrics
+                .report(|metrics| metrics.trace_filter_result_size.observe(traces.len() as f64));
+        }
         res
     }
 }
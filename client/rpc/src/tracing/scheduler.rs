@@ -0,0 +1,274 @@
+//! Scheduler of the tracing tasks of the `debug` and `trace` modules.
+//!
+//! The tasks share a fixed number of permits, each running a single trace at a time in a blocking
+//! task. When all are taken, the waiting tasks are granted the released permits by `Priority`,
+//! then in the order of their `Ticket`, taken synchronously before the task is spawned. The
+//! requests of users waiting for a single trace thus jump ahead of the blocks of the
+//! `trace_filter` ranges, which the cache task prefetches a few at a time in the order of the
+//! range.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use tokio::sync::oneshot;
+
+use crate::MetricsLink;
+
+/// The priority of a tracing task waiting for a permit, higher first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// A request of a user waiting for a single trace: `debug_trace*`, `trace_transaction` and
+    /// `trace_block`.
+    Interactive,
+    /// The block of a `trace_filter` range its request is waiting for.
+    Backfill,
+    /// The upcoming blocks of a `trace_filter` range, traced ahead of the request.
+    Prefetch,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Interactive => "interactive",
+            Priority::Backfill => "backfill",
+            Priority::Prefetch => "prefetch",
+        }
+    }
+}
+
+struct State {
+    /// The permits not taken, always 0 if a task is waiting.
+    available: usize,
+    /// The tasks waiting for a permit, by priority and order of arrival.
+    waiting: BTreeMap<(Priority, u64), oneshot::Sender<Permit>>,
+    next_ticket: u64,
+    metrics: MetricsLink,
+}
+
+impl State {
+    fn enqueue(&mut self, ticket: (Priority, u64), sender: oneshot::Sender<Permit>) {
+        self.waiting.insert(ticket, sender);
+        self.metrics.report(|metrics| {
+            metrics.tracing_queue_depth.with_label_values(&[ticket.0.as_str()]).inc()
+        });
+    }
+
+    fn dequeue(&mut self, ticket: &(Priority, u64)) -> Option<oneshot::Sender<Permit>> {
+        let sender = self.waiting.remove(ticket)?;
+        self.metrics.report(|metrics| {
+            metrics.tracing_queue_depth.with_label_values(&[ticket.0.as_str()]).dec()
+        });
+        Some(sender)
+    }
+}
+
+/// Grants the permits to run the traces, see the module documentation.
+#[derive(Clone)]
+pub struct TracingScheduler {
+    permits: usize,
+    state: Arc<Mutex<State>>,
+}
+
+impl TracingScheduler {
+    pub fn new(permits: usize, metrics: MetricsLink) -> Self {
+        Self {
+            permits,
+            state: Arc::new(Mutex::new(State {
+                available: permits,
+                waiting: BTreeMap::new(),
+                next_ticket: 0,
+                metrics,
+            })),
+        }
+    }
+
+    /// The number of traces run concurrently.
+    pub fn permits(&self) -> usize {
+        self.permits
+    }
+
+    /// The number of traces running.
+    pub fn running(&self) -> usize {
+        self.permits - lock(&self.state).available
+    }
+
+    /// The number of tasks of `priority` waiting for a permit.
+    pub fn queue_depth(&self, priority: Priority) -> usize {
+        lock(&self.state)
+            .waiting
+            .keys()
+            .filter(|(queued, _)| *queued == priority)
+            .count()
+    }
+
+    /// Takes a place in the queue of the tasks of `priority`, after the tickets taken before.
+    pub fn ticket(&self, priority: Priority) -> Ticket {
+        let mut state = lock(&self.state);
+        let sequence = state.next_ticket;
+        state.next_ticket += 1;
+        Ticket { state: self.state.clone(), ticket: (priority, sequence) }
+    }
+
+    /// Waits for a permit to run a trace, after the tickets already taken.
+    pub async fn acquire(&self, priority: Priority) -> Permit {
+        self.ticket(priority).acquire().await
+    }
+}
+
+/// A place in the queue of the tasks waiting for a permit.
+pub struct Ticket {
+    state: Arc<Mutex<State>>,
+    ticket: (Priority, u64),
+}
+
+impl Ticket {
+    /// Waits for a permit to run a trace, released when dropped. A task dropping the future leaves
+    /// the queue.
+    pub async fn acquire(self) -> Permit {
+        let Self { state: shared, ticket } = self;
+        let priority = ticket.0;
+        let started = Instant::now();
+        let (receiver, metrics) = {
+            let mut state = lock(&shared);
+            // No task waits while a permit is available.
+            if state.available > 0 {
+                state.available -= 1;
+                state.metrics.report(|metrics| {
+                    metrics.tracing_permit_wait.with_label_values(&[priority.as_str()]).observe(0.0)
+                });
+                return Permit { state: Some(shared.clone()) }
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.enqueue(ticket, sender);
+            tracing::trace!(
+                "Waiting for a tracing permit ({:?}, {} waiting)",
+                priority,
+                state.waiting.len()
+            );
+            (receiver, state.metrics.clone())
+        };
+
+        let _queued = Queued { state: shared, ticket };
+        let permit = receiver
+            .await
+            .expect("A waiting task is only removed from the queue with its permit; qed");
+        metrics.report(|metrics| {
+            metrics
+                .tracing_permit_wait
+                .with_label_values(&[priority.as_str()])
+                .observe(started.elapsed().as_secs_f64())
+        });
+        permit
+    }
+}
+
+/// Removes a task from the queue if it stops waiting.
+struct Queued {
+    state: Arc<Mutex<State>>,
+    ticket: (Priority, u64),
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        lock(&self.state).dequeue(&self.ticket);
+    }
+}
+
+/// The permit to run a trace, given to the next waiting task when dropped.
+pub struct Permit {
+    // Only `None` for a permit which could not be given and is already released.
+    state: Option<Arc<Mutex<State>>>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let state = match self.state.take() {
+            Some(state) => state,
+            None => return,
+        };
+        let mut guard = lock(&state);
+        while let Some(ticket) = guard.waiting.keys().next().copied() {
+            let waiter = guard.dequeue(&ticket).expect("The key was just read; qed");
+            match waiter.send(Permit { state: Some(state.clone()) }) {
+                Ok(()) => return,
+                // The task stopped waiting before leaving the queue.
+                Err(mut permit) => permit.state = None,
+            }
+        }
+        guard.available += 1;
+    }
+}
+
+fn lock(state: &Mutex<State>) -> std::sync::MutexGuard<'_, State> {
+    state.lock().expect("tracing scheduler lock poisoned")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    fn scheduler(permits: usize) -> TracingScheduler {
+        TracingScheduler::new(permits, MetricsLink::new(None))
+    }
+
+    #[test]
+    fn grants_the_permits_by_priority_then_ticket() {
+        let scheduler = scheduler(1);
+        let permit = scheduler.acquire(Priority::Interactive).now_or_never().unwrap();
+
+        let prefetch = scheduler.ticket(Priority::Prefetch);
+        let backfill_1 = scheduler.ticket(Priority::Backfill);
+        let backfill_2 = scheduler.ticket(Priority::Backfill);
+        let interactive = scheduler.ticket(Priority::Interactive);
+        // Polled in another order than the tickets were taken.
+        let mut waiting = vec![
+            ("prefetch", prefetch.acquire().boxed()),
+            ("backfill 2", backfill_2.acquire().boxed()),
+            ("interactive", interactive.acquire().boxed()),
+            ("backfill 1", backfill_1.acquire().boxed()),
+        ];
+        for (_, task) in waiting.iter_mut() {
+            assert!(task.now_or_never().is_none());
+        }
+        assert_eq!(scheduler.queue_depth(Priority::Backfill), 2);
+
+        let mut granted = vec![];
+        let mut permit = Some(permit);
+        while !waiting.is_empty() {
+            drop(permit.take());
+            let (index, next) = waiting
+                .iter_mut()
+                .enumerate()
+                .find_map(|(index, (_, task))| task.now_or_never().map(|permit| (index, permit)))
+                .expect("A released permit is granted to a waiting task");
+            granted.push(waiting.remove(index).0);
+            permit = Some(next);
+        }
+        assert_eq!(granted, ["interactive", "backfill 1", "backfill 2", "prefetch"]);
+
+        drop(permit);
+        assert_eq!(scheduler.running(), 0);
+    }
+
+    #[test]
+    fn skips_the_tasks_which_stopped_waiting() {
+        let scheduler = scheduler(1);
+        let permit = scheduler.acquire(Priority::Interactive).now_or_never().unwrap();
+
+        let mut first = scheduler.ticket(Priority::Backfill).acquire().boxed();
+        let mut second = scheduler.ticket(Priority::Backfill).acquire().boxed();
+        assert!((&mut first).now_or_never().is_none());
+        assert!((&mut second).now_or_never().is_none());
+        assert_eq!(scheduler.queue_depth(Priority::Backfill), 2);
+
+        drop(first);
+        assert_eq!(scheduler.queue_depth(Priority::Backfill), 1);
+        drop(permit);
+        assert!(second.now_or_never().is_some());
+        assert_eq!(scheduler.running(), 0);
+    }
+}
@@ -3,7 +3,7 @@ use std::{future::Future, marker::PhantomData, sync::Arc};
 use ethereum_types::H256;
 use futures::StreamExt;
 use jsonrpsee::core::RpcResult;
-use tokio::sync::{oneshot, Semaphore};
+use tokio::sync::oneshot;
 
 use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
 use sc_utils::mpsc::TracingUnboundedSender;
@@ -24,6 +24,8 @@ use amax_eva_client_evm_tracing::{
 use amax_eva_rpc_core::{RequestBlockId, RequestBlockTag};
 use primitives_rpc::debug::DebugRuntimeApi;
 
+use super::{block_enters_evm, Priority, TracingScheduler};
+
 pub enum Request {
     Transaction(H256),
     Block(RequestBlockId),
@@ -60,6 +62,7 @@ where
         frontier_backend: Arc<fc_db::Backend<B>>,
         transaction_hash: H256,
         overrides: Arc<OverrideHandle<B>>,
+        storage: bool,
     ) -> RpcResult<Response> {
         // load the transaction's ethereum_block_hash hash and index.
         let (hash, index) = match frontier_backend_client::load_transactions::<B, C>(
@@ -133,7 +136,8 @@ where
                     Ok(primitives_rpc::debug::Response::Single)
                 };
 
-                let mut proxy = amax_eva_client_evm_tracing::listeners::CallList::default();
+                let mut proxy = amax_eva_client_evm_tracing::listeners::CallList::default()
+                    .with_storage(storage);
                 proxy.using(f)?;
                 proxy.finish_transaction();
 
@@ -146,7 +150,7 @@ where
                     trace.block_hash = eth_block_hash;
                     trace.block_number = height;
                     trace.transaction_hash = eth_transactions
-                        .get(trace.transaction_position as usize)
+                        .get(trace.transaction_position.unwrap_or_default() as usize)
                         .ok_or_else(|| {
                             tracing::warn!(
                                 "Bug: A transaction has been replayed while it shouldn't (in block {}).",
@@ -183,6 +187,7 @@ where
         frontier_backend: Arc<fc_db::Backend<B>>,
         request_block_id: RequestBlockId,
         overrides: Arc<OverrideHandle<B>>,
+        storage: bool,
     ) -> RpcResult<Response> {
         let reference_id: BlockId<B> = match request_block_id {
             RequestBlockId::Number(n) => Ok(BlockId::Number(n.unique_saturated_into())),
@@ -236,8 +241,10 @@ where
         let eth_tx_hashes: Vec<_> =
             eth_transactions.clone().unwrap().iter().map(|t| t.transaction_hash).collect();
 
-        // If there are no ethereum transactions in the block return empty trace right away.
-        if eth_tx_hashes.is_empty() {
+        // If nothing in the block enters the EVM return empty trace right away.
+        if !block_enters_evm(client.as_ref(), &reference_id, eth_tx_hashes.len())
+            .map_err(internal_err)?
+        {
             return Ok(Response::Traces(vec![]))
         }
 
@@ -265,7 +272,8 @@ where
             Ok(primitives_rpc::debug::Response::Block)
         };
 
-        let mut proxy = amax_eva_client_evm_tracing::listeners::CallList::default();
+        let mut proxy =
+            amax_eva_client_evm_tracing::listeners::CallList::default().with_storage(storage);
         proxy.using(f)?;
         proxy.finish_transaction();
 
@@ -282,20 +290,23 @@ where
             for trace in traces.iter_mut() {
                 trace.block_hash = eth_block_hash;
                 trace.block_number = height;
-                trace.transaction_hash = eth_transactions
-                    .get(trace.transaction_position as usize)
-                    .ok_or_else(|| {
-                        tracing::warn!(
-                            "Bug: A transaction has been replayed while it shouldn't (in block {}).",
-                            height
-                        );
-
-                        internal_err(format!(
-                            "Bug: A transaction has been replayed while it shouldn't (in block {}).",
-                            height
-                        ))
-                    })?
-                    .transaction_hash;
+                // The traces of the Substrate extrinsics have no Ethereum transaction.
+                if let Some(position) = trace.transaction_position {
+                    trace.transaction_hash = eth_transactions
+                        .get(position as usize)
+                        .ok_or_else(|| {
+                            tracing::warn!(
+                                "Bug: A transaction has been replayed while it shouldn't (in block {}).",
+                                height
+                            );
+
+                            internal_err(format!(
+                                "Bug: A transaction has been replayed while it shouldn't (in block {}).",
+                                height
+                            ))
+                        })?
+                        .transaction_hash;
+                }
 
                 // Reformat error messages.
                 if let types::block::TransactionTraceOutput::Error(ref mut error) = trace.output {
@@ -326,13 +337,14 @@ where
     C::Api: ApiExt<B>,
 {
     /// Task spawned at service level that listens for messages on the rpc channel and spawns
-    /// blocking tasks using a permit pool.
+    /// blocking tasks scheduled ahead of the `trace_filter` backfills.
     pub fn create(
         client: Arc<C>,
         backend: Arc<BE>,
         frontier_backend: Arc<fc_db::Backend<B>>,
-        permit_pool: Arc<Semaphore>,
+        scheduler: TracingScheduler,
         overrides: Arc<OverrideHandle<B>>,
+        storage: bool,
     ) -> (impl Future<Output = ()>, Requester) {
         // Communication with the outside world :
         let (tx, mut rx): (Requester, _) = sc_utils::mpsc::tracing_unbounded("trace-requester");
@@ -345,13 +357,13 @@ where
                         let client = client.clone();
                         let backend = backend.clone();
                         let frontier_backend = frontier_backend.clone();
-                        let permit_pool = permit_pool.clone();
+                        let scheduler = scheduler.clone();
                         let overrides = overrides.clone();
 
                         tokio::task::spawn(async move {
                             let _ = response_tx.send(
                                 async {
-                                    let _permit = permit_pool.acquire().await;
+                                    let _permit = scheduler.acquire(Priority::Interactive).await;
                                     tokio::task::spawn_blocking(move || {
                                         Self::handle_trace_transaction_req(
                                             client.clone(),
@@ -359,6 +371,7 @@ where
                                             frontier_backend.clone(),
                                             transaction_hash,
                                             overrides,
+                                            storage,
                                         )
                                     })
                                     .await
@@ -377,13 +390,13 @@ where
                         let client = client.clone();
                         let backend = backend.clone();
                         let frontier_backend = frontier_backend.clone();
-                        let permit_pool = permit_pool.clone();
+                        let scheduler = scheduler.clone();
                         let overrides = overrides.clone();
 
                         tokio::task::spawn(async move {
                             let _ = response_tx.send(
                                 async {
-                                    let _permit = permit_pool.acquire().await;
+                                    let _permit = scheduler.acquire(Priority::Interactive).await;
 
                                     tokio::task::spawn_blocking(move || {
                                         Self::handle_trace_block_req(
@@ -392,6 +405,7 @@ where
                                             frontier_backend.clone(),
                                             request_block_id,
                                             overrides,
+                                            storage,
                                         )
                                     })
                                     .await
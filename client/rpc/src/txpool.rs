@@ -1,37 +1,177 @@
-use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
+use ethereum::TransactionV2 as EthereumTransaction;
 use ethereum_types::{H160, H256, U256};
-use jsonrpsee::core::RpcResult;
+use futures::{stream::BoxStream, FutureExt, StreamExt};
+use jsonrpsee::{core::RpcResult, types::SubscriptionResult, SubscriptionSink};
 use serde::Serialize;
 
 // Substrate
+use sc_client_api::BlockchainEvents;
 use sc_transaction_pool::{ChainApi, Pool};
 use sc_transaction_pool_api::InPoolTransaction;
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
-use sp_core::hashing::keccak_256;
+use sp_core::{hashing::keccak_256, traits::SpawnNamed};
 use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 
 // Frontier
 use fc_rpc::{internal_err, public_key};
+use fp_rpc::EthereumRuntimeRPCApi;
 
 // Local
 pub use amax_eva_rpc_core::{
-    Content, Get as GetT, Inspect, TransactionMap, TxPoolApiServer, TxPoolResult,
+    Content, Get as GetT, Inspect, PoolEventKind, PoolTransactionEvent, TransactionMap,
+    TxPoolApiServer, TxPoolResult,
 };
 pub use primitives_rpc::txpool::{TxPoolResponse, TxPoolRuntimeApi};
 
+use crate::MetricsLink;
+
+/// The interval the subscriptions check the pool at, besides the pool and block imports which
+/// don't notify the transactions added to the future queue.
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Geth `txpool` API implementation.
 pub struct TxPool<B: BlockT, C, A: ChainApi> {
     client: Arc<C>,
     graph: Arc<Pool<A>>,
+    subscriptions: Arc<dyn SpawnNamed>,
+    subscribers: Arc<Mutex<PoolSubscribers>>,
     _marker: PhantomData<B>,
 }
 
 impl<B: BlockT, C, A: ChainApi> TxPool<B, C, A> {
-    pub fn new(client: Arc<C>, graph: Arc<Pool<A>>) -> Self {
-        Self { client, graph, _marker: PhantomData }
+    pub fn new(client: Arc<C>, graph: Arc<Pool<A>>, subscriptions: Arc<dyn SpawnNamed>) -> Self {
+        Self { client, graph, subscriptions, subscribers: Default::default(), _marker: PhantomData }
+    }
+}
+
+/// The hash and the sender of an Ethereum transaction.
+pub(crate) fn hash_and_sender(txn: &EthereumTransaction) -> (H256, H160) {
+    let hash = H256::from(keccak_256(&rlp::encode(txn)));
+    let from = match public_key(txn) {
+        Ok(pk) => H160::from(H256::from(keccak_256(&pk))),
+        Err(_e) => H160::default(),
+    };
+    (hash, from)
+}
+
+/// Notifies the pool may have changed, on the pool and block imports.
+fn pool_imports<B, C, A>(client: &C, graph: &Pool<A>) -> BoxStream<'static, ()>
+where
+    B: BlockT,
+    C: BlockchainEvents<B>,
+    A: ChainApi<Block = B>,
+{
+    let pool_imports = graph.validated_pool().import_notification_stream().map(|_| ());
+    let block_imports = client.import_notification_stream().map(|_| ());
+    futures::stream::select(pool_imports, block_imports).boxed()
+}
+
+/// Notifies the pool may have changed, on the pool and block imports and every
+/// `SUBSCRIPTION_POLL_INTERVAL`.
+fn pool_updates<B, C, A>(client: &C, graph: &Pool<A>) -> BoxStream<'static, ()>
+where
+    B: BlockT,
+    C: BlockchainEvents<B>,
+    A: ChainApi<Block = B>,
+{
+    let polls = futures::stream::unfold(
+        tokio::time::interval(SUBSCRIPTION_POLL_INTERVAL),
+        |mut interval| async move {
+            interval.tick().await;
+            Some(((), interval))
+        },
+    );
+    futures::stream::select(pool_imports(client, graph), polls).boxed()
+}
+
+/// The Ethereum transactions of the pool known by a subscription, by extrinsic hash. The other
+/// extrinsics are known as `None`, so that they are only decoded once.
+type KnownTransactions = HashMap<H256, Option<Content>>;
+
+/// Update the `known` transactions to the ones of the pool, returning the changes.
+fn pool_events<B, C, A>(
+    client: &C,
+    graph: &Pool<A>,
+    known: &mut KnownTransactions,
+) -> RpcResult<Vec<PoolTransactionEvent>>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+    C::Api: TxPoolRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+    A: ChainApi<Block = B> + 'static,
+{
+    let pool = graph.validated_pool();
+    let mut extrinsics = pool
+        .ready()
+        .map(|in_pool_tx| (*in_pool_tx.hash(), in_pool_tx.data().clone()))
+        .collect::<Vec<_>>();
+    extrinsics.extend(pool.futures());
+    let hashes = extrinsics.iter().map(|(hash, _)| *hash).collect::<HashSet<_>>();
+
+    let best_block: BlockId<B> = BlockId::Hash(client.info().best_hash);
+    let api = client.runtime_api();
+
+    // Decode the new extrinsics one by one, to map them to their extrinsic hash.
+    let mut added = Vec::new();
+    for (hash, extrinsic) in extrinsics {
+        if known.contains_key(&hash) {
+            continue
+        }
+        let ethereum_txns: TxPoolResponse =
+            api.extrinsic_filter(&best_block, vec![extrinsic], vec![]).map_err(|err| {
+                internal_err(format!("fetch runtime extrinsic filter failed: {:?}", err))
+            })?;
+        let content = ethereum_txns.ready.first().map(|txn| {
+            let (hash, from) = hash_and_sender(txn);
+            Content::get(hash, from, txn)
+        });
+        if let Some(content) = &content {
+            added.push(content.clone());
+        }
+        known.insert(hash, content);
     }
+
+    let removed_hashes =
+        known.keys().filter(|hash| !hashes.contains(hash)).copied().collect::<Vec<_>>();
+    let removed = removed_hashes.iter().filter_map(|hash| known.remove(hash).flatten());
+
+    let mut events = added
+        .iter()
+        .map(|content| PoolTransactionEvent {
+            kind: PoolEventKind::Added,
+            transaction: content.clone(),
+            replaced_by: None,
+        })
+        .collect::<Vec<_>>();
+    for content in removed {
+        let replaced_by = added
+            .iter()
+            .find(|added| added.from == content.from && added.nonce == content.nonce)
+            .map(|added| added.hash);
+        let kind = if replaced_by.is_some() {
+            PoolEventKind::Replaced
+        } else {
+            let account = api
+                .account_basic(&best_block, content.from)
+                .map_err(|err| internal_err(format!("fetch runtime account failed: {:?}", err)))?;
+            if account.nonce > content.nonce {
+                PoolEventKind::Mined
+            } else {
+                PoolEventKind::Dropped
+            }
+        };
+        events.push(PoolTransactionEvent { kind, transaction: content, replaced_by });
+    }
+    Ok(events)
 }
 
 impl<B, C, A> TxPool<B, C, A>
@@ -78,11 +218,7 @@ where
         // Build the T response.
         let mut pending = TransactionMap::<T>::new();
         for txn in ethereum_txns.ready.iter() {
-            let hash = H256::from(keccak_256(&rlp::encode(txn)));
-            let from = match public_key(txn) {
-                Ok(pk) => H160::from(H256::from(keccak_256(&pk))),
-                Err(_e) => H160::default(),
-            };
+            let (hash, from) = hash_and_sender(txn);
             pending
                 .entry(from)
                 .or_insert_with(HashMap::new)
@@ -91,11 +227,7 @@ where
 
         let mut queued = TransactionMap::<T>::new();
         for txn in ethereum_txns.future.iter() {
-            let hash = H256::from(keccak_256(&rlp::encode(txn)));
-            let from = match public_key(txn) {
-                Ok(pk) => H160::from(H256::from(keccak_256(&pk))),
-                Err(_e) => H160::default(),
-            };
+            let (hash, from) = hash_and_sender(txn);
             queued
                 .entry(from)
                 .or_insert_with(HashMap::new)
@@ -109,15 +241,23 @@ where
 impl<B, C, A> TxPoolApiServer for TxPool<B, C, A>
 where
     B: BlockT<Hash = H256> + Send + Sync + 'static,
-    C: ProvideRuntimeApi<B>,
-    C: HeaderMetadata<B, Error = BlockChainError> + HeaderBackend<B> + 'static,
-    C::Api: TxPoolRuntimeApi<B>,
+    C: ProvideRuntimeApi<B> + BlockchainEvents<B>,
+    C: HeaderMetadata<B, Error = BlockChainError> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: TxPoolRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
     A: ChainApi<Block = B> + 'static,
 {
     fn content(&self) -> RpcResult<TxPoolResult<TransactionMap<Content>>> {
         self.map_build::<Content>()
     }
 
+    fn content_from(&self, address: H160) -> RpcResult<TxPoolResult<HashMap<U256, Content>>> {
+        let TxPoolResult { mut pending, mut queued } = self.map_build::<Content>()?;
+        Ok(TxPoolResult {
+            pending: pending.remove(&address).unwrap_or_default(),
+            queued: queued.remove(&address).unwrap_or_default(),
+        })
+    }
+
     fn inspect(&self) -> RpcResult<TxPoolResult<TransactionMap<Inspect>>> {
         self.map_build::<Inspect>()
     }
@@ -126,4 +266,285 @@ where
         let status = self.graph.validated_pool().status();
         Ok(TxPoolResult { pending: U256::from(status.ready), queued: U256::from(status.future) })
     }
+
+    fn subscribe(&self, mut sink: SubscriptionSink, address: Option<H160>) -> SubscriptionResult {
+        sink.accept()?;
+
+        let (sender, receiver) = tracing_unbounded("mpsc_txpool_subscription");
+        let start = {
+            let mut subscribers = lock(&self.subscribers);
+            subscribers.senders.push(sender);
+            !std::mem::replace(&mut subscribers.running, true)
+        };
+        if start {
+            self.subscriptions.spawn(
+                "txpool-rpc-events",
+                Some("rpc"),
+                pool_events_task(self.client.clone(), self.graph.clone(), self.subscribers.clone())
+                    .boxed(),
+            );
+        }
+
+        let events = receiver
+            .filter(move |event| {
+                futures::future::ready(
+                    address.map_or(true, |address| address == event.transaction.from),
+                )
+            })
+            .boxed();
+        let fut = async move {
+            sink.pipe_from_stream(events).await;
+        };
+        self.subscriptions.spawn("txpool-rpc-subscription", Some("rpc"), fut.boxed());
+        Ok(())
+    }
+}
+
+/// The subscribers of the pool events, which are computed once for all of them by a task running
+/// while there are subscribers.
+#[derive(Default)]
+struct PoolSubscribers {
+    senders: Vec<TracingUnboundedSender<PoolTransactionEvent>>,
+    /// Whether the task computing the events is running.
+    running: bool,
+}
+
+impl PoolSubscribers {
+    /// Sends the `events` to the subscribers, forgetting the ones which unsubscribed. The task
+    /// must stop if no subscriber is left, which is returned.
+    fn notify(&mut self, events: &[PoolTransactionEvent]) -> bool {
+        self.senders.retain(|sender| {
+            !sender.is_closed() &&
+                events.iter().all(|event| sender.unbounded_send(event.clone()).is_ok())
+        });
+        self.running = !self.senders.is_empty();
+        self.running
+    }
+
+    /// Ends the subscriptions, when the task stops without being able to compute the events.
+    fn stop(&mut self) {
+        self.senders.clear();
+        self.running = false;
+    }
+}
+
+fn lock(subscribers: &Mutex<PoolSubscribers>) -> std::sync::MutexGuard<'_, PoolSubscribers> {
+    subscribers.lock().expect("txpool subscribers lock poisoned")
+}
+
+/// Task computing the events of the pool for all the `subscribers`, until none is left.
+async fn pool_events_task<B, C, A>(
+    client: Arc<C>,
+    graph: Arc<Pool<A>>,
+    subscribers: Arc<Mutex<PoolSubscribers>>,
+) where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + BlockchainEvents<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: TxPoolRuntimeApi<B> + EthereumRuntimeRPCApi<B>,
+    A: ChainApi<Block = B> + 'static,
+{
+    // The transactions already in the pool are not notified.
+    let mut known = KnownTransactions::new();
+    if let Err(err) = pool_events(client.as_ref(), graph.as_ref(), &mut known) {
+        tracing::warn!(target: "txpool", "Failed to read the pool: {:?}", err);
+        lock(&subscribers).stop();
+        return
+    }
+
+    let mut updates = pool_updates(client.as_ref(), graph.as_ref());
+    while updates.next().await.is_some() {
+        // The pool is not read once all the subscribers unsubscribed.
+        if !lock(&subscribers).notify(&[]) {
+            return
+        }
+        let events =
+            pool_events(client.as_ref(), graph.as_ref(), &mut known).unwrap_or_else(|err| {
+                tracing::warn!(target: "txpool", "Failed to read the pool: {:?}", err);
+                Vec::new()
+            });
+        if !lock(&subscribers).notify(&events) {
+            return
+        }
+    }
+    lock(&subscribers).stop();
+}
+
+/// Whether the extrinsics of the pool are Ethereum transactions, by extrinsic hash, so that each
+/// extrinsic is only decoded by the runtime once while it is in the pool.
+struct EthereumExtrinsics<H>(HashMap<H, bool>);
+
+impl<H: std::hash::Hash + Eq> EthereumExtrinsics<H> {
+    fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Counts the Ethereum transactions of the ready and future queues among the `pooled`
+    /// extrinsics, with whether they are ready and their hash. Only the extrinsics not known yet
+    /// are decoded with `is_ethereum`, and the ones which left the pool are forgotten.
+    fn count<E>(
+        &mut self,
+        pooled: impl IntoIterator<Item = (bool, H, E)>,
+        mut is_ethereum: impl FnMut(E) -> RpcResult<bool>,
+    ) -> RpcResult<(u64, u64)> {
+        let mut known = HashMap::new();
+        let (mut ready_count, mut future_count) = (0, 0);
+        for (is_ready, hash, extrinsic) in pooled {
+            let is_ethereum = match self.0.get(&hash) {
+                Some(is_ethereum) => *is_ethereum,
+                None => is_ethereum(extrinsic)?,
+            };
+            match (is_ethereum, is_ready) {
+                (true, true) => ready_count += 1,
+                (true, false) => future_count += 1,
+                (false, _) => (),
+            }
+            known.insert(hash, is_ethereum);
+        }
+        self.0 = known;
+        Ok((ready_count, future_count))
+    }
+}
+
+/// Reports the Ethereum transactions of the ready and future queues of the pool to the metrics.
+fn report_ethereum_counts(metrics: &MetricsLink, counts: RpcResult<(u64, u64)>) {
+    match counts {
+        Ok((ready, future)) => metrics.report(|metrics| {
+            metrics.txpool_ethereum_ready.set(ready);
+            metrics.txpool_ethereum_future.set(future);
+        }),
+        Err(err) => tracing::warn!(target: "txpool", "Failed to read the pool: {:?}", err),
+    }
+}
+
+/// Counts the Ethereum transactions of the ready and future queues of the pool, decoding the
+/// extrinsics not `known` yet with the runtime of the best block.
+fn ethereum_counts<B, C, A>(
+    client: &C,
+    graph: &Pool<A>,
+    known: &mut EthereumExtrinsics<A::Hash>,
+) -> RpcResult<(u64, u64)>
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+    C::Api: TxPoolRuntimeApi<B>,
+    A: ChainApi<Block = B> + 'static,
+{
+    let pool = graph.validated_pool();
+    let ready = pool
+        .ready()
+        .map(|in_pool_tx| (true, in_pool_tx.hash().clone(), in_pool_tx.data().clone()));
+    let future = pool.futures().into_iter().map(|(hash, extrinsic)| (false, hash, extrinsic));
+
+    let best_block: BlockId<B> = BlockId::Hash(client.info().best_hash);
+    let api = client.runtime_api();
+    known.count(ready.chain(future), |extrinsic| {
+        let ethereum_txns: TxPoolResponse =
+            api.extrinsic_filter(&best_block, vec![extrinsic], vec![]).map_err(|err| {
+                internal_err(format!("fetch runtime extrinsic filter failed: {:?}", err))
+            })?;
+        Ok(!ethereum_txns.ready.is_empty())
+    })
+}
+
+/// Task reporting the Ethereum transactions of the ready and future queues of the pool to the
+/// metrics, on the pool and block imports. The transactions added to the future queue, which
+/// the pool doesn't notify, are counted on the next import.
+pub async fn txpool_metrics_task<B, C, A>(client: Arc<C>, graph: Arc<Pool<A>>, metrics: MetricsLink)
+where
+    B: BlockT<Hash = H256> + Send + Sync + 'static,
+    C: ProvideRuntimeApi<B> + BlockchainEvents<B> + HeaderBackend<B> + Send + Sync + 'static,
+    C::Api: TxPoolRuntimeApi<B>,
+    A: ChainApi<Block = B> + 'static,
+{
+    let mut known = EthereumExtrinsics::new();
+    let mut imports = pool_imports(client.as_ref(), graph.as_ref());
+    loop {
+        let counts = ethereum_counts(client.as_ref(), graph.as_ref(), &mut known);
+        report_ethereum_counts(&metrics, counts);
+        if imports.next().await.is_none() {
+            break
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus_endpoint::Registry;
+
+    #[test]
+    fn counts_the_ethereum_transactions_decoding_the_new_extrinsics_once() {
+        let registry = Registry::new();
+        let metrics = MetricsLink::new(Some(&registry));
+        let mut known = EthereumExtrinsics::new();
+        let mut decoded = vec![];
+        // The extrinsics are Ethereum transactions if even.
+        let mut is_ethereum = |extrinsic: u8| {
+            decoded.push(extrinsic);
+            Ok(extrinsic % 2 == 0)
+        };
+
+        let counts = known.count(
+            vec![(true, 1, 0u8), (true, 2, 1), (false, 3, 2), (false, 4, 4)],
+            &mut is_ethereum,
+        );
+        report_ethereum_counts(&metrics, counts);
+        metrics.report(|metrics| {
+            assert_eq!(metrics.txpool_ethereum_ready.get(), 1);
+            assert_eq!(metrics.txpool_ethereum_future.get(), 2);
+        });
+
+        // The extrinsic 3 became ready, 4 left the pool and 5 was imported.
+        let counts = known.count(
+            vec![(true, 1, 0u8), (true, 2, 1), (true, 3, 2), (true, 5, 6)],
+            &mut is_ethereum,
+        );
+        report_ethereum_counts(&metrics, counts);
+        metrics.report(|metrics| {
+            assert_eq!(metrics.txpool_ethereum_ready.get(), 3);
+            assert_eq!(metrics.txpool_ethereum_future.get(), 0);
+        });
+        assert_eq!(decoded, [0, 1, 2, 4, 6]);
+        assert_eq!(known.0.len(), 4);
+    }
+
+    #[test]
+    fn keeps_the_counts_if_the_pool_cannot_be_read() {
+        let registry = Registry::new();
+        let metrics = MetricsLink::new(Some(&registry));
+        let mut known = EthereumExtrinsics::new();
+
+        report_ethereum_counts(&metrics, known.count(vec![(true, 1, 0u8)], |_| Ok(true)));
+        report_ethereum_counts(
+            &metrics,
+            known.count(vec![(true, 1, 0u8), (true, 2, 1)], |_| Err(internal_err("unavailable"))),
+        );
+        metrics.report(|metrics| assert_eq!(metrics.txpool_ethereum_ready.get(), 1));
+    }
+
+    #[test]
+    fn notifies_the_events_to_the_subscribers_until_none_is_left() {
+        let event = |nonce: u64| PoolTransactionEvent {
+            kind: PoolEventKind::Added,
+            transaction: Content { nonce: nonce.into(), ..Default::default() },
+            replaced_by: None,
+        };
+        let mut subscribers = PoolSubscribers::default();
+        let (first, mut first_events) = tracing_unbounded("test");
+        let (second, second_events) = tracing_unbounded("test");
+        subscribers.senders.extend([first, second]);
+
+        assert!(subscribers.notify(&[event(0), event(1)]));
+        drop(second_events);
+        assert!(subscribers.notify(&[event(2)]));
+        assert_eq!(subscribers.senders.len(), 1);
+        let received = std::iter::from_fn(|| first_events.try_next().ok().flatten())
+            .map(|event| event.transaction.nonce.low_u64())
+            .collect::<Vec<_>>();
+        assert_eq!(received, [0, 1, 2]);
+
+        drop(first_events);
+        assert!(!subscribers.notify(&[]));
+        assert!(!subscribers.running);
+    }
 }
@@ -101,6 +101,15 @@ pub fn alith_public() -> ecdsa::Public {
     .unwrap()
 }
 
+pub fn alith_pair() -> ecdsa::Pair {
+    let seed = mnemonic_to_seed(DEV_PHRASE).expect("Wrong mnemonic provided");
+    derive_bip44_pair_from_seed(
+        seed.as_bytes(),
+        &format!("{}{}", DEFAULT_DERIVATION_PATH_PREFIX, 0),
+    )
+    .unwrap()
+}
+
 pub fn baltathar_pair() -> ecdsa::Pair {
     let seed = mnemonic_to_seed(DEV_PHRASE).expect("Wrong mnemonic provided");
     derive_bip44_pair_from_seed(
@@ -142,6 +142,7 @@ fn genesis(
         // Monetary.
         balances: BalancesConfig { balances: endowed },
         transaction_payment: Default::default(),
+        assets: Default::default(),
         // Consesnsus.
         aura: Default::default(),
         grandpa: Default::default(),
@@ -1,4 +1,4 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf};
 
 // Substrate
 use sc_cli::{
@@ -25,12 +25,17 @@ pub struct Cli {
 
 /// Available Sealing methods.
 #[cfg(feature = "manual-seal")]
-#[derive(Debug, Copy, Clone, clap::ArgEnum)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Sealing {
     /// Seal using rpc method.
     Manual,
     /// Seal when transaction is executed.
     Instant,
+    /// Seal a block every given number of milliseconds, even if no transaction is pending.
+    Interval(u64),
+    /// Seal when transaction is executed, and also every given number of milliseconds as a
+    /// heartbeat.
+    Hybrid(u64),
 }
 
 #[cfg(feature = "manual-seal")]
@@ -40,6 +45,42 @@ impl Default for Sealing {
     }
 }
 
+#[cfg(feature = "manual-seal")]
+impl std::fmt::Display for Sealing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sealing::Manual => write!(f, "manual"),
+            Sealing::Instant => write!(f, "instant"),
+            Sealing::Interval(millis) => write!(f, "interval:{}", millis),
+            Sealing::Hybrid(millis) => write!(f, "hybrid:{}", millis),
+        }
+    }
+}
+
+#[cfg(feature = "manual-seal")]
+impl std::str::FromStr for Sealing {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let parse_millis = |millis: &str| match millis.parse::<u64>() {
+            Ok(millis) if millis > 0 => Ok(millis),
+            _ => Err(format!("Invalid sealing interval `{}`, expected milliseconds", millis)),
+        };
+
+        match s.to_lowercase().split_once(':') {
+            None if s.eq_ignore_ascii_case("manual") => Ok(Sealing::Manual),
+            None if s.eq_ignore_ascii_case("instant") => Ok(Sealing::Instant),
+            Some(("interval", millis)) => Ok(Sealing::Interval(parse_millis(millis)?)),
+            Some(("hybrid", millis)) => Ok(Sealing::Hybrid(parse_millis(millis)?)),
+            _ => Err(format!(
+                "Unknown sealing method `{}`, expected `manual`, `instant`, `interval:<ms>` or \
+                 `hybrid:<ms>`",
+                s
+            )),
+        }
+    }
+}
+
 /// The `run` command used to run a node.
 #[derive(Debug, clap::Parser)]
 pub struct RunCmd {
@@ -47,11 +88,19 @@ pub struct RunCmd {
     #[clap(flatten)]
     pub base: sc_cli::RunCmd,
 
-    /// Choose sealing method.
+    /// Choose sealing method: `manual`, `instant`, `interval:<ms>` (seal a block every `ms`
+    /// milliseconds) or `hybrid:<ms>` (seal instantly and every `ms` milliseconds).
     #[cfg(feature = "manual-seal")]
-    #[clap(long, arg_enum, ignore_case = true, default_value_t)]
+    #[clap(long, default_value_t)]
     pub sealing: Sealing,
 
+    /// Start the dev chain from the state of a live chain, given the path of a chain spec exported
+    /// by `export-state` or of a raw state snapshot (its `genesis.raw` object). The `--chain` must
+    /// be the dev chain of the same runtime, with a fresh database (e.g. `--tmp`).
+    #[cfg(feature = "manual-seal")]
+    #[clap(long, value_name = "PATH")]
+    pub fork_state: Option<PathBuf>,
+
     /// Enable dev signer for eth rpc.
     #[clap(long)]
     pub enable_dev_signer: bool,
@@ -65,6 +114,7 @@ pub struct RunCmd {
     pub fee_history_limit: u64,
 
     /// Number of concurrent tracing tasks. Meant to be shared by both "debug" and "trace" modules.
+    /// The single transaction and block requests are traced ahead of the `trace_filter` ranges.
     #[clap(long, default_value = "10")]
     pub ethapi_max_permits: u32,
 
@@ -79,6 +129,57 @@ pub struct RunCmd {
     #[clap(long, default_value = "500")]
     pub ethapi_trace_max_count: u32,
 
+    /// Include the storage slots read and written by each call in the traces of the `trace`
+    /// module, at the cost of the memory of the `trace_filter` cache.
+    #[clap(long)]
+    pub ethapi_trace_storage: bool,
+
+    /// Maximum memory (in MiB) a single `debug` trace is allowed to use, for the raw and the call
+    /// list tracers. A buffered trace going over it returns an error. A streamed one holds at most
+    /// that much waiting for the client, and is aborted if the client doesn't catch up in time.
+    #[clap(long, default_value = "512")]
+    pub ethapi_trace_memory_limit: usize,
+
+    /// Directory of runtime WASM blobs built with the `evm-tracing` feature, which the `debug` and
+    /// `trace` modules run instead of the on-chain runtime of the same `spec_version`. Allows to
+    /// trace the blocks of the runtimes which were not built with it. The blocks of the
+    /// `spec_version` of the node run its native runtime unless `--execution-other` is `wasm`.
+    #[clap(long, value_name = "PATH")]
+    pub ethapi_trace_runtimes: Option<PathBuf>,
+
+    /// Timeout (in seconds) of a `debug` trace not setting one in its `timeout` parameter.
+    #[clap(long, default_value = "30")]
+    pub ethapi_trace_timeout: u64,
+
+    /// Maximum timeout (in seconds) a `debug` trace can set in its `timeout` parameter.
+    #[clap(long, default_value = "300")]
+    pub ethapi_trace_max_timeout: u64,
+
+    /// Percentage the fee cap and the tip of an Ethereum transaction must be raised by to replace
+    /// the one of the same sender and nonce in the pool.
+    #[clap(long, default_value = "10")]
+    pub txpool_price_bump: u32,
+
+    /// Maximum number of future Ethereum transactions of an account in the pool, the ones which
+    /// are not executable yet. `0` for no limit.
+    #[clap(long, default_value = "64")]
+    pub txpool_account_queue: usize,
+
+    /// Keep the Ethereum transactions whose fee cap falls below the base fee in the pool, instead
+    /// of evicting them.
+    #[clap(long)]
+    pub txpool_keep_underpriced: bool,
+
+    /// Save the Ethereum transactions submitted to `eth_sendRawTransaction` to a journal in the
+    /// config directory, resubmitting them on startup and rebroadcasting them until they are
+    /// included.
+    #[clap(long)]
+    pub txpool_journal: bool,
+
+    /// Interval (in seconds) between two rebroadcasts of the pending transactions of the journal.
+    #[clap(long, default_value = "60")]
+    pub txpool_rebroadcast_interval: u64,
+
     /// Enable EVM tracing module on a non-authority node.
     #[clap(
         long,
@@ -89,6 +190,17 @@ pub struct RunCmd {
     pub ethapi: Vec<crate::tracing::EthApiExt>,
 }
 
+impl RunCmd {
+    /// The pool policy for the Ethereum transactions.
+    pub fn pool_policy(&self) -> crate::transaction_pool::PoolPolicyConfig {
+        crate::transaction_pool::PoolPolicyConfig {
+            price_bump: self.txpool_price_bump,
+            account_queue: self.txpool_account_queue,
+            evict_underpriced: !self.txpool_keep_underpriced,
+        }
+    }
+}
+
 /// Armonia Eva Node subcommand.
 #[derive(Debug, clap::Subcommand)]
 pub enum Subcommand {
@@ -65,6 +65,7 @@ pub trait RuntimeApiCollection:
     + sp_finality_grandpa::GrandpaApi<Block>
     + frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>
     + pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
+    + primitives_rpc::call::EthCallRuntimeApi<Block>
     + primitives_rpc::debug::DebugRuntimeApi<Block>
     + primitives_rpc::txpool::TxPoolRuntimeApi<Block>
     + fp_rpc::EthereumRuntimeRPCApi<Block>
@@ -86,6 +87,7 @@ where
         + sp_finality_grandpa::GrandpaApi<Block>
         + frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>
         + pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
+        + primitives_rpc::call::EthCallRuntimeApi<Block>
         + primitives_rpc::debug::DebugRuntimeApi<Block>
         + primitives_rpc::txpool::TxPoolRuntimeApi<Block>
         + fp_rpc::EthereumRuntimeRPCApi<Block>
@@ -237,7 +237,13 @@ pub fn run() -> sc_cli::Result<()> {
             let runner = cli.create_runner_with_config(&cli.run.base, |cli, tokio_handle| {
                 // note it's `cli.run` not `cli.run.base` here, for `cli.run` is implemented by
                 // `CliConfiguration<Cli>`, for `CliConfiguration<()>`
-                SubstrateCli::create_configuration(cli, &cli.run, tokio_handle)
+                #[allow(unused_mut)]
+                let mut config = SubstrateCli::create_configuration(cli, &cli.run, tokio_handle)?;
+                #[cfg(feature = "manual-seal")]
+                if let Some(path) = &cli.run.fork_state {
+                    crate::fork::fork_chain_spec(&mut config.chain_spec, path)?;
+                }
+                Ok(config)
             })?;
             runner.run_node_until_exit(|config| async move {
                 service::build_full(config, &cli).map_err(Into::into)
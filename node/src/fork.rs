@@ -0,0 +1,252 @@
+//! Fork mode of the dev node: start a manual-seal chain from the state of a live chain.
+//!
+//! The state is taken from a chain spec exported by the `export-state` subcommand, or from a raw
+//! state snapshot (the `genesis.raw` object of such a chain spec). It becomes the genesis state of
+//! the dev chain, patched so that the dev node can seal blocks on it and the dev accounts can still
+//! administrate it.
+//!
+//! A Substrate chain always starts at block 0, so the blocks of the fork are numbered with an
+//! offset: its genesis block 0 is the forked block `N`, logged at startup, and its block `k`
+//! follows the forked block `N + k`. The Ethereum blocks are numbered like the Substrate ones, so
+//! the forked Ethereum block is returned as block 0 by the Ethereum rpc, and the `NUMBER` opcode
+//! returns `k`. The `BLOCKHASH` opcode returns the hashes of the blocks of the fork, starting with
+//! the forked block, but not the ones of its ancestors.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    path::Path,
+};
+
+use codec::{Decode, Encode};
+// Substrate
+use sc_chain_spec::ChainSpec;
+use sp_core::{
+    hashing::{blake2_128, twox_128, twox_64},
+    storage::{well_known_keys, ChildInfo, Storage, StorageChild, StorageData, StorageKey},
+    U256,
+};
+// Local
+use primitives_core::{AccountId, Balance, BlockNumber, Index};
+
+use crate::chain_spec::{key_helper::alith_public, RuntimeChain, RuntimeChainSpec};
+
+/// The free balance the dev sudo key (Alith) is topped up to, so that it can pay the fees of the
+/// dev rpc extrinsics.
+const SUDO_FREE_BALANCE: Balance = 1_000_000 * 1_000_000_000_000_000_000;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawState {
+    top: BTreeMap<StorageKey, StorageData>,
+    #[serde(default)]
+    children_default: BTreeMap<StorageKey, BTreeMap<StorageKey, StorageData>>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawGenesis {
+    raw: RawState,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum ForkSource {
+    /// A chain spec exported by `export-state`.
+    ChainSpec { id: String, genesis: RawGenesis },
+    /// A raw state snapshot.
+    Snapshot(RawState),
+}
+
+impl From<RawState> for Storage {
+    fn from(state: RawState) -> Self {
+        Storage {
+            top: state.top.into_iter().map(|(key, value)| (key.0, value.0)).collect(),
+            children_default: state
+                .children_default
+                .into_iter()
+                .map(|(key, child)| {
+                    let child = StorageChild {
+                        data: child.into_iter().map(|(key, value)| (key.0, value.0)).collect(),
+                        child_info: ChildInfo::new_default(&key.0),
+                    };
+                    (key.0, child)
+                })
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+}
+
+/// Load the state to fork from the file at `path`, checking it belongs to the `runtime` chain.
+pub fn load_fork_state(path: &Path, runtime: RuntimeChainSpec) -> Result<Storage, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Error opening fork state `{}`: {}", path.display(), e))?;
+    // SAFETY: see `ChainId::from_json_file`.
+    let bytes = unsafe {
+        memmap2::Mmap::map(&file)
+            .map_err(|e| format!("Error mmaping fork state `{}`: {}", path.display(), e))?
+    };
+
+    match serde_json::from_slice(&bytes).map_err(|e| {
+        format!("Error parsing fork state, expected a raw chain spec or state: {}", e)
+    })? {
+        ForkSource::ChainSpec { id, genesis } => {
+            if id.as_str().runtime() != runtime {
+                return Err(format!(
+                    "The fork state of `{}` can't be run by the {:?} runtime",
+                    id, runtime
+                ))
+            }
+            Ok(genesis.raw.into())
+        },
+        ForkSource::Snapshot(state) => Ok(state.into()),
+    }
+}
+
+/// Replace the genesis state of the `chain_spec` by the state at `path`, patched for development.
+pub fn fork_chain_spec(chain_spec: &mut Box<dyn ChainSpec>, path: &Path) -> Result<(), String> {
+    let runtime = chain_spec.runtime();
+    let mut storage = load_fork_state(path, runtime)?;
+    let offset = patch_dev_state(&mut storage.top, runtime)?;
+    log::info!(
+        "🍴 Forking the state of `{}` at block #{} into `{}`: block #k of the fork follows the \
+        forked block #{} + k",
+        path.display(),
+        offset,
+        chain_spec.id(),
+        offset,
+    );
+    chain_spec.set_storage(storage);
+    Ok(())
+}
+
+/// The code of the `runtime` built in the node, which is built for manual seal.
+fn runtime_code(runtime: RuntimeChainSpec) -> Result<&'static [u8], String> {
+    match runtime {
+        RuntimeChainSpec::Eva => eva_runtime::WASM_BINARY,
+        RuntimeChainSpec::WallE => wall_e_runtime::WASM_BINARY,
+        RuntimeChainSpec::Unknown => None,
+    }
+    .ok_or_else(|| format!("The node has no {:?} runtime code to run the fork", runtime))
+}
+
+fn storage_prefix(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+    [twox_128(pallet), twox_128(item)].concat()
+}
+
+/// The key of the hash of the Ethereum block `number`, in `pallet_ethereum::BlockHash`.
+fn ethereum_block_hash_key(number: U256) -> Vec<u8> {
+    [storage_prefix(b"Ethereum", b"BlockHash"), twox_64(&number.encode()).to_vec(), number.encode()]
+        .concat()
+}
+
+fn decode_value<T: Decode>(
+    top: &BTreeMap<Vec<u8>, Vec<u8>>,
+    key: &[u8],
+) -> Result<Option<T>, String> {
+    top.get(key)
+        .map(|value| T::decode(&mut &value[..]))
+        .transpose()
+        .map_err(|e| format!("Error decoding the fork state: {}", e))
+}
+
+/// Patch the live state so that it can be sealed by the dev node and administrated by the dev
+/// accounts, returning the number of the forked block, which is the offset of the numbering of the
+/// fork:
+///
+/// - the runtime code is the one of the node, the live one checking the Aura slot of each block,
+///   which manual seal doesn't provide;
+/// - the sudo key is Alith, funded to pay the fees of the dev rpc;
+/// - the Ethereum block hashes are numbered like the fork, the forked block being block 0 and the
+///   hashes of its ancestors, which can't be numbered, being removed.
+fn patch_dev_state(
+    top: &mut BTreeMap<Vec<u8>, Vec<u8>>,
+    runtime: RuntimeChainSpec,
+) -> Result<BlockNumber, String> {
+    top.insert(well_known_keys::CODE.to_vec(), runtime_code(runtime)?.to_vec());
+
+    let sudo = AccountId::from(alith_public());
+    top.insert(storage_prefix(b"Sudo", b"Key"), sudo.encode());
+
+    // `frame_system::AccountInfo<Index, pallet_balances::AccountData<Balance>>`
+    type AccountInfo = (Index, u32, u32, u32, (Balance, Balance, Balance, Balance));
+    let account_key =
+        [storage_prefix(b"System", b"Account"), blake2_128(&sudo.encode()).to_vec(), sudo.encode()]
+            .concat();
+    let mut account = decode_value::<AccountInfo>(top, &account_key)?.unwrap_or_default();
+    if account.4 .0 < SUDO_FREE_BALANCE {
+        let issuance_key = storage_prefix(b"Balances", b"TotalIssuance");
+        let issuance = decode_value::<Balance>(top, &issuance_key)?.unwrap_or_default();
+        top.insert(
+            issuance_key,
+            issuance.saturating_add(SUDO_FREE_BALANCE - account.4 .0).encode(),
+        );
+        // A new account is provided by its balance.
+        account.2 = account.2.max(1);
+        account.4 .0 = SUDO_FREE_BALANCE;
+        top.insert(account_key, account.encode());
+    }
+
+    let number = decode_value::<BlockNumber>(top, &storage_prefix(b"System", b"Number"))?
+        .ok_or("The fork state has no block number")?;
+    let forked_hash = top.get(&ethereum_block_hash_key(U256::from(number))).cloned();
+    let block_hashes = storage_prefix(b"Ethereum", b"BlockHash");
+    top.retain(|key, _| !key.starts_with(&block_hashes));
+    if let Some(hash) = forked_hash {
+        top.insert(ethereum_block_hash_key(U256::zero()), hash);
+    }
+    Ok(number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eva_runtime::{
+        Executive, Header, Runtime, Timestamp, TimestampCall, UncheckedExtrinsic, WASM_BINARY,
+    };
+    use fp_rpc::runtime_decl_for_EthereumRuntimeRPCApi::EthereumRuntimeRPCApi as _;
+    use sp_runtime::{
+        traits::{Extrinsic, Header as HeaderT},
+        BuildStorage,
+    };
+
+    #[test]
+    fn seals_a_block_on_the_forked_state() {
+        // A live state at block 100, with the code of a runtime the dev node can't run.
+        let mut storage = crate::chain_spec::eva::development_chain_spec().build_storage().unwrap();
+        storage.top.insert(well_known_keys::CODE.to_vec(), b"live runtime".to_vec());
+        storage.top.insert(storage_prefix(b"System", b"Number"), 100u32.encode());
+        // Its current Ethereum block is block 100, whose ancestor 99 is dropped by the fork.
+        let forked_hash = storage.top[&ethereum_block_hash_key(U256::zero())].clone();
+        storage.top.insert(ethereum_block_hash_key(100.into()), forked_hash.clone());
+        storage.top.insert(ethereum_block_hash_key(99.into()), vec![0x99; 32]);
+
+        let offset = patch_dev_state(&mut storage.top, RuntimeChainSpec::Eva).unwrap();
+        assert_eq!(offset, 100);
+        assert_eq!(storage.top[&ethereum_block_hash_key(U256::zero())], forked_hash);
+        assert!(!storage.top.contains_key(&ethereum_block_hash_key(99.into())));
+        assert!(!storage.top.contains_key(&ethereum_block_hash_key(100.into())));
+        assert_eq!(storage.top[well_known_keys::CODE], WASM_BINARY.unwrap());
+
+        sp_io::TestExternalities::new(storage).execute_with(|| {
+            let forked = Runtime::current_block().unwrap();
+
+            let header = Header::new(
+                1,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            );
+            Executive::initialize_block(&header);
+            let now = Timestamp::now() + runtime_common::constants::time::SLOT_DURATION;
+            let inherent = UncheckedExtrinsic::new(TimestampCall::set { now }.into(), None);
+            Executive::apply_extrinsic(inherent.unwrap()).unwrap().unwrap();
+            Executive::finalize_block();
+
+            // The first block of the fork follows the forked block 100.
+            let block = Runtime::current_block().unwrap();
+            assert_eq!(block.header.parent_hash, forked.header.hash());
+            assert_eq!(block.header.number + offset, 101.into());
+        });
+    }
+}
@@ -12,10 +12,13 @@ pub mod cli;
 mod benchmarking;
 mod command;
 #[cfg(feature = "manual-seal")]
+mod fork;
+#[cfg(feature = "manual-seal")]
 mod manual_seal;
 mod rpc;
 mod service;
 mod tracing;
+mod transaction_pool;
 
 pub use self::{cli::*, command::*};
 pub use sc_cli::{Error, Result};
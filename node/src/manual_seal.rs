@@ -1,23 +1,129 @@
-use std::cell::RefCell;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use codec::Encode;
+use futures::{channel::mpsc, SinkExt, Stream, StreamExt};
+use jsonrpsee::core::{async_trait, RpcResult};
+// Substrate
+use sc_client_api::Backend;
+use sc_consensus_manual_seal::rpc::EngineCommand;
+use sc_transaction_pool_api::{TransactionPool, TransactionSource};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+    hashing::{blake2_128, keccak_256, twox_128},
+    Pair, H160, H256, U256,
+};
+use sp_runtime::{
+    generic::{BlockId, Era, SignedPayload},
+    traits::{BlakeTwo256, Extrinsic, Hash as HashT},
+    OpaqueExtrinsic,
+};
+use substrate_frame_rpc_system::AccountNonceApi;
+// Frontier
+use fc_rpc::{internal_err, EthSigner};
+use fc_rpc_core::types::{Bytes, TransactionMessage};
+// Local
+use amax_eva_rpc::DevApiServer;
+use primitives_core::{AccountId, Balance, Block, BlockNumber, Hash, Index};
+use runtime_common::EthereumTransaction;
+
+use crate::{
+    chain_spec::{key_helper::alith_pair, RuntimeChainSpec},
+    service::FullBackend,
+};
+
+/// The slot is same in two runtime, so we use the value directly.
+const SLOT_DURATION: u64 = runtime_common::constants::time::SLOT_DURATION;
 
 const INHERENT_IDENTIFIER: sp_inherents::InherentIdentifier = *b"timstap0";
-thread_local!(static TIMESTAMP: RefCell<u64> = RefCell::new(0));
 
-/// Provide a mock duration starting at 0 in millisecond for timestamp inherent.
-/// Each call will increment timestamp by slot_duration making Aura think time has passed.
-pub struct MockTimestampInherentDataProvider;
+#[derive(Clone, Copy, Default)]
+struct TimestampState {
+    /// The last provided timestamp.
+    last: u64,
+    /// The offset (in millisecond) added to the wall-clock time.
+    offset: i64,
+    /// The timestamp of the next block, if set explicitly.
+    next: Option<u64>,
+}
+
+/// A saved state of [`MockTimestampInherentDataProvider`], used by `evm_snapshot`/`evm_revert`.
+#[derive(Clone, Copy)]
+pub struct TimestampSnapshot(TimestampState);
+
+/// Provide a mock timestamp in millisecond for timestamp inherent.
+/// The timestamp follows the wall-clock time (shifted by the offset set with the dev rpc), but
+/// each call increments it by at least slot_duration, so that blocks sealed faster than the slot
+/// duration are still valid for the timestamp pallet.
+#[derive(Clone, Default)]
+pub struct MockTimestampInherentDataProvider(Arc<Mutex<TimestampState>>);
+
+impl MockTimestampInherentDataProvider {
+    fn state(&self) -> std::sync::MutexGuard<'_, TimestampState> {
+        self.0.lock().expect("timestamp state is never poisoned; qed")
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Current time is always after unix epoch; qed")
+            .as_millis() as u64
+    }
+
+    /// Shift the timestamp of the next blocks by `millis`. Returns the total offset in millisecond.
+    pub fn increase_time(&self, millis: u64) -> u64 {
+        let mut state = self.state();
+        state.offset = state.offset.saturating_add(millis as i64);
+        if let Some(next) = state.next.as_mut() {
+            *next = next.saturating_add(millis);
+        }
+        state.offset.max(0) as u64
+    }
+
+    /// Set the timestamp of the next block, which must be valid for the timestamp pallet.
+    pub fn set_next(&self, millis: u64) -> Result<(), String> {
+        let mut state = self.state();
+        // The `MinimumPeriod` of the timestamp pallet.
+        let min = state.last + SLOT_DURATION / 2;
+        if millis < min {
+            return Err(format!("timestamp must be at least {} ms, got {} ms", min, millis))
+        }
+        state.next = Some(millis);
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> TimestampSnapshot {
+        TimestampSnapshot(*self.state())
+    }
+
+    pub fn restore(&self, snapshot: TimestampSnapshot) {
+        *self.state() = snapshot.0;
+    }
+}
 
-#[async_trait::async_trait]
+#[async_trait]
 impl sp_inherents::InherentDataProvider for MockTimestampInherentDataProvider {
     fn provide_inherent_data(
         &self,
         inherent_data: &mut sp_inherents::InherentData,
     ) -> Result<(), sp_inherents::Error> {
-        TIMESTAMP.with(|x| {
-            // The slot is same in two runtime, so we use the value directly.
-            *x.borrow_mut() += runtime_common::constants::time::SLOT_DURATION;
-            inherent_data.put_data(INHERENT_IDENTIFIER, &*x.borrow())
-        })
+        let now = Self::now();
+        let mut state = self.state();
+
+        let timestamp = match state.next.take() {
+            Some(next) => {
+                // The following blocks are relative to the explicit timestamp.
+                state.offset = next as i64 - now as i64;
+                next
+            },
+            None => (state.last + SLOT_DURATION).max((now as i64 + state.offset).max(0) as u64),
+        };
+        state.last = timestamp;
+        inherent_data.put_data(INHERENT_IDENTIFIER, &timestamp)
     }
 
     async fn try_handle_error(
@@ -29,3 +135,423 @@ impl sp_inherents::InherentDataProvider for MockTimestampInherentDataProvider {
         None
     }
 }
+
+/// Commands sealing a new block each time a transaction is imported into the pool.
+pub fn instant_seal_commands<Hash, Pool>(
+    pool: &Pool,
+) -> impl Stream<Item = EngineCommand<Hash>> + Send + 'static
+where
+    Hash: Send + 'static,
+    Pool: TransactionPool,
+{
+    pool.import_notification_stream().map(|_| EngineCommand::SealNewBlock {
+        create_empty: false,
+        finalize: false,
+        parent_hash: None,
+        sender: None,
+    })
+}
+
+/// Commands sealing a new block every `period`, whether or not transactions are pending.
+pub fn interval_seal_commands<Hash>(
+    period: Duration,
+) -> impl Stream<Item = EngineCommand<Hash>> + Send + 'static
+where
+    Hash: Send + 'static,
+{
+    let interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+
+    futures::stream::unfold(interval, |mut interval| async move {
+        interval.tick().await;
+        let command = EngineCommand::SealNewBlock {
+            create_empty: true,
+            finalize: false,
+            parent_hash: None,
+            sender: None,
+        };
+        Some((command, interval))
+    })
+}
+
+macro_rules! with_runtime {
+    ($chain:expr, $code:expr) => {
+        match $chain {
+            RuntimeChainSpec::Eva => {
+                #[allow(unused_imports)]
+                use eva_runtime as runtime;
+                $code
+            },
+            RuntimeChainSpec::WallE => {
+                #[allow(unused_imports)]
+                use wall_e_runtime as runtime;
+                $code
+            },
+            RuntimeChainSpec::Unknown => panic!("Unknown chain spec"),
+        }
+    };
+}
+
+/// The calls dispatched by the dev rpc, as root.
+enum DevCall {
+    SetBalance { who: AccountId, free: Balance },
+    SetStorage { items: Vec<(Vec<u8>, Vec<u8>)> },
+}
+
+struct Snapshot {
+    number: BlockNumber,
+    timestamp: TimestampSnapshot,
+}
+
+/// Storage key prefix of a map of the `EVM` pallet.
+fn evm_storage_prefix(item: &[u8]) -> Vec<u8> {
+    [twox_128(b"EVM"), twox_128(item)].concat()
+}
+
+fn blake2_128_concat(data: &[u8]) -> Vec<u8> {
+    [&blake2_128(data)[..], data].concat()
+}
+
+fn to_u64(value: U256, name: &str) -> RpcResult<u64> {
+    u64::try_from(value).map_err(|_| internal_err(format!("{} overflows u64", name)))
+}
+
+/// Signer of the `eth_sendTransaction` of the accounts impersonated with the dev rpc.
+///
+/// Their transactions are not signed, but hold the signature the manual-seal runtimes take the
+/// sender from (see [`runtime_common::ethereum::impersonated_source`]), so that they are executed
+/// and indexed as the other Ethereum transactions.
+#[derive(Clone, Default)]
+pub struct ImpersonatedSigner(Arc<Mutex<BTreeSet<H160>>>);
+
+impl ImpersonatedSigner {
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeSet<H160>> {
+        self.0.lock().expect("impersonated accounts are never poisoned; qed")
+    }
+}
+
+impl EthSigner for ImpersonatedSigner {
+    fn accounts(&self) -> Vec<H160> {
+        self.lock().iter().copied().collect()
+    }
+
+    fn sign(&self, message: TransactionMessage, address: &H160) -> RpcResult<EthereumTransaction> {
+        if !self.lock().contains(address) {
+            return Err(internal_err(format!("account {:?} is not impersonated", address)))
+        }
+        let (r, s) = runtime_common::ethereum::impersonated_signature(*address);
+
+        Ok(match message {
+            TransactionMessage::Legacy(m) => {
+                // `v` still holds the chain id, the parity being ignored.
+                let v = m.chain_id.map_or(27, |chain_id| chain_id * 2 + 35);
+                EthereumTransaction::Legacy(ethereum::LegacyTransaction {
+                    nonce: m.nonce,
+                    gas_price: m.gas_price,
+                    gas_limit: m.gas_limit,
+                    action: m.action,
+                    value: m.value,
+                    input: m.input,
+                    signature: ethereum::TransactionSignature::new(v, r, s)
+                        .ok_or_else(|| internal_err("the zero address can't be impersonated"))?,
+                })
+            },
+            TransactionMessage::EIP2930(m) => {
+                EthereumTransaction::EIP2930(ethereum::EIP2930Transaction {
+                    chain_id: m.chain_id,
+                    nonce: m.nonce,
+                    gas_price: m.gas_price,
+                    gas_limit: m.gas_limit,
+                    action: m.action,
+                    value: m.value,
+                    input: m.input,
+                    access_list: m.access_list,
+                    odd_y_parity: false,
+                    r,
+                    s,
+                })
+            },
+            TransactionMessage::EIP1559(m) => {
+                EthereumTransaction::EIP1559(ethereum::EIP1559Transaction {
+                    chain_id: m.chain_id,
+                    nonce: m.nonce,
+                    max_priority_fee_per_gas: m.max_priority_fee_per_gas,
+                    max_fee_per_gas: m.max_fee_per_gas,
+                    gas_limit: m.gas_limit,
+                    action: m.action,
+                    value: m.value,
+                    input: m.input,
+                    access_list: m.access_list,
+                    odd_y_parity: false,
+                    r,
+                    s,
+                })
+            },
+        })
+    }
+}
+
+/// Hardhat/Anvil compatible dev rpc of the manual-seal node, on the development chains.
+///
+/// The state changes are dispatched as sudo extrinsics signed by the dev sudo key (Alith), each
+/// one being sealed into a new block.
+pub struct DevRpc<C, P> {
+    client: Arc<C>,
+    pool: Arc<P>,
+    backend: Arc<FullBackend>,
+    frontier_backend: Arc<fc_db::Backend<Block>>,
+    command_sink: mpsc::Sender<EngineCommand<Hash>>,
+    timestamp: MockTimestampInherentDataProvider,
+    chain: RuntimeChainSpec,
+    snapshots: Mutex<(u64, BTreeMap<u64, Snapshot>)>,
+    impersonated: ImpersonatedSigner,
+}
+
+impl<C, P> DevRpc<C, P>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: AccountNonceApi<Block, AccountId, Index>,
+    P: TransactionPool<Block = Block> + 'static,
+{
+    pub fn new(
+        client: Arc<C>,
+        pool: Arc<P>,
+        backend: Arc<FullBackend>,
+        frontier_backend: Arc<fc_db::Backend<Block>>,
+        command_sink: mpsc::Sender<EngineCommand<Hash>>,
+        timestamp: MockTimestampInherentDataProvider,
+        chain: RuntimeChainSpec,
+    ) -> Self {
+        Self {
+            client,
+            pool,
+            backend,
+            frontier_backend,
+            command_sink,
+            timestamp,
+            chain,
+            snapshots: Mutex::new((1, BTreeMap::new())),
+            impersonated: ImpersonatedSigner::default(),
+        }
+    }
+
+    /// The signer of the transactions of the accounts impersonated with this rpc.
+    pub fn impersonated_signer(&self) -> ImpersonatedSigner {
+        self.impersonated.clone()
+    }
+
+    /// Seal a new block, even if no transaction is pending.
+    async fn seal(&self) -> RpcResult<Hash> {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        self.command_sink
+            .clone()
+            .send(EngineCommand::SealNewBlock {
+                create_empty: true,
+                finalize: false,
+                parent_hash: None,
+                sender: Some(sender),
+            })
+            .await
+            .map_err(|err| internal_err(format!("failed to send seal command: {}", err)))?;
+
+        match receiver.await {
+            Ok(Ok(created)) => Ok(created.hash),
+            Ok(Err(err)) => Err(internal_err(format!("failed to seal block: {}", err))),
+            Err(err) => Err(internal_err(format!("seal command dropped: {}", err))),
+        }
+    }
+
+    /// Build the sudo extrinsic of the `call`, signed by the dev sudo key.
+    fn sudo_extrinsic(&self, call: DevCall, nonce: Index, genesis: Hash) -> OpaqueExtrinsic {
+        let signer = alith_pair();
+
+        with_runtime!(self.chain, {
+            use runtime::{BalancesCall, Call, Runtime, SudoCall, SystemCall};
+
+            let call = match call {
+                DevCall::SetBalance { who, free } => Call::Balances(BalancesCall::set_balance {
+                    who,
+                    new_free: free,
+                    new_reserved: 0,
+                }),
+                DevCall::SetStorage { items } => Call::System(SystemCall::set_storage { items }),
+            };
+            let call = Call::Sudo(SudoCall::sudo { call: Box::new(call) });
+
+            let extra: runtime::SignedExtra = (
+                frame_system::CheckNonZeroSender::<Runtime>::new(),
+                frame_system::CheckSpecVersion::<Runtime>::new(),
+                frame_system::CheckTxVersion::<Runtime>::new(),
+                frame_system::CheckGenesis::<Runtime>::new(),
+                frame_system::CheckMortality::<Runtime>::from(Era::Immortal),
+                frame_system::CheckNonce::<Runtime>::from(nonce),
+                frame_system::CheckWeight::<Runtime>::new(),
+                pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+            );
+            let raw_payload = SignedPayload::<Call, runtime::SignedExtra>::from_raw(
+                call.clone(),
+                extra.clone(),
+                (
+                    (),
+                    runtime::VERSION.spec_version,
+                    runtime::VERSION.transaction_version,
+                    genesis,
+                    genesis,
+                    (),
+                    (),
+                    (),
+                ),
+            );
+            // must use `keccak_256` hash for ethereum-liked system.
+            let signature = raw_payload.using_encoded(|p| signer.sign_prehashed(&keccak_256(p)));
+
+            let sign_payload = (
+                runtime::Address::from(signer.public()),
+                runtime::Signature::from(signature),
+                extra,
+            );
+            runtime::UncheckedExtrinsic::new(call, Some(sign_payload))
+                .expect("signed extrinsic is always created; qed")
+                .into()
+        })
+    }
+
+    /// Dispatch the `call` as root, and seal it into a new block.
+    /// Returns the hash of the extrinsic.
+    async fn dispatch(&self, call: DevCall) -> RpcResult<H256> {
+        let info = self.client.info();
+        let at = BlockId::Hash(info.best_hash);
+        let sudo = AccountId::from(alith_pair().public());
+        let nonce = self
+            .client
+            .runtime_api()
+            .account_nonce(&at, sudo)
+            .map_err(|err| internal_err(format!("failed to fetch sudo nonce: {:?}", err)))?;
+
+        let xt = self.sudo_extrinsic(call, nonce, info.genesis_hash);
+        let hash = BlakeTwo256::hash_of(&xt);
+        self.pool
+            .submit_one(&at, TransactionSource::Local, xt)
+            .await
+            .map_err(|err| internal_err(format!("failed to submit extrinsic: {}", err)))?;
+        self.seal().await?;
+
+        Ok(hash)
+    }
+
+    async fn set_storage(&self, items: Vec<(Vec<u8>, Vec<u8>)>) -> RpcResult<()> {
+        self.dispatch(DevCall::SetStorage { items }).await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl<C, P> DevApiServer for DevRpc<C, P>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: AccountNonceApi<Block, AccountId, Index>,
+    P: TransactionPool<Block = Block> + 'static,
+{
+    async fn mine(&self, timestamp: Option<U256>) -> RpcResult<String> {
+        if let Some(timestamp) = timestamp {
+            let millis = to_u64(timestamp, "timestamp")?.saturating_mul(1000);
+            self.timestamp.set_next(millis).map_err(internal_err)?;
+        }
+        self.seal().await?;
+        Ok("0x0".into())
+    }
+
+    fn increase_time(&self, seconds: U256) -> RpcResult<U256> {
+        let millis = to_u64(seconds, "seconds")?.saturating_mul(1000);
+        Ok(U256::from(self.timestamp.increase_time(millis) / 1000))
+    }
+
+    fn set_next_block_timestamp(&self, timestamp: U256) -> RpcResult<()> {
+        let millis = to_u64(timestamp, "timestamp")?.saturating_mul(1000);
+        self.timestamp.set_next(millis).map_err(internal_err)
+    }
+
+    fn snapshot(&self) -> RpcResult<U256> {
+        let snapshot = Snapshot {
+            number: self.client.info().best_number,
+            timestamp: self.timestamp.snapshot(),
+        };
+        let mut snapshots = self.snapshots.lock().expect("snapshots are never poisoned; qed");
+        let id = snapshots.0;
+        snapshots.0 += 1;
+        snapshots.1.insert(id, snapshot);
+        Ok(U256::from(id))
+    }
+
+    fn revert(&self, id: U256) -> RpcResult<bool> {
+        let id = match u64::try_from(id) {
+            Ok(id) => id,
+            Err(_) => return Ok(false),
+        };
+        let mut snapshots = self.snapshots.lock().expect("snapshots are never poisoned; qed");
+        let snapshot = match snapshots.1.remove(&id) {
+            Some(snapshot) => snapshot,
+            None => return Ok(false),
+        };
+        // The snapshot is reverted, so are the later ones.
+        snapshots.1.retain(|&other, _| other < id);
+
+        let best_number = self.client.info().best_number;
+        if best_number > snapshot.number {
+            self.backend
+                .revert(best_number - snapshot.number, true)
+                .map_err(|err| internal_err(format!("failed to revert blocks: {}", err)))?;
+            // The mapping of the reverted blocks is left in the Frontier database, the Ethereum
+            // rpc ignoring the blocks which are not canonical anymore. But its sync must restart
+            // from the leaves of the reverted chain, instead of the reverted blocks.
+            self.frontier_backend
+                .meta()
+                .write_current_syncing_tips(Vec::new())
+                .map_err(|err| {
+                    internal_err(format!("failed to reset the mapping sync: {}", err))
+                })?;
+        }
+        self.timestamp.restore(snapshot.timestamp);
+        Ok(true)
+    }
+
+    async fn set_balance(&self, address: H160, balance: U256) -> RpcResult<()> {
+        // The Ethereum balance is the reducible one, which excludes the existential deposit.
+        let free = Balance::try_from(balance)
+            .ok()
+            .and_then(|balance| {
+                balance.checked_add(with_runtime!(self.chain, runtime::ExistentialDeposit::get()))
+            })
+            .ok_or_else(|| internal_err("balance overflows the native balance type"))?;
+        self.dispatch(DevCall::SetBalance { who: address.into(), free })
+            .await
+            .map(|_| ())
+    }
+
+    async fn set_code(&self, address: H160, code: Bytes) -> RpcResult<()> {
+        let key =
+            [evm_storage_prefix(b"AccountCodes"), blake2_128_concat(address.as_bytes())].concat();
+        self.set_storage(vec![(key, code.into_vec().encode())]).await
+    }
+
+    async fn set_storage_at(&self, address: H160, slot: U256, value: H256) -> RpcResult<bool> {
+        let mut index = [0u8; 32];
+        slot.to_big_endian(&mut index);
+        let key = [
+            evm_storage_prefix(b"AccountStorages"),
+            blake2_128_concat(address.as_bytes()),
+            blake2_128_concat(&index),
+        ]
+        .concat();
+        self.set_storage(vec![(key, value.encode())]).await?;
+        Ok(true)
+    }
+
+    fn impersonate_account(&self, address: H160) -> RpcResult<()> {
+        self.impersonated.lock().insert(address);
+        Ok(())
+    }
+
+    fn stop_impersonating_account(&self, address: H160) -> RpcResult<()> {
+        self.impersonated.lock().remove(&address);
+        Ok(())
+    }
+}
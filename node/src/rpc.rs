@@ -7,7 +7,9 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use jsonrpsee::RpcModule;
 // Substrate
-use sc_client_api::{client::BlockchainEvents, AuxStore, Backend, StateBackend, StorageProvider};
+use sc_client_api::{
+    client::BlockchainEvents, AuxStore, Backend, ProofProvider, StateBackend, StorageProvider,
+};
 use sc_network::NetworkService;
 use sc_rpc::SubscriptionTaskExecutor;
 use sc_rpc_api::DenyUnsafe;
@@ -25,7 +27,9 @@ use fc_rpc::{
 use fc_rpc_core::types::{FeeHistoryCache, FeeHistoryCacheLimit, FilterPool};
 use fp_storage::EthereumStorageSchema;
 // Local
-use amax_eva_rpc::{Debug as DebugRpc, DebugApiServer, TxPool as TxPoolRpc, TxPoolApiServer};
+use amax_eva_rpc::{
+    Debug as DebugRpc, DebugApiServer, TransactionJournal, TxPool as TxPoolRpc, TxPoolApiServer,
+};
 use primitives_core::{AccountId, Balance, Block, Hash, Index};
 use runtime_common::EthereumTransaction;
 
@@ -98,10 +102,21 @@ pub struct FullDeps<C, P, A: ChainApi> {
     pub block_data_cache: Arc<EthBlockDataCacheTask<Block>>,
     /// Amax Chain Type
     pub chain: RuntimeChainSpec,
+    /// Journal of the transactions submitted to `eth_sendRawTransaction`, if enabled.
+    pub transaction_journal: Option<Arc<TransactionJournal>>,
     /// Manual seal command sink
     #[cfg(feature = "manual-seal")]
     pub command_sink:
         Option<futures::channel::mpsc::Sender<sc_consensus_manual_seal::rpc::EngineCommand<Hash>>>,
+    /// Substrate backend, used by the dev rpc to revert blocks.
+    #[cfg(feature = "manual-seal")]
+    pub substrate_backend: Arc<crate::service::FullBackend>,
+    /// Mock timestamp of the sealed blocks, controlled by the dev rpc.
+    #[cfg(feature = "manual-seal")]
+    pub mock_timestamp: crate::manual_seal::MockTimestampInherentDataProvider,
+    /// Whether to enable the dev rpc, only on the development chains.
+    #[cfg(feature = "manual-seal")]
+    pub dev_rpc: bool,
 }
 
 pub fn overrides_handle<C, BE>(client: Arc<C>) -> Arc<OverrideHandle<Block>>
@@ -146,7 +161,7 @@ where
     BE: Backend<Block> + 'static,
     BE::State: StateBackend<BlakeTwo256>,
     C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + AuxStore,
-    C: BlockchainEvents<Block>,
+    C: BlockchainEvents<Block> + ProofProvider<Block>,
     C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
     C: Send + Sync + 'static,
     C::Api: BlockBuilder<Block>,
@@ -155,6 +170,7 @@ where
     C::Api: fp_rpc::ConvertTransactionRuntimeApi<Block>,
     C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
     C::Api: primitives_rpc::txpool::TxPoolRuntimeApi<Block>,
+    C::Api: primitives_rpc::call::EthCallRuntimeApi<Block>,
     P: TransactionPool<Block = Block> + 'static,
     A: ChainApi<Block = Block> + 'static,
 {
@@ -169,7 +185,12 @@ where
         EthPubSubApiServer, EthSigner, Net, NetApiServer, Web3, Web3ApiServer,
     };
     // Local
-    use amax_eva_rpc::{Trace, TraceServer};
+    #[cfg(feature = "manual-seal")]
+    use amax_eva_rpc::DevApiServer;
+    use amax_eva_rpc::{
+        EthCall, EthCallApiServer, EthJournal, EthJournalApiServer, EthProof, EthProofApiServer,
+        EthSimulate, EthSimulateApiServer, Trace, TraceServer,
+    };
 
     let mut io = RpcModule::new(());
     let FullDeps {
@@ -191,10 +212,30 @@ where
         overrides,
         block_data_cache,
         chain,
+        transaction_journal,
         #[cfg(feature = "manual-seal")]
         command_sink,
+        #[cfg(feature = "manual-seal")]
+        substrate_backend,
+        #[cfg(feature = "manual-seal")]
+        mock_timestamp,
+        #[cfg(feature = "manual-seal")]
+        dev_rpc,
     } = deps;
 
+    #[cfg(feature = "manual-seal")]
+    let dev = command_sink.as_ref().filter(|_| dev_rpc).map(|command_sink| {
+        crate::manual_seal::DevRpc::new(
+            client.clone(),
+            pool.clone(),
+            substrate_backend,
+            backend.clone(),
+            command_sink.clone(),
+            mock_timestamp,
+            chain,
+        )
+    });
+
     io.merge(System::new(client.clone(), pool.clone(), deny_unsafe).into_rpc())?;
     io.merge(TransactionPayment::new(client.clone()).into_rpc())?;
 
@@ -203,24 +244,66 @@ where
     if enable_dev_signer {
         signers.push(Box::new(EthDevSigner::new()) as Box<dyn EthSigner>);
     }
+    // `eth_sendTransaction` from the accounts impersonated with the dev rpc.
+    #[cfg(feature = "manual-seal")]
+    if let Some(dev) = &dev {
+        signers.push(Box::new(dev.impersonated_signer()) as Box<dyn EthSigner>);
+    }
+    // Frontier's `eth_call` and `eth_estimateGas`, for the blocks whose runtime can't apply the
+    // overrides.
+    let eth_call_fallback = Eth::new(
+        client.clone(),
+        pool.clone(),
+        graph.clone(),
+        Some(TransactionConverter::from(chain)),
+        network.clone(),
+        Vec::new(),
+        overrides.clone(),
+        backend.clone(),
+        is_authority,
+        block_data_cache.clone(),
+        fee_history_cache.clone(),
+        fee_history_cache_limit,
+        execute_gas_limit_multiplier,
+    );
+    let mut eth = Eth::new(
+        client.clone(),
+        pool.clone(),
+        graph.clone(),
+        Some(TransactionConverter::from(chain)),
+        network.clone(),
+        signers,
+        overrides.clone(),
+        backend.clone(),
+        is_authority,
+        block_data_cache.clone(),
+        fee_history_cache,
+        fee_history_cache_limit,
+        execute_gas_limit_multiplier,
+    )
+    .into_rpc();
+    // `eth_call` and `eth_estimateGas` are served with the state and block overrides.
+    eth.remove_method("eth_call");
+    eth.remove_method("eth_estimateGas");
+    // The submitted transactions are journaled, when enabled.
+    if transaction_journal.is_some() {
+        eth.remove_method("eth_sendRawTransaction");
+    }
+    io.merge(eth)?;
+    if let Some(journal) = transaction_journal {
+        io.merge(EthJournal::new(client.clone(), pool.clone(), journal).into_rpc())?;
+    }
     io.merge(
-        Eth::new(
+        EthCall::new(
             client.clone(),
-            pool.clone(),
-            graph.clone(),
-            Some(TransactionConverter::from(chain)),
-            network.clone(),
-            signers,
-            overrides.clone(),
             backend.clone(),
-            is_authority,
-            block_data_cache.clone(),
-            fee_history_cache,
-            fee_history_cache_limit,
             execute_gas_limit_multiplier,
+            eth_call_fallback,
         )
         .into_rpc(),
     )?;
+    io.merge(EthSimulate::new(client.clone(), backend.clone()).into_rpc())?;
+    io.merge(EthProof::new(client.clone(), backend.clone()).into_rpc())?;
     if let Some(filter_pool) = filter_pool {
         io.merge(
             EthFilter::new(
@@ -239,7 +322,7 @@ where
             pool,
             client.clone(),
             network.clone(),
-            subscription_task_executor,
+            subscription_task_executor.clone(),
             overrides,
         )
         .into_rpc(),
@@ -254,17 +337,21 @@ where
                 trace_filter_requester,
                 trace_requester,
                 trace_filter_max_count,
+                tracing_requesters.metrics.clone(),
             )
             .into_rpc(),
         )?;
     }
 
     if exts.contains(&EthApiExt::Txpool) {
-        io.merge(TxPoolRpc::new(client, graph).into_rpc())?;
+        io.merge(TxPoolRpc::new(client, graph, subscription_task_executor.clone()).into_rpc())?;
     }
 
     if let Some(debug_requester) = tracing_requesters.debug {
-        io.merge(DebugRpc::new(debug_requester).into_rpc())?;
+        io.merge(
+            DebugRpc::new(debug_requester, subscription_task_executor, tracing_requesters.metrics)
+                .into_rpc(),
+        )?;
     }
 
     #[cfg(feature = "manual-seal")]
@@ -276,5 +363,10 @@ where
         )?;
     }
 
+    #[cfg(feature = "manual-seal")]
+    if let Some(dev) = dev {
+        io.merge(dev.into_rpc())?;
+    }
+
     Ok(io)
 }
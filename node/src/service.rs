@@ -9,7 +9,9 @@ use std::{
 use futures::{future, StreamExt};
 // Substrate
 use sc_cli::SubstrateCli;
-use sc_client_api::{BlockchainEvents, StateBackendFor};
+use sc_client_api::{
+    execution_extensions::ExecutionExtensions, BlockchainEvents, StateBackendFor, UsageProvider,
+};
 use sc_executor::{NativeElseWasmExecutor, NativeExecutionDispatch};
 use sc_service::{
     error::Error as ServiceError, BasePath, Configuration, PartialComponents, TaskManager,
@@ -25,6 +27,7 @@ use sp_api::ConstructRuntimeApi;
 use sp_runtime::traits::BlakeTwo256;
 use sp_trie::PrefixedMemoryDB;
 // Local
+use amax_eva_rpc::TransactionJournal;
 use primitives_core::Block;
 
 #[cfg(feature = "manual-seal")]
@@ -33,12 +36,19 @@ use crate::{
     chain_spec::{RuntimeChain, RuntimeChainSpec},
     cli::Cli,
     client::{Client, EvaExecutor, RuntimeApiCollection, WallEExecutor},
+    transaction_pool::{pool_policy_task, EthPoolApi, OffchainTransactionPool, PoolPolicy},
 };
 
 pub type FullClient<RuntimeApi, Executor> =
     sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<Executor>>;
 pub type FullBackend = sc_service::TFullBackend<Block>;
 pub type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+pub type FullPoolApi<RuntimeApi, Executor> = EthPoolApi<
+    sc_transaction_pool::FullChainApi<FullClient<RuntimeApi, Executor>, Block>,
+    FullClient<RuntimeApi, Executor>,
+>;
+pub type FullPool<RuntimeApi, Executor> =
+    sc_transaction_pool::BasicPool<FullPoolApi<RuntimeApi, Executor>, Block>;
 
 #[cfg(feature = "aura")]
 pub type ConsensusResult<RuntimeApi, Executor> = (
@@ -76,6 +86,96 @@ pub(crate) fn db_config_dir(config: &Configuration) -> PathBuf {
         })
 }
 
+/// Opens the journal of the transactions submitted to `eth_sendRawTransaction`, if enabled.
+fn transaction_journal(
+    config: &Configuration,
+    cli: &Cli,
+) -> Result<Option<Arc<TransactionJournal>>, ServiceError> {
+    if !cli.run.txpool_journal {
+        return Ok(None)
+    }
+    let path = db_config_dir(config).join("transactions.journal");
+    let journal = TransactionJournal::open(path).map_err(|err| {
+        ServiceError::Other(format!("Failed to open the transaction journal: {}", err))
+    })?;
+    Ok(Some(Arc::new(journal)))
+}
+
+/// The client of the tracing tasks. With `--ethapi-trace-runtimes`, it is a second client of the
+/// backend whose executor runs the blobs of the directory instead of the on-chain runtimes of the
+/// same `spec_version`, leaving the import of the blocks to the on-chain ones. It has the settings
+/// of the client of the node but for its `--wasm-runtime-overrides`, and shares its executor and
+/// thus its runtime cache.
+///
+/// The blobs only replace the WASM code of the runtimes. The runtime API calls of the RPC follow
+/// `--execution-other`, which runs the native runtime of the node for the blocks of its own
+/// `spec_version` unless it is `wasm`. Those blocks are thus traced with the native runtime, and
+/// the node must be built with `evm-tracing` to trace them, or run with `--execution-other wasm`
+/// for the blob of its `spec_version` to be used.
+fn tracing_client<RuntimeApi, Executor>(
+    config: &Configuration,
+    cli: &Cli,
+    executor: NativeElseWasmExecutor<Executor>,
+    client: &Arc<FullClient<RuntimeApi, Executor>>,
+    backend: &Arc<FullBackend>,
+    task_manager: &TaskManager,
+) -> Result<Arc<FullClient<RuntimeApi, Executor>>, ServiceError>
+where
+    RuntimeApi:
+        ConstructRuntimeApi<Block, FullClient<RuntimeApi, Executor>> + Send + Sync + 'static,
+    Executor: NativeExecutionDispatch + 'static,
+{
+    use crate::tracing::EthApiExt;
+
+    let ethapi = &cli.run.ethapi;
+    let path = match &cli.run.ethapi_trace_runtimes {
+        Some(path) if ethapi.contains(&EthApiExt::Debug) || ethapi.contains(&EthApiExt::Trace) => {
+            path.clone()
+        },
+        _ => return Ok(client.clone()),
+    };
+
+    // The `ClientConfig` of `sc_service::new_full_parts`, which doesn't expose it.
+    let wasm_runtime_substitutes = config
+        .chain_spec
+        .code_substitutes()
+        .into_iter()
+        .map(|(number, code)| {
+            let block = number.parse().map_err(|_| {
+                ServiceError::Other(format!(
+                    "Invalid block number of a code substitute: {}",
+                    number
+                ))
+            })?;
+            Ok((block, code))
+        })
+        .collect::<Result<_, ServiceError>>()?;
+    let client_config = sc_service::ClientConfig {
+        offchain_worker_enabled: config.offchain_worker.enabled,
+        offchain_indexing_api: config.offchain_worker.indexing_enabled,
+        wasm_runtime_overrides: Some(path),
+        // The client of the node already wrote the genesis block to the backend.
+        no_genesis: true,
+        wasm_runtime_substitutes,
+    };
+
+    // Neither importing nor finalizing blocks, it needs no fork or bad blocks, and doesn't report
+    // the metrics of the client of the node a second time.
+    let tracing_client = sc_service::new_client(
+        backend.clone(),
+        executor,
+        config.chain_spec.as_storage_builder(),
+        None,
+        None,
+        ExecutionExtensions::new(config.execution_strategies.clone(), None, None),
+        Box::new(task_manager.spawn_handle()),
+        None,
+        None,
+        client_config,
+    )?;
+    Ok(Arc::new(tracing_client))
+}
+
 pub fn new_partial<RuntimeApi, Executor>(
     config: &Configuration,
     cli: &Cli,
@@ -85,13 +185,14 @@ pub fn new_partial<RuntimeApi, Executor>(
         FullBackend,
         FullSelectChain,
         sc_consensus::DefaultImportQueue<Block, FullClient<RuntimeApi, Executor>>,
-        sc_transaction_pool::FullPool<Block, FullClient<RuntimeApi, Executor>>,
+        FullPool<RuntimeApi, Executor>,
         (
             Option<Telemetry>,
             ConsensusResult<RuntimeApi, Executor>,
             Arc<fc_db::Backend<Block>>,
             Option<FilterPool>,
             (FeeHistoryCache, FeeHistoryCacheLimit),
+            Arc<FullClient<RuntimeApi, Executor>>,
         ),
     >,
     ServiceError,
@@ -125,13 +226,14 @@ where
         config.runtime_cache_size,
     );
 
-    let (client, backend, keystore_container, task_manager) =
+    let (client, backend, keystore_container, mut task_manager) =
         sc_service::new_full_parts::<Block, RuntimeApi, _>(
             config,
             telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
-            executor,
+            executor.clone(),
         )?;
     let client = Arc::new(client);
+    let tracing_client = tracing_client(config, cli, executor, &client, &backend, &task_manager)?;
 
     let telemetry = telemetry.map(|(worker, telemetry)| {
         task_manager.spawn_handle().spawn("telemetry", None, worker.run());
@@ -140,12 +242,32 @@ where
 
     let select_chain = sc_consensus::LongestChain::new(backend.clone());
 
-    let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+    // The pool of `BasicPool::new_full`, validating the Ethereum transactions with the pool policy.
+    let pool_policy = Arc::new(PoolPolicy::new(client.clone(), cli.run.pool_policy()));
+    let pool_api = sc_transaction_pool::FullChainApi::new(
+        client.clone(),
+        config.prometheus_registry(),
+        &task_manager.spawn_essential_handle(),
+    );
+    let transaction_pool = Arc::new(sc_transaction_pool::BasicPool::with_revalidation_type(
         config.transaction_pool.clone(),
         config.role.is_authority().into(),
+        Arc::new(EthPoolApi::new(pool_api, pool_policy.clone())),
         config.prometheus_registry(),
+        sc_transaction_pool::RevalidationType::Full,
         task_manager.spawn_essential_handle(),
-        client.clone(),
+        client.usage_info().chain.best_number,
+    ));
+    pool_policy.set_pool(transaction_pool.pool());
+    // The client only holds a weak reference to the pool of the offchain workers.
+    let offchain_pool = Arc::new(OffchainTransactionPool::new(transaction_pool.pool().clone()));
+    sc_client_api::ExecutorProvider::execution_extensions(&*client)
+        .register_transaction_pool(&offchain_pool);
+    task_manager.keep_alive(offchain_pool);
+    task_manager.spawn_handle().spawn(
+        "txpool-policy",
+        Some("transaction-pool"),
+        pool_policy_task(client.clone(), pool_policy),
     );
 
     // Frontier
@@ -213,6 +335,7 @@ where
                 frontier_backend,
                 filter_pool,
                 (fee_history_cache, fee_history_cache_limit),
+                tracing_client,
             ),
         })
     }
@@ -244,6 +367,7 @@ where
                 frontier_backend,
                 filter_pool,
                 (fee_history_cache, fee_history_cache_limit),
+                tracing_client,
             ),
         })
     }
@@ -280,6 +404,7 @@ where
                 frontier_backend,
                 filter_pool,
                 (fee_history_cache, fee_history_cache_limit),
+                tracing_client,
             ),
     } = new_partial::<RuntimeApi, Executor>(&config, cli)?;
 
@@ -323,6 +448,8 @@ where
     let name = config.network.node_name.clone();
     let enable_grandpa = !config.disable_grandpa;
     let prometheus_registry = config.prometheus_registry().cloned();
+    let transaction_journal = transaction_journal(&config, cli)?;
+    let rpc_metrics = amax_eva_rpc::MetricsLink::new(prometheus_registry.as_ref());
     // Frontier
     let overrides = crate::rpc::overrides_handle(client.clone());
     let block_data_cache = Arc::new(fc_rpc::EthBlockDataCacheTask::new(
@@ -351,17 +478,23 @@ where
                 ethapi: ethapi.clone(),
                 ethapi_max_permits: cli.run.ethapi_max_permits,
                 ethapi_trace_cache_duration: cli.run.ethapi_trace_cache_duration,
+                ethapi_trace_storage: cli.run.ethapi_trace_storage,
+                ethapi_trace_memory_limit: cli.run.ethapi_trace_memory_limit,
+                ethapi_trace_timeout: cli.run.ethapi_trace_timeout,
+                ethapi_trace_max_timeout: cli.run.ethapi_trace_max_timeout,
             },
             crate::tracing::SpawnTasksParams {
                 task_manager: &task_manager,
-                client: client.clone(),
+                client: tracing_client,
                 substrate_backend: backend.clone(),
                 frontier_backend: frontier_backend.clone(),
                 overrides: overrides.clone(),
+                metrics: rpc_metrics.clone(),
             },
         );
         let trace_filter_max_count = cli.run.ethapi_trace_max_count;
         let chain = config.chain_spec.runtime();
+        let transaction_journal = transaction_journal.clone();
 
         Box::new(move |deny_unsafe, subscription_task_executor| {
             let deps = crate::rpc::FullDeps {
@@ -383,6 +516,7 @@ where
                 overrides: overrides.clone(),
                 block_data_cache: block_data_cache.clone(),
                 chain,
+                transaction_journal: transaction_journal.clone(),
             };
 
             crate::rpc::create_full(deps, subscription_task_executor, ethapi.clone())
@@ -403,6 +537,32 @@ where
         telemetry: telemetry.as_mut(),
     })?;
 
+    if let Some(journal) = transaction_journal {
+        task_manager.spawn_handle().spawn(
+            "transaction-journal",
+            Some("frontier"),
+            amax_eva_rpc::journal_task(
+                client.clone(),
+                transaction_pool.clone(),
+                network.clone(),
+                journal,
+                Duration::from_secs(cli.run.txpool_rebroadcast_interval.max(1)),
+            ),
+        );
+    }
+
+    if prometheus_registry.is_some() {
+        task_manager.spawn_handle().spawn(
+            "txpool-metrics",
+            Some("frontier"),
+            amax_eva_rpc::txpool_metrics_task(
+                client.clone(),
+                transaction_pool.pool().clone(),
+                rpc_metrics,
+            ),
+        );
+    }
+
     spawn_frontier_tasks(
         &task_manager,
         client.clone(),
@@ -535,6 +695,7 @@ where
                 frontier_backend,
                 filter_pool,
                 (fee_history_cache, fee_history_cache_limit),
+                tracing_client,
             ),
     } = new_partial::<RuntimeApi, Executor>(&config, cli)?;
 
@@ -560,6 +721,8 @@ where
 
     let role = config.role.clone();
     let prometheus_registry = config.prometheus_registry().cloned();
+    let transaction_journal = transaction_journal(&config, cli)?;
+    let rpc_metrics = amax_eva_rpc::MetricsLink::new(prometheus_registry.as_ref());
     // Frontier
     let overrides = crate::rpc::overrides_handle(client.clone());
     let block_data_cache = Arc::new(fc_rpc::EthBlockDataCacheTask::new(
@@ -572,6 +735,8 @@ where
 
     // Channel for the rpc handler to communicate with the authorship task.
     let (command_sink, commands_stream) = futures::channel::mpsc::channel(1000);
+    // Shared by the authorship task and the dev rpc, which can shift the timestamp.
+    let mock_timestamp = crate::manual_seal::MockTimestampInherentDataProvider::default();
 
     let rpc_builder = {
         let client = client.clone();
@@ -591,17 +756,30 @@ where
                 ethapi: ethapi.clone(),
                 ethapi_max_permits: cli.run.ethapi_max_permits,
                 ethapi_trace_cache_duration: cli.run.ethapi_trace_cache_duration,
+                ethapi_trace_storage: cli.run.ethapi_trace_storage,
+                ethapi_trace_memory_limit: cli.run.ethapi_trace_memory_limit,
+                ethapi_trace_timeout: cli.run.ethapi_trace_timeout,
+                ethapi_trace_max_timeout: cli.run.ethapi_trace_max_timeout,
             },
             crate::tracing::SpawnTasksParams {
                 task_manager: &task_manager,
-                client: client.clone(),
+                client: tracing_client,
                 substrate_backend: backend.clone(),
                 frontier_backend: frontier_backend.clone(),
                 overrides: overrides.clone(),
+                metrics: rpc_metrics.clone(),
             },
         );
         let trace_filter_max_count = cli.run.ethapi_trace_max_count;
         let chain = config.chain_spec.runtime();
+        let transaction_journal = transaction_journal.clone();
+        let substrate_backend = backend.clone();
+        let mock_timestamp = mock_timestamp.clone();
+        // The dev rpc signs its extrinsics with the sudo key of the development chains.
+        let dev_rpc = matches!(
+            config.chain_spec.chain_type(),
+            sc_service::ChainType::Development | sc_service::ChainType::Local
+        );
 
         Box::new(move |deny_unsafe, subscription_task_executor| {
             let deps = crate::rpc::FullDeps {
@@ -623,7 +801,11 @@ where
                 overrides: overrides.clone(),
                 block_data_cache: block_data_cache.clone(),
                 chain,
+                transaction_journal: transaction_journal.clone(),
                 command_sink: Some(command_sink.clone()),
+                substrate_backend: substrate_backend.clone(),
+                mock_timestamp: mock_timestamp.clone(),
+                dev_rpc,
             };
 
             crate::rpc::create_full(deps, subscription_task_executor, ethapi.clone())
@@ -632,7 +814,7 @@ where
     };
 
     let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
-        network,
+        network: network.clone(),
         client: client.clone(),
         keystore: keystore_container.sync_keystore(),
         task_manager: &mut task_manager,
@@ -644,6 +826,32 @@ where
         telemetry: telemetry.as_mut(),
     })?;
 
+    if let Some(journal) = transaction_journal {
+        task_manager.spawn_handle().spawn(
+            "transaction-journal",
+            Some("frontier"),
+            amax_eva_rpc::journal_task(
+                client.clone(),
+                transaction_pool.clone(),
+                network.clone(),
+                journal,
+                Duration::from_secs(cli.run.txpool_rebroadcast_interval.max(1)),
+            ),
+        );
+    }
+
+    if prometheus_registry.is_some() {
+        task_manager.spawn_handle().spawn(
+            "txpool-metrics",
+            Some("frontier"),
+            amax_eva_rpc::txpool_metrics_task(
+                client.clone(),
+                transaction_pool.pool().clone(),
+                rpc_metrics,
+            ),
+        );
+    }
+
     spawn_frontier_tasks(
         &task_manager,
         client.clone(),
@@ -664,36 +872,45 @@ where
             telemetry.as_ref().map(|x| x.handle()),
         );
 
-        let create_inherent_data_providers = move |_, ()| async move {
-            let mock_timestamp = crate::manual_seal::MockTimestampInherentDataProvider;
-            Ok(mock_timestamp)
+        let create_inherent_data_providers = move |_, ()| {
+            let mock_timestamp = mock_timestamp.clone();
+            async move { Ok(mock_timestamp) }
         };
 
-        let manual_seal = match sealing {
-            Sealing::Manual => future::Either::Left(sc_consensus_manual_seal::run_manual_seal(
-                sc_consensus_manual_seal::ManualSealParams {
-                    block_import,
-                    env,
-                    client,
-                    pool: transaction_pool,
-                    commands_stream,
-                    select_chain,
-                    consensus_data_provider: None,
-                    create_inherent_data_providers,
-                },
-            )),
-            Sealing::Instant => future::Either::Right(sc_consensus_manual_seal::run_instant_seal(
-                sc_consensus_manual_seal::InstantSealParams {
-                    block_import,
-                    env,
-                    client,
-                    pool: transaction_pool,
-                    select_chain,
-                    consensus_data_provider: None,
-                    create_inherent_data_providers,
-                },
-            )),
+        // The rpc commands are always accepted, the sealing method adds its own commands.
+        let commands_stream = match sealing {
+            Sealing::Manual => commands_stream.boxed(),
+            Sealing::Instant => futures::stream::select(
+                commands_stream,
+                crate::manual_seal::instant_seal_commands(&*transaction_pool),
+            )
+            .boxed(),
+            Sealing::Interval(millis) => futures::stream::select(
+                commands_stream,
+                crate::manual_seal::interval_seal_commands(Duration::from_millis(millis)),
+            )
+            .boxed(),
+            Sealing::Hybrid(millis) => futures::stream::select(
+                commands_stream,
+                futures::stream::select(
+                    crate::manual_seal::instant_seal_commands(&*transaction_pool),
+                    crate::manual_seal::interval_seal_commands(Duration::from_millis(millis)),
+                ),
+            )
+            .boxed(),
         };
+
+        let manual_seal =
+            sc_consensus_manual_seal::run_manual_seal(sc_consensus_manual_seal::ManualSealParams {
+                block_import,
+                env,
+                client,
+                pool: transaction_pool,
+                commands_stream,
+                select_chain,
+                consensus_data_provider: None,
+                create_inherent_data_providers,
+            });
         // we spawn the future on a background thread managed by service.
         task_manager
             .spawn_essential_handle()
@@ -1,7 +1,5 @@
 use std::{sync::Arc, time::Duration};
 
-use tokio::sync::Semaphore;
-
 // Substrate
 use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
 use sc_service::TaskManager;
@@ -15,7 +13,7 @@ use fc_rpc::OverrideHandle;
 // Local
 use amax_eva_rpc::{
     CacheRequester as TraceFilterCacheRequester, CacheTask, DebugHandler, DebugRequester,
-    TraceRequester, TraceTask,
+    MetricsLink, TraceLimits, TraceRequester, TraceTask, TracingScheduler,
 };
 
 /// Eth RRC extensions.
@@ -48,12 +46,21 @@ pub struct RpcConfig {
     /// Duration (in seconds) after which the cache of `trace_filter` for a given block will be
     /// discarded.
     pub ethapi_trace_cache_duration: u64,
+    /// Whether the traces of the "trace" module include the storage slots accessed by the calls.
+    pub ethapi_trace_storage: bool,
+    /// Maximum memory (in MiB) a single `debug` trace is allowed to use.
+    pub ethapi_trace_memory_limit: usize,
+    /// Timeout (in seconds) of a `debug` trace not setting one.
+    pub ethapi_trace_timeout: u64,
+    /// Maximum timeout (in seconds) a `debug` trace can set.
+    pub ethapi_trace_max_timeout: u64,
 }
 
 #[derive(Clone, Default)]
 pub struct RpcRequesters {
     pub debug: Option<DebugRequester>,
     pub trace: Option<(TraceRequester, TraceFilterCacheRequester)>,
+    pub metrics: MetricsLink,
 }
 
 /// Tracing task parameters.
@@ -63,6 +70,7 @@ pub struct SpawnTasksParams<'a, B: BlockT, C, BE> {
     pub substrate_backend: Arc<BE>,
     pub frontier_backend: Arc<fc_db::Backend<B>>,
     pub overrides: Arc<OverrideHandle<B>>,
+    pub metrics: MetricsLink,
 }
 
 // Spawn the tasks that are required to run a tracing node.
@@ -82,7 +90,8 @@ where
     BE: Backend<B> + 'static,
     BE::State: StateBackend<BlakeTwo256>,
 {
-    let permit_pool = Arc::new(Semaphore::new(config.ethapi_max_permits as usize));
+    let scheduler =
+        TracingScheduler::new(config.ethapi_max_permits as usize, params.metrics.clone());
 
     let (trace_task, trace_filter_task, trace_requesters) = if config
         .ethapi
@@ -92,16 +101,19 @@ where
             params.client.clone(),
             params.substrate_backend.clone(),
             params.frontier_backend.clone(),
-            permit_pool.clone(),
+            scheduler.clone(),
             params.overrides.clone(),
+            config.ethapi_trace_storage,
         );
 
         let (trace_filter_task, trace_filter_requester) = CacheTask::create(
             params.client.clone(),
             params.substrate_backend.clone(),
             Duration::from_secs(config.ethapi_trace_cache_duration),
-            permit_pool.clone(),
+            scheduler.clone(),
             params.overrides.clone(),
+            config.ethapi_trace_storage,
+            params.metrics.clone(),
         );
 
         (Some(trace_task), Some(trace_filter_task), Some((trace_requester, trace_filter_requester)))
@@ -114,8 +126,13 @@ where
             params.client.clone(),
             params.substrate_backend.clone(),
             params.frontier_backend.clone(),
-            permit_pool,
+            scheduler,
             params.overrides.clone(),
+            TraceLimits {
+                memory: config.ethapi_trace_memory_limit.saturating_mul(1024 * 1024),
+                timeout: Duration::from_secs(config.ethapi_trace_timeout),
+                max_timeout: Duration::from_secs(config.ethapi_trace_max_timeout),
+            },
         );
         (Some(debug_task), Some(debug_requester))
     } else {
@@ -152,7 +169,7 @@ where
         );
     }
 
-    RpcRequesters { debug: debug_requester, trace: trace_requesters }
+    RpcRequesters { debug: debug_requester, trace: trace_requesters, metrics: params.metrics }
 }
 
 pub fn rpc_requesters<B, C, BE>(
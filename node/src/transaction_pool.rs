@@ -0,0 +1,608 @@
+//! Geth-like pool policy for the Ethereum transactions.
+//!
+//! The Substrate pool replaces a transaction by any one of higher priority providing the same tag,
+//! keeps any number of future transactions and only drops the ones underpriced by a rise of the
+//! base fee when revalidating them. The [`EthPoolApi`] wraps the validation of the pool to add:
+//! - a price bump: replacing a transaction of the same sender and nonce requires raising both its
+//!   fee cap and its tip by a given percentage;
+//! - a per-account cap on the future transactions, the ones not executable yet.
+//!
+//! And [`pool_policy_task`] evicts the transactions whose fee cap is below the base fee of the new
+//! best block, without banning them.
+
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    pin::Pin,
+    sync::{Arc, Mutex, RwLock, Weak},
+};
+
+use futures::{executor, Future, FutureExt, StreamExt};
+// Substrate
+use sc_client_api::BlockchainEvents;
+use sc_transaction_pool::{ChainApi, Pool, ValidatedTransaction};
+use sc_transaction_pool_api::{
+    error::Error as PoolError, OffchainSubmitTransaction, TransactionSource,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_core::{hashing::keccak_256, H160, H256, U256};
+use sp_runtime::{
+    generic::BlockId,
+    traits::{Block as BlockT, NumberFor},
+    transaction_validity::{
+        InvalidTransaction, TransactionValidity, TransactionValidityError, UnknownTransaction,
+        ValidTransaction,
+    },
+};
+// Frontier
+use fp_rpc::EthereumRuntimeRPCApi;
+// Local
+use primitives_core::{Block, Hash};
+use runtime_common::EthereumTransaction;
+
+/// `InvalidTransaction::Custom` code of a replacement not bumping the fees enough.
+pub const UNDERPRICED_REPLACEMENT: u8 = 200;
+/// `UnknownTransaction::Custom` code of a future transaction over the cap of its account. It is
+/// not banned, so it can be submitted again once the transactions before it are included.
+pub const ACCOUNT_QUEUE_FULL: u8 = 201;
+
+/// The policy of the pool for the Ethereum transactions.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolPolicyConfig {
+    /// Percentage both fees of a transaction must be raised by to replace it, `0` to replace it
+    /// by any transaction of higher priority.
+    pub price_bump: u32,
+    /// Maximum number of future transactions of an account, `0` for no limit.
+    pub account_queue: usize,
+    /// Whether to evict the transactions whose fee cap is below the base fee of the best block.
+    pub evict_underpriced: bool,
+}
+
+/// The sender, the nonce and the fees of an Ethereum transaction of the pool.
+#[derive(Clone, Copy, Debug)]
+struct PooledTransaction {
+    from: H160,
+    nonce: U256,
+    max_fee: U256,
+    tip: U256,
+    /// Whether it waits for a transaction of its sender not in the pool yet.
+    future: bool,
+}
+
+impl PooledTransaction {
+    fn new(transaction: &EthereumTransaction) -> Option<Self> {
+        let from = sender(transaction)?;
+        Some(match transaction {
+            EthereumTransaction::Legacy(t) => {
+                Self { from, nonce: t.nonce, max_fee: t.gas_price, tip: t.gas_price, future: false }
+            },
+            EthereumTransaction::EIP2930(t) => {
+                Self { from, nonce: t.nonce, max_fee: t.gas_price, tip: t.gas_price, future: false }
+            },
+            EthereumTransaction::EIP1559(t) => Self {
+                from,
+                nonce: t.nonce,
+                max_fee: t.max_fee_per_gas,
+                tip: t.max_priority_fee_per_gas,
+                future: false,
+            },
+        })
+    }
+}
+
+/// The sender of an Ethereum transaction.
+fn sender(transaction: &EthereumTransaction) -> Option<H160> {
+    // The dev rpc doesn't sign the transactions of the impersonated accounts.
+    #[cfg(feature = "manual-seal")]
+    if let Some(source) = runtime_common::ethereum::impersonated_source(transaction) {
+        return Some(source)
+    }
+    let public = fc_rpc::public_key(transaction).ok()?;
+    Some(H160::from(H256::from(keccak_256(&public))))
+}
+
+/// The Ethereum transactions validated for the pool, indexed by sender so that applying the
+/// policy to a transaction doesn't walk the pool.
+///
+/// They are added when validated, and pruned of the ones which left the pool at every new best
+/// block, as well as of the ones of the sender of each validated transaction.
+#[derive(Default)]
+struct PooledTransactions {
+    /// The transactions, by extrinsic hash.
+    by_hash: HashMap<Hash, PooledTransaction>,
+    /// The extrinsic hash of the last transaction validated at each nonce, by sender.
+    by_sender: HashMap<H160, BTreeMap<U256, Hash>>,
+    /// The number of future transactions among the ones of `by_sender`, by sender.
+    queued: HashMap<H160, usize>,
+}
+
+impl PooledTransactions {
+    /// The last transaction validated for `from` at `nonce`.
+    fn get(&self, from: &H160, nonce: &U256) -> Option<&PooledTransaction> {
+        let hash = self.by_sender.get(from)?.get(nonce)?;
+        self.by_hash.get(hash)
+    }
+
+    /// Whether a transaction of `from` at `nonce` follows a ready one, so is ready too.
+    fn follows_ready(&self, from: &H160, nonce: &U256) -> bool {
+        !nonce.is_zero() && self.get(from, &(*nonce - 1)).map_or(false, |t| !t.future)
+    }
+
+    /// The number of future transactions of `from`.
+    fn queued(&self, from: &H160) -> usize {
+        self.queued.get(from).copied().unwrap_or_default()
+    }
+
+    /// Adds a validated transaction, taking the place of the one of the same sender and nonce.
+    ///
+    /// The replaced transaction is kept until it is pruned, in case the pool rejects the
+    /// replacement.
+    fn insert(&mut self, hash: Hash, transaction: PooledTransaction) {
+        let replaced = self
+            .by_sender
+            .entry(transaction.from)
+            .or_default()
+            .insert(transaction.nonce, hash)
+            .and_then(|replaced| self.by_hash.get(&replaced));
+        if replaced.map_or(false, |t| t.future) {
+            self.unqueue(&transaction.from);
+        }
+        if transaction.future {
+            *self.queued.entry(transaction.from).or_default() += 1;
+        }
+        self.by_hash.insert(hash, transaction);
+    }
+
+    /// Marks a transaction as ready, once the ones before it are included.
+    fn set_ready(&mut self, hash: &Hash) {
+        let (from, nonce) = match self.by_hash.get_mut(hash) {
+            Some(transaction) if transaction.future => {
+                transaction.future = false;
+                (transaction.from, transaction.nonce)
+            },
+            _ => return,
+        };
+        if self.by_sender.get(&from).and_then(|nonces| nonces.get(&nonce)) == Some(hash) {
+            self.unqueue(&from);
+        }
+    }
+
+    fn unqueue(&mut self, from: &H160) {
+        if let Entry::Occupied(mut queued) = self.queued.entry(*from) {
+            *queued.get_mut() -= 1;
+            if *queued.get() == 0 {
+                queued.remove();
+            }
+        }
+    }
+
+    /// Forgets the transactions of `from` which left the pool since it was last pruned, `in_pool`
+    /// telling whether each one is still in it.
+    fn remove_gone(&mut self, from: &H160, in_pool: impl Fn(&Hash) -> bool) {
+        let gone = self.by_sender.get(from).map_or_else(Vec::new, |nonces| {
+            nonces
+                .iter()
+                .filter(|(_, hash)| !in_pool(hash))
+                .map(|(nonce, hash)| (*nonce, *hash))
+                .collect()
+        });
+        for (nonce, hash) in gone {
+            if self.by_hash.remove(&hash).map_or(false, |t| t.future) {
+                self.unqueue(from);
+            }
+            // The transaction it failed to replace takes its place back, if still in the pool.
+            let kept = self
+                .by_hash
+                .iter()
+                .find(|(hash, t)| t.from == *from && t.nonce == nonce && in_pool(hash))
+                .map(|(hash, t)| (*hash, t.future));
+            let nonces = self.by_sender.get_mut(from).expect("gone transactions are indexed; qed");
+            match kept {
+                Some((hash, future)) => {
+                    nonces.insert(nonce, hash);
+                    if future {
+                        *self.queued.entry(*from).or_default() += 1;
+                    }
+                },
+                None => {
+                    nonces.remove(&nonce);
+                },
+            }
+        }
+        if self.by_sender.get(from).map_or(false, BTreeMap::is_empty) {
+            self.by_sender.remove(from);
+        }
+    }
+
+    /// Keeps the transactions of the pool, `in_pool` telling whether each one is still in it and
+    /// is a future one, and rebuilds the indexes.
+    fn retain(&mut self, in_pool: impl Fn(&Hash) -> Option<bool>) {
+        self.by_hash.retain(|hash, transaction| match in_pool(hash) {
+            Some(future) => {
+                transaction.future = future;
+                true
+            },
+            None => false,
+        });
+        self.by_sender.clear();
+        self.queued.clear();
+        for (hash, transaction) in &self.by_hash {
+            self.by_sender
+                .entry(transaction.from)
+                .or_default()
+                .insert(transaction.nonce, *hash);
+            if transaction.future {
+                *self.queued.entry(transaction.from).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// The state of the policy, shared by the [`EthPoolApi`] and the [`pool_policy_task`].
+pub struct PoolPolicy<A: ChainApi, C> {
+    client: Arc<C>,
+    config: PoolPolicyConfig,
+    /// The pool validating with the policy, set once it is built.
+    pool: RwLock<Weak<Pool<EthPoolApi<A, C>>>>,
+    transactions: Mutex<PooledTransactions>,
+}
+
+impl<A, C> PoolPolicy<A, C>
+where
+    A: ChainApi<Block = Block> + 'static,
+    C: ProvideRuntimeApi<Block> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<Block>,
+{
+    pub fn new(client: Arc<C>, config: PoolPolicyConfig) -> Self {
+        Self {
+            client,
+            config,
+            pool: RwLock::new(Weak::new()),
+            transactions: Mutex::new(PooledTransactions::default()),
+        }
+    }
+
+    /// Sets the pool the policy applies to.
+    pub fn set_pool(&self, pool: &Arc<Pool<EthPoolApi<A, C>>>) {
+        *self.pool.write().expect("pool policy lock poisoned") = Arc::downgrade(pool);
+    }
+
+    /// Applies the policy to a transaction valid for the runtime.
+    fn check(
+        &self,
+        at: &BlockId<Block>,
+        source: TransactionSource,
+        hash: Hash,
+        uxt: <Block as BlockT>::Extrinsic,
+        valid: ValidTransaction,
+    ) -> TransactionValidity {
+        {
+            let mut transactions = self.transactions.lock().expect("pool policy lock poisoned");
+            // A revalidation of a transaction of the pool.
+            if transactions.by_hash.contains_key(&hash) {
+                if valid.requires.is_empty() {
+                    transactions.set_ready(&hash);
+                }
+                return Ok(valid)
+            }
+        }
+
+        let mut transaction = match self.client.runtime_api().extrinsic_filter(at, vec![uxt]) {
+            Ok(transactions) => match transactions.first().and_then(PooledTransaction::new) {
+                Some(transaction) => transaction,
+                None => return Ok(valid),
+            },
+            Err(_) => return Ok(valid),
+        };
+
+        let pool = self.pool.read().expect("pool policy lock poisoned").upgrade();
+        let mut transactions = self.transactions.lock().expect("pool policy lock poisoned");
+        // The index is only pruned on new best blocks, so the transactions of the sender which
+        // were included, dropped or evicted since are forgotten first.
+        if let Some(pool) = pool {
+            transactions.remove_gone(&transaction.from, |hash| {
+                // The ready and future transactions are the ones reported as already imported.
+                pool.validated_pool().check_is_known(hash, true).is_err()
+            });
+        }
+        transaction.future = !valid.requires.is_empty() &&
+            !transactions.follows_ready(&transaction.from, &transaction.nonce);
+        // The transactions of blocks retracted by a reorg are taken back as they are.
+        if source != TransactionSource::InBlock {
+            match transactions.get(&transaction.from, &transaction.nonce) {
+                Some(replaced) => {
+                    let bumped = |fee: U256| {
+                        fee.saturating_mul(U256::from(100 + self.config.price_bump)) / 100
+                    };
+                    if transaction.max_fee < bumped(replaced.max_fee) ||
+                        transaction.tip < bumped(replaced.tip)
+                    {
+                        return Err(InvalidTransaction::Custom(UNDERPRICED_REPLACEMENT).into())
+                    }
+                },
+                None if self.config.account_queue > 0 &&
+                    transaction.future &&
+                    transactions.queued(&transaction.from) >= self.config.account_queue =>
+                {
+                    return Err(TransactionValidityError::Unknown(UnknownTransaction::Custom(
+                        ACCOUNT_QUEUE_FULL,
+                    )))
+                },
+                None => {},
+            }
+        }
+
+        transactions.insert(hash, transaction);
+        Ok(valid)
+    }
+
+    /// Forgets the transactions which left the pool, and evicts the ones underpriced by the base
+    /// fee of the block `at`, if enabled.
+    fn maintain(&self, at: &BlockId<Block>) {
+        let pool = match self.pool.read().expect("pool policy lock poisoned").upgrade() {
+            Some(pool) => pool,
+            None => return,
+        };
+        let validated_pool = pool.validated_pool();
+        let ready = validated_pool
+            .ready()
+            .map(|transaction| transaction.hash)
+            .collect::<HashSet<_>>();
+        let futures = validated_pool
+            .futures()
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect::<HashSet<_>>();
+        let mut transactions = self.transactions.lock().expect("pool policy lock poisoned");
+        transactions.retain(|hash| {
+            if ready.contains(hash) {
+                Some(false)
+            } else if futures.contains(hash) {
+                Some(true)
+            } else {
+                None
+            }
+        });
+
+        if !self.config.evict_underpriced {
+            return
+        }
+        let base_fee = match self.client.runtime_api().gas_price(at) {
+            Ok(base_fee) => base_fee,
+            Err(err) => {
+                log::warn!(target: "txpool", "Failed to get the base fee: {:?}", err);
+                return
+            },
+        };
+        let underpriced = transactions
+            .by_hash
+            .iter()
+            .filter(|(_, transaction)| transaction.max_fee < base_fee)
+            .map(|(hash, _)| *hash)
+            .collect::<HashSet<_>>();
+        if !underpriced.is_empty() {
+            log::debug!(
+                target: "txpool",
+                "Evicting {} transactions underpriced by the base fee {}",
+                underpriced.len(),
+                base_fee,
+            );
+            transactions.retain(|hash| {
+                if underpriced.contains(hash) {
+                    None
+                } else {
+                    Some(futures.contains(hash))
+                }
+            });
+            drop(transactions);
+            // Unlike `remove_invalid`, the resubmission doesn't ban the evicted transactions, they
+            // can be submitted again once the base fee decreases. The transactions depending on
+            // them are kept as future ones.
+            validated_pool.resubmit(
+                underpriced
+                    .into_iter()
+                    .map(|hash| {
+                        let error = PoolError::InvalidTransaction(InvalidTransaction::Payment);
+                        (hash, ValidatedTransaction::Invalid(hash, error.into()))
+                    })
+                    .collect(),
+            );
+        }
+    }
+}
+
+/// The chain api of the pool, validating with the runtime then applying the [`PoolPolicy`] to the
+/// Ethereum transactions.
+pub struct EthPoolApi<A: ChainApi, C> {
+    inner: A,
+    policy: Arc<PoolPolicy<A, C>>,
+}
+
+impl<A: ChainApi, C> EthPoolApi<A, C> {
+    pub fn new(inner: A, policy: Arc<PoolPolicy<A, C>>) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl<A, C> ChainApi for EthPoolApi<A, C>
+where
+    A: ChainApi<Block = Block> + 'static,
+    C: ProvideRuntimeApi<Block> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<Block>,
+{
+    type Block = Block;
+    type Error = A::Error;
+    type ValidationFuture =
+        Pin<Box<dyn Future<Output = Result<TransactionValidity, Self::Error>> + Send>>;
+    type BodyFuture = A::BodyFuture;
+
+    fn validate_transaction(
+        &self,
+        at: &BlockId<Block>,
+        source: TransactionSource,
+        uxt: <Block as BlockT>::Extrinsic,
+    ) -> Self::ValidationFuture {
+        let (hash, _) = self.inner.hash_and_length(&uxt);
+        let validation = self.inner.validate_transaction(at, source, uxt.clone());
+        let policy = self.policy.clone();
+        let at = *at;
+        async move {
+            Ok(match validation.await? {
+                Ok(valid) => policy.check(&at, source, hash, uxt, valid),
+                invalid => invalid,
+            })
+        }
+        .boxed()
+    }
+
+    fn block_id_to_number(
+        &self,
+        at: &BlockId<Block>,
+    ) -> Result<Option<NumberFor<Block>>, Self::Error> {
+        self.inner.block_id_to_number(at)
+    }
+
+    fn block_id_to_hash(&self, at: &BlockId<Block>) -> Result<Option<Hash>, Self::Error> {
+        self.inner.block_id_to_hash(at)
+    }
+
+    fn hash_and_length(&self, uxt: &<Block as BlockT>::Extrinsic) -> (Hash, usize) {
+        self.inner.hash_and_length(uxt)
+    }
+
+    fn block_body(&self, at: &BlockId<Block>) -> Self::BodyFuture {
+        self.inner.block_body(at)
+    }
+
+    fn block_header(
+        &self,
+        at: &BlockId<Block>,
+    ) -> Result<Option<<Block as BlockT>::Header>, Self::Error> {
+        self.inner.block_header(at)
+    }
+}
+
+/// Submits the transactions of the offchain workers to the pool, like the pool of
+/// `BasicPool::new_full` registered for them does.
+pub struct OffchainTransactionPool<A: ChainApi, C> {
+    pool: Arc<Pool<EthPoolApi<A, C>>>,
+}
+
+impl<A: ChainApi, C> OffchainTransactionPool<A, C> {
+    pub fn new(pool: Arc<Pool<EthPoolApi<A, C>>>) -> Self {
+        Self { pool }
+    }
+}
+
+impl<A, C> OffchainSubmitTransaction<Block> for OffchainTransactionPool<A, C>
+where
+    A: ChainApi<Block = Block> + 'static,
+    C: ProvideRuntimeApi<Block> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<Block>,
+{
+    fn submit_at(
+        &self,
+        at: &BlockId<Block>,
+        extrinsic: <Block as BlockT>::Extrinsic,
+    ) -> Result<(), ()> {
+        // The offchain workers run on their own threads, the validation on the ones of the chain
+        // api.
+        executor::block_on(self.pool.submit_one(at, TransactionSource::Local, extrinsic))
+            .map(|_| ())
+            .map_err(|err| {
+                log::debug!(target: "txpool", "Failed to submit an offchain transaction: {:?}", err)
+            })
+    }
+}
+
+/// Maintains the [`PoolPolicy`] at every new best block.
+pub async fn pool_policy_task<A, C>(client: Arc<C>, policy: Arc<PoolPolicy<A, C>>)
+where
+    A: ChainApi<Block = Block> + 'static,
+    C: ProvideRuntimeApi<Block> + BlockchainEvents<Block> + Send + Sync + 'static,
+    C::Api: EthereumRuntimeRPCApi<Block>,
+{
+    let mut imports = client.import_notification_stream();
+    while let Some(notification) = imports.next().await {
+        if notification.is_new_best {
+            policy.maintain(&BlockId::Hash(notification.hash));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(from: u8, nonce: u64, future: bool) -> PooledTransaction {
+        PooledTransaction {
+            from: H160::repeat_byte(from),
+            nonce: nonce.into(),
+            max_fee: 1.into(),
+            tip: 1.into(),
+            future,
+        }
+    }
+
+    #[test]
+    fn counts_the_future_transactions_by_sender() {
+        let mut transactions = PooledTransactions::default();
+        transactions.insert(H256::repeat_byte(1), transaction(1, 0, false));
+        transactions.insert(H256::repeat_byte(2), transaction(1, 2, true));
+        transactions.insert(H256::repeat_byte(3), transaction(1, 3, true));
+        transactions.insert(H256::repeat_byte(4), transaction(2, 5, true));
+        assert_eq!(transactions.queued(&H160::repeat_byte(1)), 2);
+        assert_eq!(transactions.queued(&H160::repeat_byte(2)), 1);
+        assert!(transactions.follows_ready(&H160::repeat_byte(1), &1.into()));
+        assert!(!transactions.follows_ready(&H160::repeat_byte(1), &4.into()));
+
+        // A replacement takes the place of the replaced transaction.
+        transactions.insert(H256::repeat_byte(5), transaction(1, 3, true));
+        assert_eq!(transactions.queued(&H160::repeat_byte(1)), 2);
+
+        // The transaction of nonce 2 becomes ready once the one of nonce 1 is included.
+        transactions.set_ready(&H256::repeat_byte(2));
+        assert_eq!(transactions.queued(&H160::repeat_byte(1)), 1);
+    }
+
+    #[test]
+    fn prunes_the_transactions_which_left_the_pool() {
+        let mut transactions = PooledTransactions::default();
+        transactions.insert(H256::repeat_byte(1), transaction(1, 2, true));
+        transactions.insert(H256::repeat_byte(2), transaction(1, 3, true));
+        // A replacement the pool rejected.
+        transactions.insert(H256::repeat_byte(3), transaction(1, 3, true));
+
+        let in_pool = [H256::repeat_byte(1), H256::repeat_byte(2)];
+        transactions.retain(|hash| in_pool.contains(hash).then(|| *hash != H256::repeat_byte(1)));
+        assert_eq!(transactions.by_hash.len(), 2);
+        assert_eq!(transactions.by_sender[&H160::repeat_byte(1)][&3.into()], H256::repeat_byte(2));
+        assert_eq!(transactions.queued(&H160::repeat_byte(1)), 1);
+        assert!(transactions.follows_ready(&H160::repeat_byte(1), &3.into()));
+    }
+
+    #[test]
+    fn forgets_the_transactions_of_a_sender_which_left_the_pool() {
+        let mut transactions = PooledTransactions::default();
+        transactions.insert(H256::repeat_byte(1), transaction(1, 0, false));
+        transactions.insert(H256::repeat_byte(2), transaction(1, 2, true));
+        // A replacement the pool rejected.
+        transactions.insert(H256::repeat_byte(3), transaction(1, 2, true));
+        transactions.insert(H256::repeat_byte(4), transaction(1, 3, true));
+        transactions.insert(H256::repeat_byte(5), transaction(2, 0, false));
+        assert_eq!(transactions.queued(&H160::repeat_byte(1)), 2);
+
+        // The first transaction is included, and the last one dropped.
+        let in_pool = [H256::repeat_byte(2), H256::repeat_byte(5)];
+        transactions.remove_gone(&H160::repeat_byte(1), |hash| in_pool.contains(hash));
+        assert!(transactions.get(&H160::repeat_byte(1), &0.into()).is_none());
+        assert!(transactions.get(&H160::repeat_byte(1), &3.into()).is_none());
+        assert_eq!(transactions.by_sender[&H160::repeat_byte(1)][&2.into()], H256::repeat_byte(2));
+        assert_eq!(transactions.queued(&H160::repeat_byte(1)), 1);
+        // The transactions of the other senders are left as they are.
+        assert!(transactions.get(&H160::repeat_byte(2), &0.into()).is_some());
+
+        transactions.remove_gone(&H160::repeat_byte(1), |_| false);
+        assert!(!transactions.by_sender.contains_key(&H160::repeat_byte(1)));
+        assert_eq!(transactions.queued(&H160::repeat_byte(1)), 0);
+    }
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.3;
+
+/// @title EIP2612 extension of the assets precompile set
+/// @dev See https://eips.ethereum.org/EIPS/eip-2612.
+interface IERC20AssetPermit {
+    /// @dev Consumes an approval permit.
+    /// Anyone can call this function for a permit.
+    /// @custom:selector d505accf
+    /// @param owner Owner of the tokens issuing the permit
+    /// @param spender Address whose allowance will be increased.
+    /// @param value Allowed value.
+    /// @param deadline Timestamp (in seconds) after which the permit is no longer valid.
+    /// @param v V part of the signature.
+    /// @param r R part of the signature.
+    /// @param s S part of the signature.
+    function permit(
+        address owner,
+        address spender,
+        uint256 value,
+        uint256 deadline,
+        uint8 v,
+        bytes32 r,
+        bytes32 s
+    ) external;
+
+    /// @dev Returns the current nonce for given owner.
+    /// A permit must have this nonce to be consumed, which will
+    /// increase the nonce by one.
+    /// @custom:selector 7ecebe00
+    function nonces(address owner) external view returns (uint256);
+
+    /// @dev Returns the EIP712 domain separator. It is used to avoid replay
+    /// attacks across assets or other similar EIP2612 implementations.
+    /// @custom:selector 3644e515
+    function DOMAIN_SEPARATOR() external view returns (bytes32);
+}
@@ -0,0 +1,337 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarks of the selectors of `Erc20AssetsPrecompileSet`, each measured in its worst case.
+//! They are run with the `benchmark pallet` command of a node built with the `runtime-benchmarks`
+//! feature, to review the gas recorded by the selectors against their measured weights:
+//!
+//! ```sh
+//! ./target/release/amax-eva benchmark pallet \
+//!     --chain=eva-dev \
+//!     --pallet=pallet_evm_precompileset_assets_erc20 \
+//!     --extrinsic='*' \
+//!     --steps=50 \
+//!     --repeat=20
+//! ```
+
+use super::*;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, BenchmarkError};
+use frame_support::traits::{
+    fungibles::{Create, Mutate},
+    Currency, EnsureOrigin, Get,
+};
+use precompile_utils::benchmarking::PrecompileBenchmarkExt;
+use sp_core::{crypto::KeyTypeId, ecdsa};
+use sp_io::hashing::keccak_256;
+use sp_std::{vec, vec::Vec};
+
+/// Key type of the keys generated to sign permits.
+const BENCHMARK_KEY_TYPE: KeyTypeId = KeyTypeId(*b"bnch");
+
+/// Benchmarks are declared on a dedicated pallet-like struct, as precompiles are not pallets.
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+/// Implemented by runtimes benchmarking the precompile set.
+pub trait Config:
+    pallet_assets::Config
+    + pallet_evm::Config
+    + pallet_timestamp::Config
+    + AddressToAssetId<AssetIdOf<Self>>
+{
+}
+
+fn account<T: Config>(address: H160) -> T::AccountId {
+    T::AddressMapping::into_account_id(address)
+}
+
+/// Create a sufficient asset owned by `owner`, with metadata of maximal length, and mint
+/// `amount` to each of the `holders`. Returns the address of its precompile.
+fn create_asset<T: Config>(
+    owner: H160,
+    holders: &[H160],
+    amount: BalanceOf<T>,
+) -> Result<(AssetIdOf<T>, H160), BenchmarkError> {
+    let asset_id: AssetIdOf<T> = Default::default();
+    let owner_account = account::<T>(owner);
+
+    // Reserve approval deposits.
+    <T as pallet_assets::Config>::Currency::make_free_balance_be(
+        &owner_account,
+        Bounded::max_value(),
+    );
+
+    <pallet_assets::Pallet<T> as Create<T::AccountId>>::create(
+        asset_id,
+        owner_account,
+        true,
+        1u32.into(),
+    )?;
+
+    let string_limit = <T as pallet_assets::Config>::StringLimit::get() as usize;
+    pallet_assets::Pallet::<T>::force_set_metadata(
+        T::ForceOrigin::successful_origin(),
+        asset_id,
+        vec![b'n'; string_limit],
+        vec![b's'; string_limit],
+        18,
+        false,
+    )?;
+
+    for holder in holders {
+        <pallet_assets::Pallet<T> as Mutate<T::AccountId>>::mint_into(
+            asset_id,
+            &account::<T>(*holder),
+            amount,
+        )?;
+    }
+
+    Ok((asset_id, T::asset_id_to_address(asset_id)))
+}
+
+/// Generate a secp256k1 key in the keystore and return its Ethereum address.
+fn generate_signer() -> Result<(ecdsa::Public, H160), BenchmarkError> {
+    let public = sp_io::crypto::ecdsa_generate(BENCHMARK_KEY_TYPE, None);
+
+    // The address is derived from the uncompressed public key, which is recovered from a
+    // signature as the keystore only exposes the compressed one.
+    let message = [0u8; 32];
+    let signature = sp_io::crypto::ecdsa_sign_prehashed(BENCHMARK_KEY_TYPE, &public, &message)
+        .ok_or("Key has been generated")?;
+    let uncompressed = sp_io::crypto::secp256k1_ecdsa_recover(&signature.0, &message)
+        .map_err(|_| "Signature is valid")?;
+
+    Ok((public, H160::from(H256::from(keccak_256(&uncompressed)))))
+}
+
+benchmarks! {
+    where_clause {
+        where
+            T::Call: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo + CallMetadata,
+            T::Call: From<pallet_assets::Call<T>>,
+            <T::Call as Dispatchable>::Origin: From<Option<T::AccountId>>,
+            BalanceOf<T>: TryFrom<U256> + Into<U256>,
+            <T as pallet_timestamp::Config>::Moment: Into<U256>,
+    }
+
+    total_supply {
+        let owner = H160::repeat_byte(0xAA);
+        let (_, address) = create_asset::<T>(owner, &[owner], 1_000u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::total_supply {}.into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).with_static_call(true).execute()?;
+    }
+
+    balance_of {
+        let owner = H160::repeat_byte(0xAA);
+        let (_, address) = create_asset::<T>(owner, &[owner], 1_000u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> =
+            Erc20AssetsPrecompileSetCall::<T, ()>::balance_of { who: owner.into() }.into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).with_static_call(true).execute()?;
+    }
+
+    allowance {
+        let owner = H160::repeat_byte(0xAA);
+        let spender = H160::repeat_byte(0xBB);
+        let (_, address) = create_asset::<T>(owner, &[owner], 1_000u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::allowance {
+            owner: owner.into(),
+            spender: spender.into(),
+        }
+        .into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).with_static_call(true).execute()?;
+    }
+
+    // Worst case: an existing approval is cancelled before approving the new amount.
+    approve {
+        let owner = H160::repeat_byte(0xAA);
+        let spender = H160::repeat_byte(0xBB);
+        let (_, address) = create_asset::<T>(owner, &[owner], 1_000u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let approve: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::approve {
+            spender: spender.into(),
+            value: 100u32.into(),
+        }
+        .into();
+        precompiles.prepare_benchmark(owner, address, approve.clone()).execute()?;
+    }: {
+        precompiles.prepare_benchmark(owner, address, approve).execute()?;
+    }
+
+    // Worst case: the recipient account is created.
+    transfer {
+        let owner = H160::repeat_byte(0xAA);
+        let to = H160::repeat_byte(0xBB);
+        let (_, address) = create_asset::<T>(owner, &[owner], 1_000u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::transfer {
+            to: to.into(),
+            value: 100u32.into(),
+        }
+        .into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).execute()?;
+    }
+
+    // Worst case: the transfer uses an approval and the recipient account is created.
+    transfer_from {
+        let owner = H160::repeat_byte(0xAA);
+        let spender = H160::repeat_byte(0xBB);
+        let to = H160::repeat_byte(0xCC);
+        let (_, address) = create_asset::<T>(owner, &[owner], 1_000u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let approve: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::approve {
+            spender: spender.into(),
+            value: 100u32.into(),
+        }
+        .into();
+        precompiles.prepare_benchmark(owner, address, approve).execute()?;
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::transfer_from {
+            from: owner.into(),
+            to: to.into(),
+            value: 100u32.into(),
+        }
+        .into();
+    }: {
+        precompiles.prepare_benchmark(spender, address, input).execute()?;
+    }
+
+    name {
+        let owner = H160::repeat_byte(0xAA);
+        let (_, address) = create_asset::<T>(owner, &[], 0u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::name {}.into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).with_static_call(true).execute()?;
+    }
+
+    symbol {
+        let owner = H160::repeat_byte(0xAA);
+        let (_, address) = create_asset::<T>(owner, &[], 0u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::symbol {}.into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).with_static_call(true).execute()?;
+    }
+
+    decimals {
+        let owner = H160::repeat_byte(0xAA);
+        let (_, address) = create_asset::<T>(owner, &[], 0u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::decimals {}.into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).with_static_call(true).execute()?;
+    }
+
+    // Worst case: the beneficiary account is created.
+    mint {
+        let owner = H160::repeat_byte(0xAA);
+        let to = H160::repeat_byte(0xBB);
+        let (_, address) = create_asset::<T>(owner, &[], 0u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::mint {
+            to: to.into(),
+            value: 100u32.into(),
+        }
+        .into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).execute()?;
+    }
+
+    // Worst case: the whole balance is burned and the account removed.
+    burn {
+        let owner = H160::repeat_byte(0xAA);
+        let from = H160::repeat_byte(0xBB);
+        let (_, address) = create_asset::<T>(owner, &[from], 1_000u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::burn {
+            from: from.into(),
+            value: 1_000u32.into(),
+        }
+        .into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).execute()?;
+    }
+
+    // Worst case: an existing approval is replaced.
+    permit {
+        let (public, owner) = generate_signer()?;
+        let spender = H160::repeat_byte(0xBB);
+        let (asset_id, address) = create_asset::<T>(owner, &[owner], 1_000u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let approve: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::approve {
+            spender: spender.into(),
+            value: 100u32.into(),
+        }
+        .into();
+        precompiles.prepare_benchmark(owner, address, approve).execute()?;
+
+        let value = U256::from(500u32);
+        let deadline = U256::MAX;
+        let permit = Eip2612::<T>::generate_permit(
+            address,
+            asset_id,
+            owner,
+            spender,
+            value,
+            U256::zero(),
+            deadline,
+        );
+        let signature = sp_io::crypto::ecdsa_sign_prehashed(BENCHMARK_KEY_TYPE, &public, &permit)
+            .ok_or("Key has been generated")?;
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::permit {
+            owner: owner.into(),
+            spender: spender.into(),
+            value,
+            deadline,
+            v: signature.0[64] + 27,
+            r: H256::from_slice(&signature.0[..32]),
+            s: H256::from_slice(&signature.0[32..64]),
+        }
+        .into();
+    }: {
+        precompiles.prepare_benchmark(spender, address, input).execute()?;
+    }
+
+    nonces {
+        let owner = H160::repeat_byte(0xAA);
+        let (_, address) = create_asset::<T>(owner, &[], 0u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> =
+            Erc20AssetsPrecompileSetCall::<T, ()>::nonces { owner: owner.into() }.into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).with_static_call(true).execute()?;
+    }
+
+    domain_separator {
+        let owner = H160::repeat_byte(0xAA);
+        let (_, address) = create_asset::<T>(owner, &[], 0u32.into())?;
+        let precompiles = Erc20AssetsPrecompileSet::<T>::default();
+        let input: Vec<u8> = Erc20AssetsPrecompileSetCall::<T, ()>::domain_separator {}.into();
+    }: {
+        precompiles.prepare_benchmark(owner, address, input).with_static_call(true).execute()?;
+    }
+}
+
+impl_benchmark_test_suite!(
+    Pallet,
+    crate::mock::ExtBuilder::default().build_with_keystore(),
+    crate::mock::Runtime,
+);
@@ -0,0 +1,204 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use frame_support::{
+    storage::types::{StorageDoubleMap, ValueQuery},
+    traits::{fungibles::metadata::Inspect as _, Get, StorageInstance},
+    Blake2_128Concat,
+};
+use sp_io::hashing::keccak_256;
+use sp_std::vec::Vec;
+
+/// EIP2612 permit typehash.
+pub const PERMIT_TYPEHASH: [u8; 32] = keccak256!(
+    "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)"
+);
+
+/// EIP2612 permit domain used to compute an individualized domain separator.
+const PERMIT_DOMAIN: [u8; 32] = keccak256!(
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"
+);
+
+/// Storage prefix for nonces.
+pub struct Nonces;
+
+impl StorageInstance for Nonces {
+    const STORAGE_PREFIX: &'static str = "Nonces";
+
+    fn pallet_prefix() -> &'static str {
+        "Erc20AssetsPrecompileSet"
+    }
+}
+
+/// Storage type used to store EIP2612 nonces.
+/// Nonces are keyed by the address of the asset precompile, then by the owner.
+pub type NoncesStorage = StorageDoubleMap<
+    Nonces,
+    // Asset contract address
+    Blake2_128Concat,
+    H160,
+    // Owner
+    Blake2_128Concat,
+    H160,
+    // Nonce
+    U256,
+    ValueQuery,
+>;
+
+pub struct Eip2612<Runtime, Instance: 'static = ()>(PhantomData<(Runtime, Instance)>);
+
+impl<Runtime, Instance> Eip2612<Runtime, Instance>
+where
+    Instance: 'static,
+    Runtime: pallet_assets::Config<Instance> + pallet_evm::Config + pallet_timestamp::Config,
+    Runtime: AddressToAssetId<AssetIdOf<Runtime, Instance>>,
+    Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo + CallMetadata,
+    Runtime::Call: From<pallet_assets::Call<Runtime, Instance>>,
+    <Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+    BalanceOf<Runtime, Instance>: TryFrom<U256> + Into<U256>,
+    <Runtime as pallet_timestamp::Config>::Moment: Into<U256>,
+{
+    /// The domain separator depends on the name of the asset, the chain id and the address of
+    /// the precompile, so that a permit can't be replayed for another asset or chain.
+    pub fn compute_domain_separator(
+        address: H160,
+        asset_id: AssetIdOf<Runtime, Instance>,
+    ) -> [u8; 32] {
+        let name: H256 =
+            keccak_256(&pallet_assets::Pallet::<Runtime, Instance>::name(asset_id)).into();
+        let version: H256 = keccak256!("1").into();
+        let chain_id: U256 = Runtime::ChainId::get().into();
+
+        let domain_separator_inner = EvmDataWriter::new()
+            .write(H256::from(PERMIT_DOMAIN))
+            .write(name)
+            .write(version)
+            .write(chain_id)
+            .write(Address(address))
+            .build();
+
+        keccak_256(&domain_separator_inner)
+    }
+
+    pub fn generate_permit(
+        address: H160,
+        asset_id: AssetIdOf<Runtime, Instance>,
+        owner: H160,
+        spender: H160,
+        value: U256,
+        nonce: U256,
+        deadline: U256,
+    ) -> [u8; 32] {
+        let domain_separator = Self::compute_domain_separator(address, asset_id);
+
+        let permit_content = EvmDataWriter::new()
+            .write(H256::from(PERMIT_TYPEHASH))
+            .write(Address(owner))
+            .write(Address(spender))
+            .write(value)
+            .write(nonce)
+            .write(deadline)
+            .build();
+        let permit_content = keccak_256(&permit_content);
+
+        let mut pre_digest = Vec::with_capacity(2 + 32 + 32);
+        pre_digest.extend_from_slice(b"\x19\x01");
+        pre_digest.extend_from_slice(&domain_separator);
+        pre_digest.extend_from_slice(&permit_content);
+        keccak_256(&pre_digest)
+    }
+
+    /// Approve `spender` to spend `value` assets of `owner`, authorized by a signature of the
+    /// owner instead of a transaction sent by it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn permit(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        owner: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+        v: u8,
+        r: H256,
+        s: H256,
+    ) -> EvmResult {
+        // Nonce read/write and name read for the domain separator.
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost() * 2)?;
+        handle.record_cost(RuntimeHelper::<Runtime>::db_write_gas_cost())?;
+        handle.record_log_costs_manual(3, 32)?;
+
+        let owner: H160 = owner.into();
+        let spender: H160 = spender.into();
+        let address = handle.code_address();
+
+        // Blockchain time is in ms while Ethereum use second timestamps.
+        let timestamp: U256 = pallet_timestamp::Pallet::<Runtime>::get().into();
+        let timestamp = timestamp / 1000;
+
+        if deadline < timestamp {
+            return Err(revert("Permit expired"))
+        }
+
+        let nonce = NoncesStorage::get(address, owner);
+
+        let permit =
+            Self::generate_permit(address, asset_id, owner, spender, value, nonce, deadline);
+
+        let mut sig = [0u8; 65];
+        sig[0..32].copy_from_slice(r.as_bytes());
+        sig[32..64].copy_from_slice(s.as_bytes());
+        sig[64] = v;
+
+        let signer = sp_io::crypto::secp256k1_ecdsa_recover(&sig, &permit)
+            .map_err(|_| revert("Invalid permit"))?;
+        let signer = H160::from(H256::from_slice(keccak_256(&signer).as_slice()));
+
+        if signer == H160::zero() || signer != owner {
+            return Err(revert("Invalid permit"))
+        }
+
+        NoncesStorage::insert(address, owner, nonce + U256::one());
+
+        Erc20AssetsPrecompileSet::<Runtime, Instance>::approve_inner(
+            asset_id, handle, owner, spender, value,
+        )?;
+
+        let log = Approval { owner: owner.into(), spender: spender.into(), value }.log(address);
+        log.record(handle)?;
+
+        Ok(())
+    }
+
+    pub fn nonces(
+        _asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        owner: Address,
+    ) -> EvmResult<U256> {
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+
+        Ok(NoncesStorage::get(handle.code_address(), H160::from(owner)))
+    }
+
+    pub fn domain_separator(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+    ) -> EvmResult<H256> {
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+
+        Ok(Self::compute_domain_separator(handle.code_address(), asset_id).into())
+    }
+}
@@ -0,0 +1,449 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Precompile set exposing every asset of `pallet_assets` as an ERC-20 token.
+//!
+//! Each asset lives at an address derived from its id (see [`AddressToAssetId`]), and balances,
+//! allowances and metadata are stored by `pallet_assets` instead of contract storage.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use fp_evm::PrecompileHandle;
+use frame_support::{
+    dispatch::{Dispatchable, GetDispatchInfo, PostDispatchInfo},
+    traits::fungibles::{approvals::Inspect as _, metadata::Inspect as _, Inspect as _},
+};
+use pallet_evm::AddressMapping;
+use precompile_utils::{prelude::*, substrate::CallMetadata};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::{Bounded, StaticLookup, Zero};
+use sp_std::marker::PhantomData;
+
+mod eip2612;
+use eip2612::Eip2612;
+
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+pub type BalanceOf<Runtime, Instance = ()> = <Runtime as pallet_assets::Config<Instance>>::Balance;
+
+pub type AssetIdOf<Runtime, Instance = ()> = <Runtime as pallet_assets::Config<Instance>>::AssetId;
+
+/// Conversion between an asset id and the address of its ERC-20 precompile.
+/// Implemented by the runtime, which chooses the address prefix routed to this precompile set.
+pub trait AddressToAssetId<AssetId> {
+    /// Asset id corresponding to this address, if it has the expected prefix.
+    fn address_to_asset_id(address: H160) -> Option<AssetId>;
+
+    /// Address of the ERC-20 precompile of this asset.
+    fn asset_id_to_address(asset_id: AssetId) -> H160;
+}
+
+event!(
+    pub struct Transfer("Transfer(address,address,uint256)") {
+        #[indexed]
+        from: Address,
+        #[indexed]
+        to: Address,
+        value: U256,
+    }
+);
+
+event!(
+    pub struct Approval("Approval(address,address,uint256)") {
+        #[indexed]
+        owner: Address,
+        #[indexed]
+        spender: Address,
+        value: U256,
+    }
+);
+
+/// Precompile set exposing assets of `pallet_assets` as ERC-20 tokens.
+///
+/// Metadata, balances and allowances are read from the pallet, while transfers, approvals, mints
+/// and burns are dispatched as calls of the pallet from the caller account. Minting and burning
+/// thus require the caller to be respectively the issuer and the admin of the asset, which are
+/// the owner unless changed with `set_team`.
+pub struct Erc20AssetsPrecompileSet<Runtime, Instance: 'static = ()>(
+    PhantomData<(Runtime, Instance)>,
+);
+
+impl<Runtime, Instance> Default for Erc20AssetsPrecompileSet<Runtime, Instance> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[precompile_utils::precompile]
+#[precompile::precompile_set]
+impl<Runtime, Instance> Erc20AssetsPrecompileSet<Runtime, Instance>
+where
+    Instance: 'static,
+    Runtime: pallet_assets::Config<Instance> + pallet_evm::Config + pallet_timestamp::Config,
+    Runtime: AddressToAssetId<AssetIdOf<Runtime, Instance>>,
+    Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo + CallMetadata,
+    Runtime::Call: From<pallet_assets::Call<Runtime, Instance>>,
+    <Runtime::Call as Dispatchable>::Origin: From<Option<Runtime::AccountId>>,
+    BalanceOf<Runtime, Instance>: TryFrom<U256> + Into<U256>,
+    <Runtime as pallet_timestamp::Config>::Moment: Into<U256>,
+{
+    /// The asset must exist for its address to be a precompile.
+    #[precompile::discriminant]
+    fn discriminant(address: H160) -> Option<AssetIdOf<Runtime, Instance>> {
+        let asset_id = Runtime::address_to_asset_id(address)?;
+
+        pallet_assets::Pallet::<Runtime, Instance>::maybe_total_supply(asset_id).map(|_| asset_id)
+    }
+
+    #[precompile::public("totalSupply()")]
+    #[precompile::view]
+    fn total_supply(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+    ) -> EvmResult<U256> {
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+
+        Ok(pallet_assets::Pallet::<Runtime, Instance>::total_issuance(asset_id).into())
+    }
+
+    #[precompile::public("balanceOf(address)")]
+    #[precompile::view]
+    fn balance_of(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        who: Address,
+    ) -> EvmResult<U256> {
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+
+        let who: Runtime::AccountId = Runtime::AddressMapping::into_account_id(who.into());
+
+        Ok(pallet_assets::Pallet::<Runtime, Instance>::balance(asset_id, &who).into())
+    }
+
+    #[precompile::public("allowance(address,address)")]
+    #[precompile::view]
+    fn allowance(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        owner: Address,
+        spender: Address,
+    ) -> EvmResult<U256> {
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+
+        let owner: Runtime::AccountId = Runtime::AddressMapping::into_account_id(owner.into());
+        let spender: Runtime::AccountId = Runtime::AddressMapping::into_account_id(spender.into());
+
+        Ok(pallet_assets::Pallet::<Runtime, Instance>::allowance(asset_id, &owner, &spender).into())
+    }
+
+    #[precompile::public("approve(address,uint256)")]
+    fn approve(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        spender: Address,
+        value: U256,
+    ) -> EvmResult<bool> {
+        handle.record_log_costs_manual(3, 32)?;
+
+        let owner = handle.context().caller;
+        Self::approve_inner(asset_id, handle, owner, spender.into(), value)?;
+
+        let log = Approval { owner: owner.into(), spender, value }.log(handle.context().address);
+        log.record(handle)?;
+
+        Ok(true)
+    }
+
+    /// Replace the allowance of `spender` over the assets of `owner`.
+    /// `pallet_assets` approvals are cumulative, so any existing approval is cancelled first.
+    fn approve_inner(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        owner: H160,
+        spender: H160,
+        value: U256,
+    ) -> EvmResult {
+        let owner: Runtime::AccountId = Runtime::AddressMapping::into_account_id(owner);
+        let spender: Runtime::AccountId = Runtime::AddressMapping::into_account_id(spender);
+        // Allowances larger than the balance type are capped, as is usual for infinite approvals.
+        let amount = Self::u256_to_amount(value).unwrap_or_else(|_| Bounded::max_value());
+
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+        if !pallet_assets::Pallet::<Runtime, Instance>::allowance(asset_id, &owner, &spender)
+            .is_zero()
+        {
+            RuntimeHelper::<Runtime>::try_dispatch(
+                handle,
+                Some(owner.clone()).into(),
+                pallet_assets::Call::<Runtime, Instance>::cancel_approval {
+                    id: asset_id,
+                    delegate: Runtime::Lookup::unlookup(spender.clone()),
+                },
+            )?;
+        }
+
+        if !amount.is_zero() {
+            RuntimeHelper::<Runtime>::try_dispatch(
+                handle,
+                Some(owner).into(),
+                pallet_assets::Call::<Runtime, Instance>::approve_transfer {
+                    id: asset_id,
+                    delegate: Runtime::Lookup::unlookup(spender),
+                    amount,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[precompile::public("transfer(address,uint256)")]
+    fn transfer(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        to: Address,
+        value: U256,
+    ) -> EvmResult<bool> {
+        handle.record_log_costs_manual(3, 32)?;
+
+        let caller = handle.context().caller;
+        let amount = Self::u256_to_amount(value).in_field("value")?;
+
+        {
+            let origin: Runtime::AccountId = Runtime::AddressMapping::into_account_id(caller);
+            let to: Runtime::AccountId = Runtime::AddressMapping::into_account_id(to.into());
+
+            RuntimeHelper::<Runtime>::try_dispatch(
+                handle,
+                Some(origin).into(),
+                pallet_assets::Call::<Runtime, Instance>::transfer {
+                    id: asset_id,
+                    target: Runtime::Lookup::unlookup(to),
+                    amount,
+                },
+            )?;
+        }
+
+        let log = Transfer { from: caller.into(), to, value }.log(handle.context().address);
+        log.record(handle)?;
+
+        Ok(true)
+    }
+
+    #[precompile::public("transferFrom(address,address,uint256)")]
+    fn transfer_from(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        from: Address,
+        to: Address,
+        value: U256,
+    ) -> EvmResult<bool> {
+        handle.record_log_costs_manual(3, 32)?;
+
+        let caller = handle.context().caller;
+        let amount = Self::u256_to_amount(value).in_field("value")?;
+
+        {
+            let caller: Runtime::AccountId = Runtime::AddressMapping::into_account_id(caller);
+            let from: Runtime::AccountId = Runtime::AddressMapping::into_account_id(from.into());
+            let to: Runtime::AccountId = Runtime::AddressMapping::into_account_id(to.into());
+
+            // If caller is "from", it can spend as much as it wants from its own balance.
+            if caller != from {
+                RuntimeHelper::<Runtime>::try_dispatch(
+                    handle,
+                    Some(caller).into(),
+                    pallet_assets::Call::<Runtime, Instance>::transfer_approved {
+                        id: asset_id,
+                        owner: Runtime::Lookup::unlookup(from),
+                        destination: Runtime::Lookup::unlookup(to),
+                        amount,
+                    },
+                )?;
+            } else {
+                RuntimeHelper::<Runtime>::try_dispatch(
+                    handle,
+                    Some(from).into(),
+                    pallet_assets::Call::<Runtime, Instance>::transfer {
+                        id: asset_id,
+                        target: Runtime::Lookup::unlookup(to),
+                        amount,
+                    },
+                )?;
+            }
+        }
+
+        let log = Transfer { from, to, value }.log(handle.context().address);
+        log.record(handle)?;
+
+        Ok(true)
+    }
+
+    #[precompile::public("name()")]
+    #[precompile::view]
+    fn name(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+    ) -> EvmResult<UnboundedString> {
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+
+        Ok(pallet_assets::Pallet::<Runtime, Instance>::name(asset_id).into())
+    }
+
+    #[precompile::public("symbol()")]
+    #[precompile::view]
+    fn symbol(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+    ) -> EvmResult<UnboundedString> {
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+
+        Ok(pallet_assets::Pallet::<Runtime, Instance>::symbol(asset_id).into())
+    }
+
+    #[precompile::public("decimals()")]
+    #[precompile::view]
+    fn decimals(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+    ) -> EvmResult<u8> {
+        handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+
+        Ok(pallet_assets::Pallet::<Runtime, Instance>::decimals(asset_id))
+    }
+
+    #[precompile::public("mint(address,uint256)")]
+    fn mint(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        to: Address,
+        value: U256,
+    ) -> EvmResult<bool> {
+        handle.record_log_costs_manual(3, 32)?;
+
+        let amount = Self::u256_to_amount(value).in_field("value")?;
+
+        {
+            let origin: Runtime::AccountId =
+                Runtime::AddressMapping::into_account_id(handle.context().caller);
+            let to: Runtime::AccountId = Runtime::AddressMapping::into_account_id(to.into());
+
+            RuntimeHelper::<Runtime>::try_dispatch(
+                handle,
+                Some(origin).into(),
+                pallet_assets::Call::<Runtime, Instance>::mint {
+                    id: asset_id,
+                    beneficiary: Runtime::Lookup::unlookup(to),
+                    amount,
+                },
+            )?;
+        }
+
+        let log = Transfer { from: H160::zero().into(), to, value }.log(handle.context().address);
+        log.record(handle)?;
+
+        Ok(true)
+    }
+
+    #[precompile::public("burn(address,uint256)")]
+    fn burn(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        from: Address,
+        value: U256,
+    ) -> EvmResult<bool> {
+        handle.record_log_costs_manual(3, 32)?;
+
+        let amount = Self::u256_to_amount(value).in_field("value")?;
+
+        {
+            let origin: Runtime::AccountId =
+                Runtime::AddressMapping::into_account_id(handle.context().caller);
+            let from: Runtime::AccountId = Runtime::AddressMapping::into_account_id(from.into());
+
+            // `pallet_assets` burns as much as possible up to the amount, while an ERC-20 burn
+            // must either burn the full amount or fail.
+            handle.record_cost(RuntimeHelper::<Runtime>::db_read_gas_cost())?;
+            if pallet_assets::Pallet::<Runtime, Instance>::balance(asset_id, &from) < amount {
+                return Err(revert("Burn amount exceeds balance"))
+            }
+
+            RuntimeHelper::<Runtime>::try_dispatch(
+                handle,
+                Some(origin).into(),
+                pallet_assets::Call::<Runtime, Instance>::burn {
+                    id: asset_id,
+                    who: Runtime::Lookup::unlookup(from),
+                    amount,
+                },
+            )?;
+        }
+
+        let log = Transfer { from, to: H160::zero().into(), value }.log(handle.context().address);
+        log.record(handle)?;
+
+        Ok(true)
+    }
+
+    #[precompile::public("permit(address,address,uint256,uint256,uint8,bytes32,bytes32)")]
+    #[allow(clippy::too_many_arguments)]
+    fn permit(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        owner: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+        v: u8,
+        r: H256,
+        s: H256,
+    ) -> EvmResult {
+        Eip2612::<Runtime, Instance>::permit(
+            asset_id, handle, owner, spender, value, deadline, v, r, s,
+        )
+    }
+
+    #[precompile::public("nonces(address)")]
+    #[precompile::view]
+    fn nonces(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+        owner: Address,
+    ) -> EvmResult<U256> {
+        Eip2612::<Runtime, Instance>::nonces(asset_id, handle, owner)
+    }
+
+    #[precompile::public("DOMAIN_SEPARATOR()")]
+    #[precompile::view]
+    fn domain_separator(
+        asset_id: AssetIdOf<Runtime, Instance>,
+        handle: &mut impl PrecompileHandle,
+    ) -> EvmResult<H256> {
+        Eip2612::<Runtime, Instance>::domain_separator(asset_id, handle)
+    }
+
+    fn u256_to_amount(value: U256) -> MayRevert<BalanceOf<Runtime, Instance>> {
+        value
+            .try_into()
+            .map_err(|_| RevertReason::value_is_too_large("balance type").into())
+    }
+}
@@ -0,0 +1,446 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{eip2612::Eip2612, mock::*, *};
+use fp_evm::PrecompileSet;
+use frame_support::assert_ok;
+use libsecp256k1::{sign, Message, PublicKey, SecretKey};
+use precompile_utils::{solidity, testing::*};
+
+type PCall = Erc20AssetsPrecompileSetCall<Runtime, ()>;
+
+const ASSET_ID: AssetId = 0;
+
+fn asset_address() -> H160 {
+    Runtime::asset_id_to_address(ASSET_ID)
+}
+
+/// Create an asset owned by Alice, with metadata.
+fn create_asset() {
+    assert_ok!(Assets::force_create(Origin::root(), ASSET_ID, ALICE.into(), true, 1));
+    assert_ok!(Assets::force_set_metadata(
+        Origin::root(),
+        ASSET_ID,
+        b"Tether USD".to_vec(),
+        b"USDT".to_vec(),
+        6,
+        false,
+    ));
+}
+
+fn build() -> sp_io::TestExternalities {
+    ExtBuilder::default()
+        .with_balances(vec![(ALICE.into(), 1000), (BOB.into(), 1000)])
+        .build()
+}
+
+#[test]
+fn address_to_asset_id_roundtrip() {
+    let address = Runtime::asset_id_to_address(42);
+
+    assert_eq!(&address.as_bytes()[..4], ASSET_PRECOMPILE_ADDRESS_PREFIX);
+    assert_eq!(Runtime::address_to_asset_id(address), Some(42));
+    assert_eq!(Runtime::address_to_asset_id(H160::repeat_byte(0x11)), None);
+}
+
+#[test]
+fn only_existing_assets_are_precompiles() {
+    build().execute_with(|| {
+        assert!(!precompiles().is_precompile(asset_address()));
+
+        create_asset();
+
+        assert!(precompiles().is_precompile(asset_address()));
+        assert!(!precompiles().is_precompile(Runtime::asset_id_to_address(1)));
+    });
+}
+
+#[test]
+fn metadata() {
+    build().execute_with(|| {
+        create_asset();
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::name {})
+            .expect_no_logs()
+            .execute_returns_encoded(UnboundedString::from("Tether USD"));
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::symbol {})
+            .expect_no_logs()
+            .execute_returns_encoded(UnboundedString::from("USDT"));
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::decimals {})
+            .expect_no_logs()
+            .execute_returns_encoded(6u8);
+    });
+}
+
+#[test]
+fn mint_and_balances() {
+    build().execute_with(|| {
+        create_asset();
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::mint { to: BOB.into(), value: 400.into() })
+            .expect_log(
+                Transfer { from: H160::zero().into(), to: BOB.into(), value: 400.into() }
+                    .log(asset_address()),
+            )
+            .execute_returns_encoded(true);
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::total_supply {})
+            .execute_returns_encoded(U256::from(400));
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::balance_of { who: BOB.into() })
+            .execute_returns_encoded(U256::from(400));
+    });
+}
+
+#[test]
+fn mint_requires_issuer() {
+    build().execute_with(|| {
+        create_asset();
+
+        precompiles()
+            .prepare_test(BOB, asset_address(), PCall::mint { to: BOB.into(), value: 400.into() })
+            .execute_reverts(|output| output.starts_with(b"Dispatched call failed"));
+    });
+}
+
+#[test]
+fn burn() {
+    build().execute_with(|| {
+        create_asset();
+        assert_ok!(Assets::mint(Origin::signed(ALICE.into()), ASSET_ID, BOB.into(), 400));
+
+        precompiles()
+            .prepare_test(
+                ALICE,
+                asset_address(),
+                PCall::burn { from: BOB.into(), value: 500.into() },
+            )
+            .execute_reverts(|output| output == b"Burn amount exceeds balance");
+
+        precompiles()
+            .prepare_test(
+                ALICE,
+                asset_address(),
+                PCall::burn { from: BOB.into(), value: 100.into() },
+            )
+            .expect_log(
+                Transfer { from: BOB.into(), to: H160::zero().into(), value: 100.into() }
+                    .log(asset_address()),
+            )
+            .execute_returns_encoded(true);
+
+        assert_eq!(Assets::balance(ASSET_ID, AccountId::from(BOB)), 300);
+        assert_eq!(Assets::total_supply(ASSET_ID), 300);
+    });
+}
+
+#[test]
+fn transfer() {
+    build().execute_with(|| {
+        create_asset();
+        assert_ok!(Assets::mint(Origin::signed(ALICE.into()), ASSET_ID, ALICE.into(), 400));
+
+        precompiles()
+            .prepare_test(
+                ALICE,
+                asset_address(),
+                PCall::transfer { to: BOB.into(), value: 150.into() },
+            )
+            .expect_log(
+                Transfer { from: ALICE.into(), to: BOB.into(), value: 150.into() }
+                    .log(asset_address()),
+            )
+            .execute_returns_encoded(true);
+
+        assert_eq!(Assets::balance(ASSET_ID, AccountId::from(ALICE)), 250);
+        assert_eq!(Assets::balance(ASSET_ID, AccountId::from(BOB)), 150);
+
+        precompiles()
+            .prepare_test(
+                ALICE,
+                asset_address(),
+                PCall::transfer { to: BOB.into(), value: U256::MAX },
+            )
+            .execute_reverts(|output| output == b"value: Value is too large for balance type");
+    });
+}
+
+#[test]
+fn approve_replaces_allowance() {
+    build().execute_with(|| {
+        create_asset();
+        assert_ok!(Assets::mint(Origin::signed(ALICE.into()), ASSET_ID, ALICE.into(), 400));
+
+        for value in [300u64, 100] {
+            precompiles()
+                .prepare_test(
+                    ALICE,
+                    asset_address(),
+                    PCall::approve { spender: BOB.into(), value: value.into() },
+                )
+                .expect_log(
+                    Approval { owner: ALICE.into(), spender: BOB.into(), value: value.into() }
+                        .log(asset_address()),
+                )
+                .execute_returns_encoded(true);
+
+            precompiles()
+                .prepare_test(
+                    ALICE,
+                    asset_address(),
+                    PCall::allowance { owner: ALICE.into(), spender: BOB.into() },
+                )
+                .execute_returns_encoded(U256::from(value));
+        }
+    });
+}
+
+#[test]
+fn transfer_from() {
+    build().execute_with(|| {
+        create_asset();
+        assert_ok!(Assets::mint(Origin::signed(ALICE.into()), ASSET_ID, ALICE.into(), 400));
+        assert_ok!(Assets::approve_transfer(
+            Origin::signed(ALICE.into()),
+            ASSET_ID,
+            BOB.into(),
+            100
+        ));
+
+        precompiles()
+            .prepare_test(
+                BOB,
+                asset_address(),
+                PCall::transfer_from { from: ALICE.into(), to: CHARLIE.into(), value: 150.into() },
+            )
+            .execute_reverts(|output| output.starts_with(b"Dispatched call failed"));
+
+        precompiles()
+            .prepare_test(
+                BOB,
+                asset_address(),
+                PCall::transfer_from { from: ALICE.into(), to: CHARLIE.into(), value: 60.into() },
+            )
+            .expect_log(
+                Transfer { from: ALICE.into(), to: CHARLIE.into(), value: 60.into() }
+                    .log(asset_address()),
+            )
+            .execute_returns_encoded(true);
+
+        assert_eq!(Assets::balance(ASSET_ID, AccountId::from(CHARLIE)), 60);
+
+        precompiles()
+            .prepare_test(
+                BOB,
+                asset_address(),
+                PCall::allowance { owner: ALICE.into(), spender: BOB.into() },
+            )
+            .execute_returns_encoded(U256::from(40));
+    });
+}
+
+fn sign_permit(
+    secret_key: &SecretKey,
+    spender: H160,
+    value: U256,
+    nonce: U256,
+    deadline: U256,
+) -> (H160, u8, H256, H256) {
+    let owner: H160 = AccountId::from(PublicKey::from_secret_key(secret_key)).into();
+    let permit = Eip2612::<Runtime>::generate_permit(
+        asset_address(),
+        ASSET_ID,
+        owner,
+        spender,
+        value,
+        nonce,
+        deadline,
+    );
+
+    let (signature, recovery_id) = sign(&Message::parse(&permit), secret_key);
+    let signature = signature.serialize();
+
+    (
+        owner,
+        recovery_id.serialize() + 27,
+        H256::from_slice(&signature[..32]),
+        H256::from_slice(&signature[32..]),
+    )
+}
+
+#[test]
+fn permit() {
+    build().execute_with(|| {
+        create_asset();
+        pallet_timestamp::Pallet::<Runtime>::set_timestamp(10_000);
+
+        let secret_key = SecretKey::parse(&[0x42; 32]).unwrap();
+        let (owner, v, r, s) = sign_permit(&secret_key, BOB, 500.into(), 0.into(), 20.into());
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::nonces { owner: owner.into() })
+            .execute_returns_encoded(U256::zero());
+
+        precompiles()
+            .prepare_test(
+                CHARLIE,
+                asset_address(),
+                PCall::permit {
+                    owner: owner.into(),
+                    spender: BOB.into(),
+                    value: 500.into(),
+                    deadline: 20.into(),
+                    v,
+                    r,
+                    s,
+                },
+            )
+            .expect_log(
+                Approval { owner: owner.into(), spender: BOB.into(), value: 500.into() }
+                    .log(asset_address()),
+            )
+            .execute_returns(vec![]);
+
+        precompiles()
+            .prepare_test(
+                ALICE,
+                asset_address(),
+                PCall::allowance { owner: owner.into(), spender: BOB.into() },
+            )
+            .execute_returns_encoded(U256::from(500));
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::nonces { owner: owner.into() })
+            .execute_returns_encoded(U256::one());
+
+        // The same permit can't be used twice.
+        precompiles()
+            .prepare_test(
+                CHARLIE,
+                asset_address(),
+                PCall::permit {
+                    owner: owner.into(),
+                    spender: BOB.into(),
+                    value: 500.into(),
+                    deadline: 20.into(),
+                    v,
+                    r,
+                    s,
+                },
+            )
+            .execute_reverts(|output| output == b"Invalid permit");
+    });
+}
+
+#[test]
+fn permit_expired() {
+    build().execute_with(|| {
+        create_asset();
+        pallet_timestamp::Pallet::<Runtime>::set_timestamp(30_000);
+
+        let secret_key = SecretKey::parse(&[0x42; 32]).unwrap();
+        let (owner, v, r, s) = sign_permit(&secret_key, BOB, 500.into(), 0.into(), 20.into());
+
+        precompiles()
+            .prepare_test(
+                CHARLIE,
+                asset_address(),
+                PCall::permit {
+                    owner: owner.into(),
+                    spender: BOB.into(),
+                    value: 500.into(),
+                    deadline: 20.into(),
+                    v,
+                    r,
+                    s,
+                },
+            )
+            .execute_reverts(|output| output == b"Permit expired");
+    });
+}
+
+#[test]
+fn permit_invalid_signer() {
+    build().execute_with(|| {
+        create_asset();
+
+        let secret_key = SecretKey::parse(&[0x42; 32]).unwrap();
+        let (_, v, r, s) = sign_permit(&secret_key, BOB, 500.into(), 0.into(), 20.into());
+
+        precompiles()
+            .prepare_test(
+                CHARLIE,
+                asset_address(),
+                PCall::permit {
+                    owner: ALICE.into(),
+                    spender: BOB.into(),
+                    value: 500.into(),
+                    deadline: 20.into(),
+                    v,
+                    r,
+                    s,
+                },
+            )
+            .execute_reverts(|output| output == b"Invalid permit");
+    });
+}
+
+#[test]
+fn domain_separator() {
+    build().execute_with(|| {
+        create_asset();
+
+        precompiles()
+            .prepare_test(ALICE, asset_address(), PCall::domain_separator {})
+            .execute_returns_encoded(H256::from(Eip2612::<Runtime>::compute_domain_separator(
+                asset_address(),
+                ASSET_ID,
+            )));
+    });
+}
+
+#[test]
+fn test_solidity_interface_has_all_function_selectors_documented() {
+    for file in ["ERC20.sol", "Permit.sol"] {
+        for solidity_fn in solidity::get_selectors(file) {
+            assert_eq!(
+                solidity_fn.compute_selector_hex(),
+                solidity_fn.docs_selector,
+                "documented selector for '{}' did not match for file '{}'",
+                solidity_fn.signature(),
+                file,
+            );
+
+            let selector = solidity_fn.compute_selector();
+            if !PCall::supports_selector(selector) {
+                panic!(
+                    "failed decoding selector 0x{:x} => '{}' as Action for file '{}'",
+                    selector,
+                    solidity_fn.signature(),
+                    file,
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use case::CaseExt;
+use syn::{Data, DataEnum, DataStruct, DeriveInput, Fields};
+
+/// Maximum amount of fields of a struct, as structs are encoded using the `EvmData`
+/// implementation of tuples.
+const MAX_FIELDS: usize = 18;
+
+pub fn main(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let output = match &input.data {
+        Data::Struct(data) => expand_struct(&input, data),
+        Data::Enum(data) => expand_enum(&input, data),
+        Data::Union(_) => {
+            Err(syn::Error::new(input.ident.span(), "EvmData cannot be derived for unions"))
+        },
+    };
+
+    output.unwrap_or_else(|e| e.into_compile_error()).into()
+}
+
+/// Structs are encoded as Solidity tuples, with fields in declaration order.
+fn expand_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &data.fields {
+        Fields::Named(fields) => &fields.named,
+        _ => {
+            let msg = "EvmData can only be derived for structs with named fields";
+            return Err(syn::Error::new(input.ident.span(), msg))
+        },
+    };
+
+    if fields.is_empty() || fields.len() > MAX_FIELDS {
+        let msg =
+            format!("EvmData can only be derived for structs with 1 to {} fields", MAX_FIELDS);
+        return Err(syn::Error::new(input.ident.span(), msg))
+    }
+
+    let ident = &input.ident;
+    let idents: Vec<_> = fields.iter().filter_map(|f| f.ident.as_ref()).collect();
+    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let names: Vec<_> = idents.iter().map(|i| i.to_string().to_camel_lowercase()).collect();
+
+    let mut generics = input.generics.clone();
+    {
+        let where_clause = generics.make_where_clause();
+        for ty in &types {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#ty: ::precompile_utils::data::EvmData));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote!(
+        impl #impl_generics ::precompile_utils::data::EvmData for #ident #ty_generics
+        #where_clause
+        {
+            fn read(
+                reader: &mut ::precompile_utils::data::EvmDataReader
+            ) -> ::precompile_utils::revert::MayRevert<Self> {
+                use ::precompile_utils::revert::BacktraceExt as _;
+                let (#(#idents,)*): (#(#types,)*) = reader
+                    .read()
+                    .map_in_tuple_to_field(&[#(#names),*])?;
+                Ok(Self { #(#idents),* })
+            }
+
+            fn write(writer: &mut ::precompile_utils::data::EvmDataWriter, value: Self) {
+                ::precompile_utils::data::EvmData::write(writer, (#(value.#idents,)*));
+            }
+
+            fn has_static_size() -> bool {
+                <(#(#types,)*) as ::precompile_utils::data::EvmData>::has_static_size()
+            }
+
+            fn solidity_type() -> ::precompile_utils::data::String {
+                <(#(#types,)*) as ::precompile_utils::data::EvmData>::solidity_type()
+            }
+        }
+    ))
+}
+
+/// Enums are encoded as Solidity enums (`uint8`), and thus must be `#[repr(u8)]` with only
+/// unit variants.
+fn expand_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<proc_macro2::TokenStream> {
+    let is_repr_u8 = input.attrs.iter().any(|attr| {
+        attr.path.is_ident("repr") && attr.parse_args::<Ident>().map_or(false, |repr| repr == "u8")
+    });
+
+    if !is_repr_u8 {
+        let msg = "EvmData can only be derived for enums with `#[repr(u8)]`";
+        return Err(syn::Error::new(input.ident.span(), msg))
+    }
+
+    if let Some(variant) = data.variants.iter().find(|v| !matches!(v.fields, Fields::Unit)) {
+        let msg = "EvmData can only be derived for enums with unit variants";
+        return Err(syn::Error::new(variant.ident.span(), msg))
+    }
+
+    let ident = &input.ident;
+    let variants: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let invalid_msg = format!("Value is not a valid {} variant", ident);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote!(
+        impl #impl_generics ::precompile_utils::data::EvmData for #ident #ty_generics
+        #where_clause
+        {
+            fn read(
+                reader: &mut ::precompile_utils::data::EvmDataReader
+            ) -> ::precompile_utils::revert::MayRevert<Self> {
+                use ::precompile_utils::revert::RevertReason;
+
+                let value: u8 = reader.read()?;
+                match value {
+                    #(
+                        v if v == Self::#variants as u8 => Ok(Self::#variants),
+                    )*
+                    _ => Err(RevertReason::custom(#invalid_msg).into()),
+                }
+            }
+
+            fn write(writer: &mut ::precompile_utils::data::EvmDataWriter, value: Self) {
+                ::precompile_utils::data::EvmData::write(writer, value as u8);
+            }
+
+            fn has_static_size() -> bool {
+                true
+            }
+
+            fn solidity_type() -> ::precompile_utils::data::String {
+                <u8 as ::precompile_utils::data::EvmData>::solidity_type()
+            }
+        }
+    ))
+}
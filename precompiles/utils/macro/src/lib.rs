@@ -25,6 +25,7 @@ use syn::{
     parse_macro_input, spanned::Spanned, Attribute, Expr, ExprLit, Ident, ItemEnum, Lit, LitStr,
 };
 
+mod derive_evm_data;
 mod generate_function_selector;
 mod precompile;
 
@@ -59,6 +60,18 @@ pub fn keccak256(input: TokenStream) -> TokenStream {
     quote!(#eval_ts).into()
 }
 
+/// Convert a Rust `snake_case` identifier to the `camelCase` string literal naming it in
+/// Solidity, as done by the `precompile` and `EvmData` macros.
+#[doc(hidden)]
+#[proc_macro]
+pub fn lower_camel_case(input: TokenStream) -> TokenStream {
+    use case::CaseExt;
+
+    let ident = parse_macro_input!(input as Ident);
+    let name = LitStr::new(&ident.to_string().to_camel_lowercase(), ident.span());
+    quote!(#name).into()
+}
+
 /// This macro allows to associate to each variant of an enumeration a discriminant (of type u32
 /// whose value corresponds to the first 4 bytes of the Hash Keccak256 of the character string
 ///indicated by the user of this macro.
@@ -91,3 +104,30 @@ pub fn generate_function_selector(attr: TokenStream, input: TokenStream) -> Toke
 pub fn precompile(attr: TokenStream, input: TokenStream) -> TokenStream {
     precompile::main(attr, input)
 }
+
+/// Derive `EvmData` for Solidity structs and enums.
+///
+/// Structs with named fields are encoded as tuples, with fields in declaration order, and their
+/// `solidity_type()` is the tuple signature (ex: `(uint8,bytes[])`). Field names are converted
+/// to `camelCase` in revert backtraces.
+///
+/// Enums must be `#[repr(u8)]` with only unit variants, and are encoded as `uint8`.
+///
+/// ```ignore
+/// #[derive(EvmData)]
+/// struct MultiLocation {
+/// 	parents: u8,
+/// 	interior: Vec<UnboundedBytes>,
+/// }
+///
+/// #[derive(EvmData, Clone, Copy)]
+/// #[repr(u8)]
+/// enum Status {
+/// 	Pending,
+/// 	Executed,
+/// }
+/// ```
+#[proc_macro_derive(EvmData)]
+pub fn derive_evm_data(input: TokenStream) -> TokenStream {
+    derive_evm_data::main(input)
+}
@@ -49,6 +49,7 @@ pub mod keyword {
     syn::custom_keyword!(precompile_set);
     syn::custom_keyword!(test_concrete_types);
     syn::custom_keyword!(pre_check);
+    syn::custom_keyword!(solidity_interface);
 }
 
 /// Attributes for methods.
@@ -99,6 +100,7 @@ impl syn::parse::Parse for MethodAttr {
 pub enum ImplAttr {
     PrecompileSet(Span),
     TestConcreteTypes(Span, Vec<syn::Type>),
+    SolidityInterface(Span, syn::Ident),
 }
 
 impl syn::parse::Parse for ImplAttr {
@@ -121,6 +123,14 @@ impl syn::parse::Parse for ImplAttr {
             let types = inner.parse_terminated::<_, syn::Token![,]>(syn::Type::parse)?;
 
             Ok(ImplAttr::TestConcreteTypes(span, types.into_iter().collect()))
+        } else if lookahead.peek(keyword::solidity_interface) {
+            let span = content.parse::<keyword::solidity_interface>()?.span();
+
+            let inner;
+            syn::parenthesized!(inner in content);
+            let name = inner.parse::<syn::Ident>()?;
+
+            Ok(ImplAttr::SolidityInterface(span, name))
         } else {
             Err(lookahead.error())
         }
@@ -168,8 +168,15 @@ impl Precompile {
         let variants_encode: Vec<_> =
             self.variants_content.values().map(Self::expand_variant_encoding).collect();
 
+        let (signatures_selectors, signatures): (Vec<_>, Vec<_>) = self
+            .variants_content
+            .values()
+            .flat_map(|variant| variant.selectors.iter().zip(variant.selectors_signatures.iter()))
+            .unzip();
+
         let parse_call_data_fn = self.expand_enum_parse_call_data();
         let execute_fn = self.expand_enum_execute_fn();
+        let solidity_interface_fn = self.expand_solidity_interface_fn();
 
         quote!(
             impl #impl_generics #enum_ident #ty_generics #where_clause {
@@ -179,6 +186,8 @@ impl Precompile {
 
                 #execute_fn
 
+                #solidity_interface_fn
+
                 pub fn supports_selector(selector: u32) -> bool {
                     match selector {
                         #(
@@ -194,6 +203,15 @@ impl Precompile {
                     ),*]
                 }
 
+                pub fn selector_signature(selector: u32) -> Option<&'static str> {
+                    match selector {
+                        #(
+                            #signatures_selectors => Some(#signatures),
+                        )*
+                        _ => None,
+                    }
+                }
+
                 #(
                     pub fn #variants_selectors_fn() -> &'static [u32] {
                         &[#(
@@ -290,6 +308,73 @@ impl Precompile {
         )
     }
 
+    /// Expand the function building the Solidity interface of the precompile, if requested
+    /// with the `solidity_interface` attribute.
+    fn expand_solidity_interface_fn(&self) -> impl ToTokens {
+        use case::CaseExt;
+
+        let interface_name = match &self.solidity_interface {
+            Some(name) => name.to_string(),
+            None => return quote!().to_token_stream(),
+        };
+
+        let functions = self.variants_order.iter().flat_map(|ident| {
+            let variant = &self.variants_content[ident];
+            let modifier = match variant.modifier {
+                Modifier::NonPayable => format_ident!("NonPayable"),
+                Modifier::Payable => format_ident!("Payable"),
+                Modifier::View => format_ident!("View"),
+            };
+
+            let inputs: Vec<_> = variant
+                .arguments
+                .iter()
+                .map(|arg| {
+                    let name = arg.ident.to_string().to_camel_lowercase();
+                    let ty = &arg.ty;
+                    quote_spanned!(ty.span()=>
+                        .input(#name, <#ty as EvmData>::solidity_type())
+                    )
+                })
+                .collect();
+
+            let output = variant
+                .output_type
+                .as_ref()
+                .map(|ty| quote_spanned!(ty.span()=> .output(<#ty as EvmData>::solidity_type())));
+
+            variant.selectors.iter().zip(variant.selectors_signatures.iter()).map(
+                move |(selector, signature)| {
+                    quote!(
+                        .function(
+                            FunctionDefinition::new(
+                                #signature,
+                                #selector,
+                                FunctionModifier::#modifier,
+                            )
+                            #(#inputs)*
+                            #output
+                        )
+                    )
+                },
+            )
+        });
+
+        quote!(
+            pub fn solidity_interface() -> ::precompile_utils::codegen::InterfaceDefinition {
+                use ::precompile_utils::{
+                    codegen::{FunctionDefinition, InterfaceDefinition},
+                    data::EvmData,
+                    modifier::FunctionModifier,
+                };
+
+                InterfaceDefinition::new(#interface_name)
+                #(#functions)*
+            }
+        )
+        .to_token_stream()
+    }
+
     /// Expand how a variant can be Solidity encoded.
     fn expand_variant_encoding(variant: &Variant) -> impl ToTokens {
         match variant.selectors.first() {
@@ -387,6 +472,11 @@ impl Precompile {
 
                         #opt_pre_check
 
+                        ::precompile_utils::trace_function(
+                            handle.input(),
+                            <#enum_ident #ty_generics>::selector_signature,
+                        );
+
                         Some(
                             <#enum_ident #ty_generics>::parse_call_data(handle)
                                 .and_then(|call| call.execute(discriminant, handle))
@@ -412,6 +502,11 @@ impl Precompile {
                     ) -> ::precompile_utils::EvmResult<::fp_evm::PrecompileOutput> {
                         #opt_pre_check
 
+                        ::precompile_utils::trace_function(
+                            handle.input(),
+                            <#enum_ident #ty_generics>::selector_signature,
+                        );
+
                         <#enum_ident #ty_generics>::parse_call_data(handle)?.execute(handle)
                     }
                 }
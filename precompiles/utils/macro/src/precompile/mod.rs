@@ -70,6 +70,9 @@ struct Precompile {
     /// Describes the content of each variant based on the precompile methods.
     variants_content: BTreeMap<syn::Ident, Variant>,
 
+    /// Idents of the variants, in declaration order of the methods.
+    variants_order: Vec<syn::Ident>,
+
     /// Since being a precompile set implies lots of changes, we must know it early
     /// in the form of an attribute on the impl block itself.
     tagged_as_precompile_set: bool,
@@ -87,6 +90,9 @@ struct Precompile {
     /// Ident of a function that performs a check before the call is dispatched to the proper
     /// function.
     pre_check: Option<syn::Ident>,
+
+    /// Name of the Solidity interface to generate from the public methods, if any.
+    solidity_interface: Option<syn::Ident>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -119,6 +125,13 @@ struct Variant {
     /// Empty if it only the fallback function.
     selectors: Vec<u32>,
 
+    /// Solidity signature of each selector, in the same order as `selectors`.
+    selectors_signatures: Vec<String>,
+
+    /// Type `T` of the `EvmResult<T>` output. Only extracted when a Solidity interface
+    /// must be generated.
+    output_type: Option<syn::Type>,
+
     /// Output of the variant fn (for better error messages).
     fn_output: syn::Type,
 }
@@ -31,12 +31,14 @@ impl Precompile {
             generics: impl_.generics.clone(),
             selector_to_variant: BTreeMap::new(),
             variants_content: BTreeMap::new(),
+            variants_order: vec![],
             fallback_to_variant: None,
             tagged_as_precompile_set: false,
             precompile_set_discriminant_fn: None,
             precompile_set_discriminant_type: None,
             test_concrete_types: None,
             pre_check: None,
+            solidity_interface: None,
         };
 
         precompile.process_impl_attr(impl_)?;
@@ -83,6 +85,14 @@ impl Precompile {
 
                     self.test_concrete_types = Some(types);
                 },
+                attr::ImplAttr::SolidityInterface(span, name) => {
+                    if self.solidity_interface.is_some() {
+                        let msg = "Only one Solidity interface can be generated";
+                        return Err(syn::Error::new(span, msg))
+                    }
+
+                    self.solidity_interface = Some(name);
+                },
             }
         }
 
@@ -129,6 +139,7 @@ impl Precompile {
         let mut arguments = vec![];
         let mut is_fallback = false;
         let mut selectors = vec![];
+        let mut selectors_signatures = vec![];
         let initial_arguments = if self.tagged_as_precompile_set { 2 } else { 1 };
 
         // We first look for unique attributes.
@@ -200,12 +211,13 @@ impl Precompile {
                 attr::MethodAttr::Public(_, signature_lit) => {
                     used = true;
 
-                    let selector = self.parse_public_attr(
+                    let (selector, signature) = self.parse_public_attr(
                         signature_lit,
                         &method_name,
                         &mut solidity_arguments_type,
                     )?;
                     selectors.push(selector);
+                    selectors_signatures.push(signature);
                 },
             }
         }
@@ -279,7 +291,7 @@ impl Precompile {
 
         // Function output.
         let output_type = match &method.sig.output {
-            syn::ReturnType::Type(_, t) => t,
+            syn::ReturnType::Type(_, t) => t.as_ref(),
             _ => {
                 let msg = "A precompile method must have a return type of `EvmResult<_>` (exposed \
 				by `precompile_utils`)";
@@ -287,6 +299,14 @@ impl Precompile {
             },
         };
 
+        let method_output = output_type.clone();
+
+        // The Solidity interface needs the output type to compute its Solidity type.
+        let output_type = match self.solidity_interface {
+            Some(_) => Some(extract_evm_result_output(output_type)?),
+            None => None,
+        };
+
         // We insert the collected data in self.
         if let Some(_) = self.variants_content.insert(
             method_name.clone(),
@@ -295,12 +315,15 @@ impl Precompile {
                 solidity_arguments_type: solidity_arguments_type.unwrap_or(String::from("()")),
                 modifier,
                 selectors,
-                fn_output: output_type.as_ref().clone(),
+                selectors_signatures,
+                output_type,
+                fn_output: method_output,
             },
         ) {
             let msg = "Duplicate method name";
             return Err(syn::Error::new(method_name.span(), msg))
         }
+        self.variants_order.push(method_name.clone());
 
         Ok(())
     }
@@ -498,7 +521,7 @@ impl Precompile {
         signature_lit: syn::LitStr,
         method_name: &syn::Ident,
         solidity_arguments_type: &mut Option<String>,
-    ) -> syn::Result<u32> {
+    ) -> syn::Result<(u32, String)> {
         let signature = signature_lit.value();
         // Split signature to get arguments type.
         let split: Vec<_> = signature.splitn(2, "(").collect();
@@ -529,7 +552,34 @@ impl Precompile {
             return Err(syn::Error::new(signature_lit.span(), msg))
         }
 
-        Ok(selector)
+        Ok((selector, signature))
+    }
+}
+
+/// Extract `T` from an output type written as `EvmResult<T>`, `EvmResult` or `Result<T, _>`.
+fn extract_evm_result_output(output_type: &syn::Type) -> syn::Result<syn::Type> {
+    let msg = "Methods of a precompile generating a Solidity interface must return \
+	`EvmResult<T>` or `Result<T, PrecompileFailure>` (no other type alias)";
+
+    let segment = match output_type {
+        syn::Type::Path(p) if p.qself.is_none() => p.path.segments.last(),
+        _ => None,
+    }
+    .ok_or_else(|| syn::Error::new(output_type.span(), msg))?;
+
+    let first_argument = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(t)) => Some(t.clone()),
+            _ => return Err(syn::Error::new(args.span(), msg)),
+        },
+        syn::PathArguments::None => None,
+        _ => return Err(syn::Error::new(segment.span(), msg)),
+    };
+
+    match (segment.ident.to_string().as_str(), first_argument) {
+        ("EvmResult", Some(t)) | ("Result", Some(t)) => Ok(t),
+        ("EvmResult", None) => Ok(syn::parse_quote!(())),
+        _ => Err(syn::Error::new(segment.span(), msg)),
     }
 }
 
@@ -0,0 +1,120 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+use precompile_utils::{
+    data::{Address, EvmData, EvmDataReader, EvmDataWriter, UnboundedBytes},
+    revert::{InjectBacktrace, Revert, RevertReason},
+};
+use sp_core::{H160, U256};
+
+#[derive(precompile_utils_macro::EvmData, Clone, Debug, PartialEq, Eq)]
+struct MultiLocation {
+    parents: u8,
+    interior: Vec<UnboundedBytes>,
+}
+
+#[derive(precompile_utils_macro::EvmData, Clone, Debug, PartialEq, Eq)]
+struct Transfer {
+    recipient: Address,
+    fixed_amounts: [U256; 2],
+    destination: MultiLocation,
+    status: Status,
+}
+
+#[derive(precompile_utils_macro::EvmData, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+enum Status {
+    Pending,
+    Executed = 3,
+}
+
+#[test]
+fn derived_solidity_types() {
+    assert_eq!(MultiLocation::solidity_type(), "(uint8,bytes[])");
+    assert_eq!(Status::solidity_type(), "uint8");
+    assert_eq!(Transfer::solidity_type(), "(address,uint256[2],(uint8,bytes[]),uint8)");
+
+    assert!(!MultiLocation::has_static_size());
+    assert!(Status::has_static_size());
+    assert!(!Transfer::has_static_size());
+}
+
+#[test]
+fn derived_struct_matches_tuple_encoding() {
+    let location = MultiLocation { parents: 1, interior: vec![UnboundedBytes::from(vec![0x01])] };
+
+    let output = EvmDataWriter::new().write(location.clone()).build();
+    let expected = EvmDataWriter::new()
+        .write((1u8, vec![UnboundedBytes::from(vec![0x01])]))
+        .build();
+    assert_eq!(output, expected);
+
+    let mut reader = EvmDataReader::new(&output);
+    assert_eq!(reader.read::<MultiLocation>().unwrap(), location);
+}
+
+#[test]
+fn derived_nested_struct_roundtrip() {
+    let transfer = Transfer {
+        recipient: Address(H160::repeat_byte(0x11)),
+        fixed_amounts: [U256::from(1u8), U256::from(2u8)],
+        destination: MultiLocation {
+            parents: 1,
+            interior: vec![UnboundedBytes::from(vec![0x01]), UnboundedBytes::from(vec![0x02])],
+        },
+        status: Status::Executed,
+    };
+
+    let output = EvmDataWriter::new().write(transfer.clone()).build();
+
+    let mut reader = EvmDataReader::new(&output);
+    assert_eq!(reader.read::<Transfer>().unwrap(), transfer);
+}
+
+#[test]
+fn derived_struct_backtrace_uses_field_names() {
+    let output = EvmDataWriter::new()
+        .write((
+            Address(H160::zero()),
+            [U256::zero(), U256::zero()],
+            (1u8, Vec::<UnboundedBytes>::new()),
+            42u8,
+        ))
+        .build();
+
+    let mut reader = EvmDataReader::new(&output);
+    assert_eq!(
+        reader.read::<Transfer>(),
+        Err(Revert::new(RevertReason::custom("Value is not a valid Status variant"))
+            .in_field("status"))
+    );
+}
+
+#[test]
+fn derived_enum_encoding() {
+    let output = EvmDataWriter::new().write(Status::Executed).build();
+    assert_eq!(output, EvmDataWriter::new().write(3u8).build());
+
+    let mut reader = EvmDataReader::new(&output);
+    assert_eq!(reader.read::<Status>().unwrap(), Status::Executed);
+
+    let output = EvmDataWriter::new().write(1u8).build();
+    let mut reader = EvmDataReader::new(&output);
+    assert_eq!(
+        reader.read::<Status>(),
+        Err(RevertReason::custom("Value is not a valid Status variant").into())
+    );
+}
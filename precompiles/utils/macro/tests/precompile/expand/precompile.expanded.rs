@@ -257,6 +257,14 @@ where
     pub fn selectors() -> &'static [u32] {
         &[2044677020u32, 2531431096u32, 3473183175u32]
     }
+    pub fn selector_signature(selector: u32) -> Option<&'static str> {
+        match selector {
+            2531431096u32 => Some("batchAll(address[],uint256[],bytes[],uint64[])"),
+            2044677020u32 => Some("batchSome(address[],uint256[],bytes[],uint64[])"),
+            3473183175u32 => Some("batchSomeUntilFailure(address[],uint256[],bytes[],uint64[])"),
+            _ => None,
+        }
+    }
     pub fn batch_all_selectors() -> &'static [u32] {
         &[2531431096u32]
     }
@@ -324,6 +332,10 @@ where
         handle: &mut impl PrecompileHandle,
     ) -> ::precompile_utils::EvmResult<::fp_evm::PrecompileOutput> {
         let _: () = <BatchPrecompile<Runtime>>::pre_check(handle)?;
+        ::precompile_utils::trace_function(
+            handle.input(),
+            <BatchPrecompileCall<Runtime>>::selector_signature,
+        );
         <BatchPrecompileCall<Runtime>>::parse_call_data(handle)?.execute(handle)
     }
 }
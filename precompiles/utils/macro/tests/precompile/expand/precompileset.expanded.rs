@@ -1013,6 +1013,39 @@ where
             4173303445u32,
         ]
     }
+    pub fn selector_signature(selector: u32) -> Option<&'static str> {
+        match selector {
+            3714247998u32 => Some("allowance(address,address)"),
+            157198259u32 => Some("approve(address,uint256)"),
+            1889567281u32 => Some("balanceOf(address)"),
+            2646777772u32 => Some("burn(address,uint256)"),
+            4021736498u32 => Some("clearMetadata()"),
+            3552201630u32 => Some("clear_metadata()"),
+            826074471u32 => Some("decimals()"),
+            910484757u32 => Some("DOMAIN_SEPARATOR()"),
+            2127478272u32 => Some("nonces(address)"),
+            3573918927u32 => Some("permit(address,address,uint256,uint256,uint8,bytes32,bytes32)"),
+            2367676207u32 => Some("freeze(address)"),
+            3566436177u32 => Some("freezeAsset()"),
+            1804030401u32 => Some("freeze_asset()"),
+            1086394137u32 => Some("mint(address,uint256)"),
+            117300739u32 => Some("name()"),
+            936559348u32 => Some("setMetadata(string,string,uint8)"),
+            3999121892u32 => Some("set_metadata(string,string,uint8)"),
+            3352902745u32 => Some("setTeam(address,address,address)"),
+            4173303445u32 => Some("set_team(address,address,address)"),
+            2514000705u32 => Some("symbol()"),
+            1587675670u32 => Some("thaw(address)"),
+            1374431959u32 => Some("thawAsset()"),
+            484305945u32 => Some("thaw_asset()"),
+            404098525u32 => Some("totalSupply()"),
+            2835717307u32 => Some("transfer(address,uint256)"),
+            599290589u32 => Some("transferFrom(address,address,uint256)"),
+            4076725131u32 => Some("transferOwnership(address)"),
+            4030008324u32 => Some("transfer_ownership(address)"),
+            _ => None,
+        }
+    }
     pub fn allowance_selectors() -> &'static [u32] {
         &[3714247998u32]
     }
@@ -1210,6 +1243,10 @@ where
             Some(d) => d,
             None => return None,
         };
+        ::precompile_utils::trace_function(
+            handle.input(),
+            <PrecompileSetCall<Runtime>>::selector_signature,
+        );
         Some(
             <PrecompileSetCall<Runtime>>::parse_call_data(handle)
                 .and_then(|call| call.execute(discriminant, handle)),
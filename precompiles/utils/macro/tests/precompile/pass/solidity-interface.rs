@@ -0,0 +1,64 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+use {
+	core::marker::PhantomData,
+	precompile_utils::{EvmResult, prelude::*, modifier::FunctionModifier},
+	sp_core::U256,
+};
+
+pub struct Precompile<R>(PhantomData<R>);
+
+#[precompile_utils_macro::precompile]
+#[precompile::solidity_interface(ITest)]
+#[precompile::test_concrete_types(())]
+impl<R> Precompile<R> {
+	#[precompile::public("balanceOf(address)")]
+	#[precompile::public("balance_of(address)")]
+	#[precompile::view]
+	fn balance_of(_: &mut impl PrecompileHandle, owner: Address) -> EvmResult<U256> {
+		todo!()
+	}
+
+	#[precompile::public("transfer(address[],uint256)")]
+	#[precompile::payable]
+	fn transfer(
+		_: &mut impl PrecompileHandle,
+		recipients: Vec<Address>,
+		amount_per_recipient: U256,
+	) -> EvmResult {
+		todo!()
+	}
+}
+
+fn main() {
+	let interface = PrecompileCall::<()>::solidity_interface();
+	assert_eq!(interface.name, "ITest");
+	assert_eq!(
+		interface.functions.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+		["balanceOf", "balance_of", "transfer"],
+	);
+
+	let transfer = interface.functions.iter().find(|f| f.name == "transfer").unwrap();
+	assert_eq!(transfer.signature, "transfer(address[],uint256)");
+	assert_eq!(transfer.inputs[1].name, "amountPerRecipient");
+	assert_eq!(transfer.output, "()");
+	assert_eq!(transfer.modifier, FunctionModifier::Payable);
+
+	for name in ["balanceOf", "balance_of"] {
+		let balance_of = interface.functions.iter().find(|f| f.name == name).unwrap();
+		assert_eq!(balance_of.signature, format!("{}(address)", name));
+		assert_eq!(balance_of.output, "uint256");
+		assert_eq!(balance_of.modifier, FunctionModifier::View);
+	}
+}
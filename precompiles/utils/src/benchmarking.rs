@@ -0,0 +1,95 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers to benchmark precompiles with `frame_benchmarking`.
+//!
+//! Precompiles are not dispatchables, so they can't be benchmarked directly by the
+//! `benchmark pallet` command. Instead a precompile crate declares a pallet-like benchmarking
+//! module (a `Pallet` struct and a `Config` trait implemented by the runtime, like
+//! `frame_system_benchmarking`), whose benchmarks execute one selector each through a
+//! [`MockHandle`]. The measured weights, converted to gas with the runtime `GasWeightMapping`,
+//! are then compared to the gas recorded by the precompile instead of guessing costs.
+
+use crate::{alloc::vec::Vec, testing::MockHandle};
+use fp_evm::{Context, PrecompileSet};
+use frame_benchmarking::BenchmarkError;
+use sp_core::{H160, U256};
+
+/// Call of a precompile set to be executed in the measured block of a benchmark.
+pub struct PrecompileBenchmark<'p, P> {
+    precompiles: &'p P,
+    handle: MockHandle,
+}
+
+impl<'p, P: PrecompileSet> PrecompileBenchmark<'p, P> {
+    pub fn new(
+        precompiles: &'p P,
+        from: impl Into<H160>,
+        to: impl Into<H160>,
+        data: Vec<u8>,
+    ) -> Self {
+        let to = to.into();
+        let mut handle = MockHandle::new(
+            to,
+            Context { address: to, caller: from.into(), apparent_value: U256::zero() },
+        );
+
+        handle.input = data;
+
+        Self { precompiles, handle }
+    }
+
+    pub fn with_value(mut self, value: impl Into<U256>) -> Self {
+        self.handle.context.apparent_value = value.into();
+        self
+    }
+
+    pub fn with_static_call(mut self, static_call: bool) -> Self {
+        self.handle.is_static = static_call;
+        self
+    }
+
+    /// Execute the call, which must succeed as the benchmark would otherwise measure an early
+    /// exit instead of the worst case.
+    /// Returns the gas recorded by the precompile.
+    pub fn execute(mut self) -> Result<u64, BenchmarkError> {
+        match self.precompiles.execute(&mut self.handle) {
+            Some(Ok(_)) => Ok(self.handle.gas_used),
+            Some(Err(_)) => Err("Benchmarked precompile call failed".into()),
+            None => Err("No precompile at the benchmarked address".into()),
+        }
+    }
+}
+
+pub trait PrecompileBenchmarkExt: PrecompileSet + Sized {
+    fn prepare_benchmark(
+        &self,
+        from: impl Into<H160>,
+        to: impl Into<H160>,
+        data: impl Into<Vec<u8>>,
+    ) -> PrecompileBenchmark<Self>;
+}
+
+impl<T: PrecompileSet> PrecompileBenchmarkExt for T {
+    fn prepare_benchmark(
+        &self,
+        from: impl Into<H160>,
+        to: impl Into<H160>,
+        data: impl Into<Vec<u8>>,
+    ) -> PrecompileBenchmark<Self> {
+        PrecompileBenchmark::new(self, from, to, data.into())
+    }
+}
@@ -0,0 +1,631 @@
+// Copyright 2019-2022 PureStake Inc.
+// This file is part of Moonbeam.
+
+// Moonbeam is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Moonbeam is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generation of Solidity interfaces and ABI JSON from `#[precompile]` definitions.
+//!
+//! The `#[precompile::solidity_interface(Name)]` attribute makes the macro generate a
+//! `solidity_interface()` function on the call enum, which returns an [`InterfaceDefinition`]
+//! built from the `public` signatures and the `EvmData::solidity_type()` of each argument and
+//! output.
+
+use crate::modifier::FunctionModifier;
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Write;
+
+/// Solidity interface of a precompile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterfaceDefinition {
+    /// Name of the Solidity interface.
+    pub name: String,
+    /// Functions of the interface, in declaration order of the methods of the precompile.
+    pub functions: Vec<FunctionDefinition>,
+    /// Events emitted by the precompile.
+    pub events: Vec<EventDefinition>,
+    /// Custom errors the precompile can revert with.
+    pub errors: Vec<ErrorDefinition>,
+}
+
+/// Solidity function of a precompile interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionDefinition {
+    /// Name of the function.
+    pub name: String,
+    /// Signature of the function, as written in its `#[precompile::public]` attribute.
+    pub signature: String,
+    /// 4-bytes selector of the function, computed from its signature.
+    pub selector: u32,
+    /// Parameters of the function.
+    pub inputs: Vec<ParameterDefinition>,
+    /// Solidity type of the output, `()` if the function doesn't return anything.
+    pub output: String,
+    /// State mutability of the function.
+    pub modifier: FunctionModifier,
+}
+
+/// Named parameter of a Solidity function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParameterDefinition {
+    /// Name of the parameter (camelCase).
+    pub name: String,
+    /// Solidity type of the parameter, as returned by `EvmData::solidity_type()`.
+    pub solidity_type: String,
+}
+
+/// Solidity event, as declared with the `event!` macro.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventDefinition {
+    /// Name of the event.
+    pub name: String,
+    /// Fields of the event, with a flag telling if they are indexed.
+    pub inputs: Vec<(ParameterDefinition, bool)>,
+}
+
+/// Solidity custom error, as declared with the `custom_error!` macro.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorDefinition {
+    /// Name of the error.
+    pub name: String,
+    /// Arguments of the error.
+    pub inputs: Vec<ParameterDefinition>,
+}
+
+impl InterfaceDefinition {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), functions: Vec::new(), events: Vec::new(), errors: Vec::new() }
+    }
+
+    /// Add a function to the interface.
+    pub fn function(mut self, function: FunctionDefinition) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    /// Add an event to the interface.
+    pub fn event(mut self, event: EventDefinition) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Add a custom error to the interface.
+    pub fn error(mut self, error: ErrorDefinition) -> Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Generate the Solidity source of the interface.
+    /// Tuples are declared as structs inside the interface, as their names are not known from
+    /// the Rust types.
+    pub fn to_solidity(&self) -> String {
+        let structs = self.collect_structs();
+
+        let mut output = String::new();
+        output.push_str("// SPDX-License-Identifier: GPL-3.0-only\n");
+        output.push_str("pragma solidity >=0.8.3;\n\n");
+        let _ = writeln!(output, "/// @title {}", self.name);
+        output.push_str("/// @dev Generated from the `#[precompile]` definition.\n");
+        let _ = writeln!(output, "interface {} {{", self.name);
+
+        let mut declarations = Vec::new();
+
+        for (index, (_, components)) in structs.iter().enumerate() {
+            let mut declaration = format!("    struct {} {{\n", struct_name(index));
+            for (field, component) in components.iter().enumerate() {
+                let _ = writeln!(
+                    declaration,
+                    "        {} field{};",
+                    component.solidity_name(&structs),
+                    field
+                );
+            }
+            declaration.push_str("    }");
+            declarations.push(declaration);
+        }
+
+        for event in &self.events {
+            let inputs: Vec<_> = event
+                .inputs
+                .iter()
+                .map(|(input, indexed)| {
+                    let ty = SolidityType::parse(&input.solidity_type).solidity_name(&structs);
+                    let indexed = if *indexed { " indexed" } else { "" };
+                    format!("{}{} {}", ty, indexed, input.name)
+                })
+                .collect();
+
+            declarations.push(format!("    event {}({});", event.name, inputs.join(", ")));
+        }
+
+        for error in &self.errors {
+            let inputs: Vec<_> = error
+                .inputs
+                .iter()
+                .map(|input| {
+                    let ty = SolidityType::parse(&input.solidity_type).solidity_name(&structs);
+                    format!("{} {}", ty, input.name)
+                })
+                .collect();
+
+            declarations.push(format!("    error {}({});", error.name, inputs.join(", ")));
+        }
+
+        for function in &self.functions {
+            let inputs: Vec<_> = function
+                .inputs
+                .iter()
+                .map(|input| {
+                    let ty = SolidityType::parse(&input.solidity_type);
+                    format!("{}{} {}", ty.solidity_name(&structs), ty.data_location(), input.name)
+                })
+                .collect();
+
+            let mutability = match function.modifier {
+                FunctionModifier::View => " view",
+                FunctionModifier::Payable => " payable",
+                FunctionModifier::NonPayable => "",
+            };
+
+            let returns = match function.output_type() {
+                Some(ty) => {
+                    format!(" returns ({}{})", ty.solidity_name(&structs), ty.data_location())
+                },
+                None => String::new(),
+            };
+
+            declarations.push(format!(
+                "    /// @custom:selector {:0>8x}\n    function {}({}) external{}{};",
+                function.selector,
+                function.name,
+                inputs.join(", "),
+                mutability,
+                returns
+            ));
+        }
+
+        if !declarations.is_empty() {
+            output.push_str(&declarations.join("\n\n"));
+            output.push('\n');
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    /// Generate the ABI JSON of the interface.
+    pub fn to_abi_json(&self) -> String {
+        let structs = self.collect_structs();
+
+        let functions = self
+            .functions
+            .iter()
+            .map(|function| {
+                let inputs = function
+                    .inputs
+                    .iter()
+                    .map(|input| {
+                        SolidityType::parse(&input.solidity_type).to_json(
+                            &input.name,
+                            &self.name,
+                            &structs,
+                        )
+                    })
+                    .collect();
+
+                let outputs = function
+                    .output_type()
+                    .map(|ty| ty.to_json("", &self.name, &structs))
+                    .into_iter()
+                    .collect();
+
+                let mutability = match function.modifier {
+                    FunctionModifier::View => "view",
+                    FunctionModifier::Payable => "payable",
+                    FunctionModifier::NonPayable => "nonpayable",
+                };
+
+                Json::Object(vec![
+                    ("inputs", Json::Array(inputs)),
+                    ("name", Json::String(function.name.clone())),
+                    ("outputs", Json::Array(outputs)),
+                    ("stateMutability", Json::String(mutability.to_owned())),
+                    ("type", Json::String("function".to_owned())),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let events = self.events.iter().map(|event| {
+            let inputs = event
+                .inputs
+                .iter()
+                .map(|(input, indexed)| {
+                    let mut json = SolidityType::parse(&input.solidity_type).to_json(
+                        &input.name,
+                        &self.name,
+                        &structs,
+                    );
+                    if let Json::Object(fields) = &mut json {
+                        let position = fields.iter().position(|(key, _)| *key == "internalType");
+                        fields.insert(position.unwrap_or(0), ("indexed", Json::Bool(*indexed)));
+                    }
+                    json
+                })
+                .collect();
+
+            Json::Object(vec![
+                ("anonymous", Json::Bool(false)),
+                ("inputs", Json::Array(inputs)),
+                ("name", Json::String(event.name.clone())),
+                ("type", Json::String("event".to_owned())),
+            ])
+        });
+
+        let errors = self.errors.iter().map(|error| {
+            let inputs = error
+                .inputs
+                .iter()
+                .map(|input| {
+                    SolidityType::parse(&input.solidity_type).to_json(
+                        &input.name,
+                        &self.name,
+                        &structs,
+                    )
+                })
+                .collect();
+
+            Json::Object(vec![
+                ("inputs", Json::Array(inputs)),
+                ("name", Json::String(error.name.clone())),
+                ("type", Json::String("error".to_owned())),
+            ])
+        });
+
+        let items = functions.into_iter().chain(events).chain(errors).collect();
+
+        let mut output = String::new();
+        Json::Array(items).write(&mut output, 0);
+        output.push('\n');
+        output
+    }
+
+    /// Collect all tuples used in the interface, with inner tuples first.
+    /// The index of a tuple in the returned list is used to name its struct.
+    fn collect_structs(&self) -> Vec<(String, Vec<SolidityType>)> {
+        let mut structs = Vec::new();
+
+        for function in &self.functions {
+            for input in &function.inputs {
+                SolidityType::parse(&input.solidity_type).collect_structs(&mut structs);
+            }
+
+            if let Some(output) = function.output_type() {
+                output.collect_structs(&mut structs);
+            }
+        }
+
+        let events_inputs = self.events.iter().flat_map(|e| e.inputs.iter().map(|(i, _)| i));
+        let errors_inputs = self.errors.iter().flat_map(|e| e.inputs.iter());
+        for input in events_inputs.chain(errors_inputs) {
+            SolidityType::parse(&input.solidity_type).collect_structs(&mut structs);
+        }
+
+        structs
+    }
+}
+
+impl FunctionDefinition {
+    /// Function of the given signature, like `transfer(address,uint256)`.
+    pub fn new(signature: impl Into<String>, selector: u32, modifier: FunctionModifier) -> Self {
+        let signature = signature.into();
+        Self {
+            name: signature_name(&signature).to_owned(),
+            signature,
+            selector,
+            inputs: Vec::new(),
+            output: String::from("()"),
+            modifier,
+        }
+    }
+
+    /// Add a parameter to the function.
+    pub fn input(mut self, name: impl Into<String>, solidity_type: impl Into<String>) -> Self {
+        self.inputs
+            .push(ParameterDefinition { name: name.into(), solidity_type: solidity_type.into() });
+        self
+    }
+
+    /// Set the output type of the function.
+    pub fn output(mut self, solidity_type: impl Into<String>) -> Self {
+        self.output = solidity_type.into();
+        self
+    }
+
+    fn output_type(&self) -> Option<SolidityType> {
+        match self.output.as_str() {
+            "()" => None,
+            output => Some(SolidityType::parse(output)),
+        }
+    }
+}
+
+impl EventDefinition {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), inputs: Vec::new() }
+    }
+
+    /// Add a field to the event.
+    pub fn input(
+        mut self,
+        name: impl Into<String>,
+        solidity_type: impl Into<String>,
+        indexed: bool,
+    ) -> Self {
+        let parameter =
+            ParameterDefinition { name: name.into(), solidity_type: solidity_type.into() };
+        self.inputs.push((parameter, indexed));
+        self
+    }
+}
+
+impl ErrorDefinition {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), inputs: Vec::new() }
+    }
+
+    /// Add an argument to the error.
+    pub fn input(mut self, name: impl Into<String>, solidity_type: impl Into<String>) -> Self {
+        self.inputs
+            .push(ParameterDefinition { name: name.into(), solidity_type: solidity_type.into() });
+        self
+    }
+}
+
+/// Name part of a Solidity signature, like `Transfer` for `Transfer(address,address,uint256)`.
+pub fn signature_name(signature: &str) -> &str {
+    signature.split('(').next().unwrap_or(signature)
+}
+
+fn struct_name(index: usize) -> String {
+    format!("Tuple{}", index)
+}
+
+/// Solidity type parsed from the canonical string returned by `EvmData::solidity_type()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SolidityType {
+    Elementary(String),
+    Tuple(Vec<SolidityType>),
+    Array(Box<SolidityType>, Option<usize>),
+}
+
+impl SolidityType {
+    fn parse(input: &str) -> Self {
+        let input = input.trim();
+
+        // Array suffixes are always at the end, and the last one is the outermost array.
+        if input.ends_with(']') {
+            if let Some(open) = input.rfind('[') {
+                let inner = Self::parse(&input[..open]);
+                let size = input[open + 1..input.len() - 1].parse().ok();
+                return SolidityType::Array(Box::new(inner), size)
+            }
+        }
+
+        match input.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            Some(content) => {
+                SolidityType::Tuple(split_top_level(content).into_iter().map(Self::parse).collect())
+            },
+            None => SolidityType::Elementary(input.to_string()),
+        }
+    }
+
+    fn canonical(&self) -> String {
+        match self {
+            SolidityType::Elementary(name) => name.clone(),
+            SolidityType::Tuple(components) => {
+                let components: Vec<_> = components.iter().map(Self::canonical).collect();
+                format!("({})", components.join(","))
+            },
+            SolidityType::Array(inner, size) => {
+                format!("{}{}", inner.canonical(), array_suffix(size))
+            },
+        }
+    }
+
+    fn collect_structs(&self, structs: &mut Vec<(String, Vec<SolidityType>)>) {
+        match self {
+            SolidityType::Elementary(_) => (),
+            SolidityType::Array(inner, _) => inner.collect_structs(structs),
+            SolidityType::Tuple(components) => {
+                for component in components {
+                    component.collect_structs(structs);
+                }
+
+                let canonical = self.canonical();
+                if !structs.iter().any(|(known, _)| known == &canonical) {
+                    structs.push((canonical, components.clone()));
+                }
+            },
+        }
+    }
+
+    fn struct_index(&self, structs: &[(String, Vec<SolidityType>)]) -> usize {
+        let canonical = self.canonical();
+        structs
+            .iter()
+            .position(|(known, _)| known == &canonical)
+            .expect("all tuples are collected before generation")
+    }
+
+    /// Name of the type in Solidity source code.
+    fn solidity_name(&self, structs: &[(String, Vec<SolidityType>)]) -> String {
+        match self {
+            SolidityType::Elementary(name) => name.clone(),
+            SolidityType::Tuple(_) => struct_name(self.struct_index(structs)),
+            SolidityType::Array(inner, size) => {
+                format!("{}{}", inner.solidity_name(structs), array_suffix(size))
+            },
+        }
+    }
+
+    /// Data location required by reference types in function parameters and outputs.
+    fn data_location(&self) -> &'static str {
+        match self {
+            SolidityType::Elementary(name) if name != "bytes" && name != "string" => "",
+            _ => " memory",
+        }
+    }
+
+    /// Type as written in the `type` field of ABI JSON, where tuples are replaced by `tuple`.
+    fn abi_type(&self) -> String {
+        match self {
+            SolidityType::Elementary(name) => name.clone(),
+            SolidityType::Tuple(_) => String::from("tuple"),
+            SolidityType::Array(inner, size) => {
+                format!("{}{}", inner.abi_type(), array_suffix(size))
+            },
+        }
+    }
+
+    /// Components of the tuple, if this type is a tuple or an array of tuples.
+    fn components(&self) -> Option<&Vec<SolidityType>> {
+        match self {
+            SolidityType::Elementary(_) => None,
+            SolidityType::Tuple(components) => Some(components),
+            SolidityType::Array(inner, _) => inner.components(),
+        }
+    }
+
+    fn internal_type(&self, scope: &str, structs: &[(String, Vec<SolidityType>)]) -> String {
+        match self.components() {
+            Some(_) => format!("struct {}.{}", scope, self.solidity_name(structs)),
+            None => self.canonical(),
+        }
+    }
+
+    fn to_json(&self, name: &str, scope: &str, structs: &[(String, Vec<SolidityType>)]) -> Json {
+        let mut fields = Vec::new();
+
+        if let Some(components) = self.components() {
+            let components = components
+                .iter()
+                .enumerate()
+                .map(|(index, component)| {
+                    component.to_json(&format!("field{}", index), scope, structs)
+                })
+                .collect();
+            fields.push(("components", Json::Array(components)));
+        }
+
+        fields.push(("internalType", Json::String(self.internal_type(scope, structs))));
+        fields.push(("name", Json::String(name.to_owned())));
+        fields.push(("type", Json::String(self.abi_type())));
+
+        Json::Object(fields)
+    }
+}
+
+fn array_suffix(size: &Option<usize>) -> String {
+    match size {
+        Some(size) => format!("[{}]", size),
+        None => String::from("[]"),
+    }
+}
+
+/// Split a list of types on the commas which are not inside a nested tuple.
+fn split_top_level(input: &str) -> Vec<&str> {
+    if input.is_empty() {
+        return Vec::new()
+    }
+
+    let mut output = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (index, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                output.push(&input[start..index]);
+                start = index + 1;
+            },
+            _ => (),
+        }
+    }
+
+    output.push(&input[start..]);
+    output
+}
+
+/// Minimal JSON representation to generate ABI files without extra dependencies.
+enum Json {
+    Bool(bool),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn write(&self, output: &mut String, indent: usize) {
+        let padding = "  ".repeat(indent + 1);
+        let closing_padding = "  ".repeat(indent);
+
+        match self {
+            Json::Bool(value) => {
+                let _ = write!(output, "{}", value);
+            },
+            Json::String(value) => {
+                output.push('"');
+                for c in value.chars() {
+                    match c {
+                        '"' => output.push_str("\\\""),
+                        '\\' => output.push_str("\\\\"),
+                        c => output.push(c),
+                    }
+                }
+                output.push('"');
+            },
+            Json::Array(items) if items.is_empty() => output.push_str("[]"),
+            Json::Array(items) => {
+                output.push_str("[\n");
+                for (index, item) in items.iter().enumerate() {
+                    if index != 0 {
+                        output.push_str(",\n");
+                    }
+                    output.push_str(&padding);
+                    item.write(output, indent + 1);
+                }
+                let _ = write!(output, "\n{}]", closing_padding);
+            },
+            Json::Object(fields) => {
+                output.push_str("{\n");
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index != 0 {
+                        output.push_str(",\n");
+                    }
+                    let _ = write!(output, "{}\"{}\": ", padding, key);
+                    value.write(output, indent + 1);
+                }
+                let _ = write!(output, "\n{}}}", closing_padding);
+            },
+        }
+    }
+}
@@ -252,6 +252,52 @@ impl<T: EvmData> EvmData for Vec<T> {
     }
 }
 
+/// The fixed-size array `T[N]` of Solidity.
+/// It is encoded like a tuple of `N` elements of type `T`: inline if `T` has a static size,
+/// behind a pointer otherwise.
+impl<T: EvmData, const N: usize> EvmData for [T; N] {
+    fn read(reader: &mut EvmDataReader) -> MayRevert<Self> {
+        let mut array: Vec<T> = Vec::with_capacity(N);
+
+        if T::has_static_size() {
+            for i in 0..N {
+                array.push(reader.read().in_array(i)?);
+            }
+        } else {
+            let mut inner_reader = reader.read_pointer()?;
+            for i in 0..N {
+                array.push(inner_reader.read().in_array(i)?);
+            }
+        }
+
+        array
+            .try_into()
+            .map_err(|_| RevertReason::read_out_of_bounds("array content").into())
+    }
+
+    fn write(writer: &mut EvmDataWriter, value: Self) {
+        if T::has_static_size() {
+            for inner in value {
+                T::write(writer, inner);
+            }
+        } else {
+            let mut inner_writer = EvmDataWriter::new();
+            for inner in value {
+                T::write(&mut inner_writer, inner);
+            }
+            writer.write_pointer(inner_writer.build());
+        }
+    }
+
+    fn has_static_size() -> bool {
+        T::has_static_size()
+    }
+
+    fn solidity_type() -> String {
+        alloc::format!("{}[{}]", T::solidity_type(), N)
+    }
+}
+
 /// Wrapper around a Vec that provides a max length bound on read.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BoundedVec<T, S> {
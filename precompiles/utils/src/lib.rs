@@ -20,6 +20,7 @@
 
 extern crate alloc;
 
+pub mod codegen;
 pub mod costs;
 pub mod handle;
 pub mod logs;
@@ -27,13 +28,18 @@ pub mod modifier;
 pub mod precompile_set;
 pub mod revert;
 pub mod substrate;
+#[cfg(feature = "evm-tracing")]
+pub mod tracing;
 
 #[cfg(feature = "testing")]
 pub mod solidity;
 
-#[cfg(feature = "testing")]
+#[cfg(any(feature = "testing", feature = "runtime-benchmarks"))]
 pub mod testing;
 
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+
 #[cfg(test)]
 mod tests;
 
@@ -44,7 +50,13 @@ pub mod data;
 
 pub use data::{EvmData, EvmDataReader, EvmDataWriter};
 pub use fp_evm::Precompile;
-pub use precompile_utils_macro::{generate_function_selector, keccak256, precompile};
+// Used by the macros of this crate, so that their users don't depend on `fp_evm` directly.
+#[doc(hidden)]
+pub use fp_evm;
+pub use precompile_utils_macro::{generate_function_selector, keccak256, precompile, EvmData};
+// Used by `event!` and `custom_error!` to name the fields like the other macros do.
+#[doc(hidden)]
+pub use precompile_utils_macro::lower_camel_case;
 
 /// Generated a `PrecompileFailure::Revert` with proper encoding for the output.
 /// If the revert needs improved formatting such as backtraces, `Revert` type should
@@ -65,6 +77,21 @@ pub fn succeed(output: impl AsRef<[u8]>) -> PrecompileOutput {
     PrecompileOutput { exit_status: ExitSucceed::Returned, output: output.as_ref().to_owned() }
 }
 
+/// Reports the Solidity signature of the function called by `input` to the EVM tracing, with the
+/// `evm-tracing` feature of this crate. Called by the precompiles generated by `#[precompile]`,
+/// which cannot check the features of this crate.
+#[doc(hidden)]
+#[cfg_attr(not(feature = "evm-tracing"), allow(unused_variables))]
+pub fn trace_function(input: &[u8], selector_signature: fn(u32) -> Option<&'static str>) {
+    #[cfg(feature = "evm-tracing")]
+    if let Some(signature) = input
+        .get(0..4)
+        .and_then(|selector| selector_signature(u32::from_be_bytes(selector.try_into().ok()?)))
+    {
+        tracing::function(signature);
+    }
+}
+
 /// Alias for Result returning an EVM precompile error.
 pub type EvmResult<T = ()> = Result<T, PrecompileFailure>;
 
@@ -82,18 +109,22 @@ pub trait StatefulPrecompile {
 
 pub mod prelude {
     pub use crate::{
+        custom_error,
         data::{
             Address, BoundedBytes, BoundedString, BoundedVec, EvmData, EvmDataReader,
             EvmDataWriter, SolidityConvert, UnboundedBytes, UnboundedString,
         },
+        event,
         handle::PrecompileHandleExt,
-        logs::{log0, log1, log2, log3, log4, LogExt},
+        logs::{log0, log1, log2, log3, log4, Event, EventBuilder, LogExt},
         modifier::{check_function_modifier, FunctionModifier},
         read_args, read_struct, revert,
-        revert::{BacktraceExt, InjectBacktrace, MayRevert, Revert, RevertExt, RevertReason},
+        revert::{
+            BacktraceExt, CustomError, InjectBacktrace, MayRevert, Revert, RevertExt, RevertReason,
+        },
         substrate::{RuntimeHelper, TryDispatchError},
         succeed, EvmResult, StatefulPrecompile,
     };
     pub use pallet_evm::{PrecompileHandle, PrecompileOutput};
-    pub use precompile_utils_macro::{generate_function_selector, keccak256, precompile};
+    pub use precompile_utils_macro::{generate_function_selector, keccak256, precompile, EvmData};
 }
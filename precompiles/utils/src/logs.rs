@@ -14,7 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::EvmResult;
+use crate::{
+    data::{EvmData, EvmDataWriter},
+    EvmResult,
+};
 use pallet_evm::{Log, PrecompileHandle};
 use sp_core::{H160, H256};
 use sp_std::{vec, vec::Vec};
@@ -92,3 +95,184 @@ impl LogExt for Log {
         crate::costs::log_costs(self.topics.len(), self.data.len())
     }
 }
+
+/// Solidity event, usually declared with the `event!` macro.
+pub trait Event: Sized {
+    /// Solidity signature of the event, like `Transfer(address,address,uint256)`.
+    const SIGNATURE: &'static str;
+
+    /// First topic of the event, which is the Keccak256 of the signature.
+    const TOPIC0: [u8; 32];
+
+    /// Add the indexed fields and data of the event to the builder.
+    fn build(self, builder: EventBuilder) -> EventBuilder;
+
+    /// Definition of the event, used to generate Solidity interfaces and ABI.
+    fn definition() -> crate::codegen::EventDefinition;
+
+    /// Create the log of this event emitted by the provided address.
+    fn log(self, address: impl Into<H160>) -> Log {
+        self.build(EventBuilder::new(Self::TOPIC0)).into_log(address)
+    }
+}
+
+/// Helper to build the topics and data of an event.
+pub struct EventBuilder {
+    topics: Vec<H256>,
+    data: EvmDataWriter,
+}
+
+impl EventBuilder {
+    pub fn new(topic0: impl Into<H256>) -> Self {
+        Self { topics: vec![topic0.into()], data: EvmDataWriter::new() }
+    }
+
+    /// Add an indexed field as a topic.
+    /// Values of static size fitting in 32 bytes are stored as is, while larger static values
+    /// (structs, fixed-size arrays) are hashed like Solidity does.
+    /// Dynamic types (bytes, string, arrays) are not supported.
+    pub fn indexed<T: EvmData>(mut self, value: T) -> Self {
+        let encoded = EvmDataWriter::new().write(value).build();
+
+        let topic = if encoded.len() == 32 {
+            H256::from_slice(&encoded)
+        } else {
+            H256::from(sp_io::hashing::keccak_256(&encoded))
+        };
+
+        self.topics.push(topic);
+        self
+    }
+
+    /// Add a non-indexed field to the data.
+    pub fn data<T: EvmData>(mut self, value: T) -> Self {
+        self.data = self.data.write(value);
+        self
+    }
+
+    /// Build the log emitted by the provided address.
+    /// A log has at most 4 topics, so at most 3 fields can be indexed.
+    pub fn into_log(self, address: impl Into<H160>) -> Log {
+        let data = self.data.build();
+        let topics = self.topics;
+        debug_assert!(topics.len() <= 4, "a log has at most 4 topics, got {}", topics.len());
+
+        match topics.len() {
+            1 => log1(address, topics[0], data),
+            2 => log2(address, topics[0], topics[1], data),
+            3 => log3(address, topics[0], topics[1], topics[2], data),
+            4 => log4(address, topics[0], topics[1], topics[2], topics[3], data),
+            _ => Log { address: address.into(), topics, data },
+        }
+    }
+}
+
+/// Declare a struct representing a Solidity event.
+/// Topic0 is computed from the provided signature, fields marked with `#[indexed]` are
+/// stored as topics and other fields are ABI-encoded as the log data using `EvmData`.
+///
+/// A log having at most 4 topics, declaring more than 3 indexed fields fails to compile.
+/// A test is generated to check the signature matches the types of the fields, and that
+/// the indexed fields have a static size.
+///
+/// ```rust,ignore
+/// event!(
+/// 	pub struct Transfer("Transfer(address,address,uint256)") {
+/// 		#[indexed] from: Address,
+/// 		#[indexed] to: Address,
+/// 		value: U256,
+/// 	}
+/// );
+///
+/// let log = Transfer { from, to, value }.log(handle.context().address);
+/// handle.record_log_costs(&[&log])?;
+/// log.record(handle)?;
+/// ```
+#[macro_export]
+macro_rules! event {
+	(
+		$(#[$attr:meta])*
+		$vis:vis struct $name:ident($signature:literal) {
+			$($(#[$indexed:ident])? $field_vis:vis $field:ident: $ty:ty),* $(,)?
+		}
+	) => {
+		$(#[$attr])*
+		$vis struct $name {
+			$($field_vis $field: $ty),*
+		}
+
+		const _: () = assert!(
+			0 $(+ $crate::__event_field!(@is_indexed $($indexed)?) as usize)* <= 3,
+			"an event has at most 3 indexed fields"
+		);
+
+		impl $crate::logs::Event for $name {
+			const SIGNATURE: &'static str = $signature;
+
+			const TOPIC0: [u8; 32] = $crate::keccak256!($signature);
+
+			fn build(self, builder: $crate::logs::EventBuilder) -> $crate::logs::EventBuilder {
+				$(
+					let builder =
+						$crate::__event_field!(@build builder, self.$field, $ty, $($indexed)?);
+				)*
+				builder
+			}
+
+			fn definition() -> $crate::codegen::EventDefinition {
+				use $crate::{codegen, data::EvmData};
+				codegen::EventDefinition::new(codegen::signature_name($signature))
+					$(.input(
+						$crate::lower_camel_case!($field),
+						<$ty>::solidity_type(),
+						$crate::__event_field!(@is_indexed $($indexed)?),
+					))*
+			}
+		}
+
+		$crate::data::paste! {
+			#[test]
+			#[allow(non_snake_case)]
+			fn [<__ $name _test_event_signature>]() {
+				use $crate::data::EvmData;
+				let signature: &str = $signature;
+				let arguments = &signature[signature.find('(').unwrap_or(signature.len())..];
+				assert_eq!(
+					arguments,
+					<($($ty,)*)>::solidity_type(),
+					"{} event signature doesn't match (left: signature, right: computed from \
+					Rust types)",
+					stringify!($name)
+				);
+
+				$(
+					if $crate::__event_field!(@is_indexed $($indexed)?) {
+						assert!(
+							<$ty>::has_static_size(),
+							"{}.{} is indexed but doesn't have a static size",
+							stringify!($name),
+							stringify!($field)
+						);
+					}
+				)*
+			}
+		}
+	};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __event_field {
+    (@is_indexed indexed) => {
+        true
+    };
+    (@is_indexed) => {
+        false
+    };
+    (@build $builder:ident, $value:expr, $ty:ty, indexed) => {
+        $builder.indexed::<$ty>($value)
+    };
+    (@build $builder:ident, $value:expr, $ty:ty,) => {
+        $builder.data::<$ty>($value)
+    };
+}
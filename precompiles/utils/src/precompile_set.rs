@@ -510,6 +510,11 @@ pub struct PrecompileSetBuilder<R, P> {
 
 impl<R, P: PrecompileSetFragment> PrecompileSet for PrecompileSetBuilder<R, P> {
     fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
+        #[cfg(feature = "evm-tracing")]
+        if self.inner.is_precompile(handle.code_address()) {
+            crate::tracing::precompile(handle.code_address());
+        }
+
         self.inner.execute(handle)
     }
 
@@ -370,3 +370,105 @@ impl From<RevertReason> for PrecompileFailure {
         Revert::new(err).into()
     }
 }
+
+/// Solidity custom error (`error Name(type1 arg1, ...)`), usually declared with the
+/// `custom_error!` macro.
+/// It is encoded like a function call: 4-bytes selector followed by the ABI-encoded arguments.
+pub trait CustomError: Sized {
+    /// Solidity signature of the error, like `InsufficientBalance(uint256,uint256)`.
+    const SIGNATURE: &'static str;
+
+    /// Selector of the error, which is the first 4 bytes of the Keccak256 of the signature.
+    const SELECTOR: u32;
+
+    /// Write the arguments of the error.
+    fn write_arguments(self, writer: EvmDataWriter) -> EvmDataWriter;
+
+    /// Definition of the error, used to generate Solidity interfaces and ABI.
+    fn definition() -> crate::codegen::ErrorDefinition;
+
+    /// Encode the error to be used as revert output.
+    fn encode(self) -> Vec<u8> {
+        self.write_arguments(EvmDataWriter::new_with_selector(Self::SELECTOR)).build()
+    }
+}
+
+/// Declare a struct representing a Solidity custom error.
+/// The selector is computed from the provided signature, and the struct fields are encoded as
+/// the error arguments using `EvmData`. It implements `Into<PrecompileFailure>`, which allows to
+/// return it with `?` or `.into()` in a precompile.
+///
+/// A test is generated to check the signature matches the types of the fields.
+///
+/// ```rust,ignore
+/// custom_error!(
+/// 	pub struct InsufficientBalance("InsufficientBalance(uint256,uint256)") {
+/// 		available: U256,
+/// 		required: U256,
+/// 	}
+/// );
+///
+/// return Err(InsufficientBalance { available, required }.into());
+/// ```
+#[macro_export]
+macro_rules! custom_error {
+	(
+		$(#[$attr:meta])*
+		$vis:vis struct $name:ident($signature:literal) {
+			$($field_vis:vis $field:ident: $ty:ty),* $(,)?
+		}
+	) => {
+		$(#[$attr])*
+		$vis struct $name {
+			$($field_vis $field: $ty),*
+		}
+
+		impl $crate::revert::CustomError for $name {
+			const SIGNATURE: &'static str = $signature;
+
+			const SELECTOR: u32 = {
+				let hash = $crate::keccak256!($signature);
+				u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+			};
+
+			fn write_arguments(
+				self,
+				writer: $crate::data::EvmDataWriter,
+			) -> $crate::data::EvmDataWriter {
+				writer $(.write::<$ty>(self.$field))*
+			}
+
+			fn definition() -> $crate::codegen::ErrorDefinition {
+				use $crate::{codegen, data::EvmData};
+				codegen::ErrorDefinition::new(codegen::signature_name($signature))
+					$(.input($crate::lower_camel_case!($field), <$ty>::solidity_type()))*
+			}
+		}
+
+		impl From<$name> for $crate::fp_evm::PrecompileFailure {
+			fn from(error: $name) -> Self {
+				$crate::fp_evm::PrecompileFailure::Revert {
+					exit_status: $crate::fp_evm::ExitRevert::Reverted,
+					output: $crate::revert::CustomError::encode(error),
+				}
+			}
+		}
+
+		$crate::data::paste! {
+			#[test]
+			#[allow(non_snake_case)]
+			fn [<__ $name _test_custom_error_signature>]() {
+				use $crate::data::EvmData;
+				let signature: &str = $signature;
+				let arguments = &signature[signature.find('(').unwrap_or(signature.len())..];
+				assert_eq!(
+					arguments,
+					<($($ty,)*)>::solidity_type(),
+					"{} error signature doesn't match (left: signature, right: computed from \
+					Rust types)",
+					stringify!($name)
+				);
+			}
+		}
+	};
+}
@@ -16,11 +16,13 @@
 
 //! Utility module to interact with solidity file.
 
+use crate::codegen::InterfaceDefinition;
 use sp_io::hashing::keccak_256;
 use std::{
     collections::HashMap,
     fs::File,
     io::{BufRead, BufReader, Read},
+    path::Path,
 };
 
 /// Represents a declared custom type struct within a solidity file
@@ -88,6 +90,19 @@ pub fn get_selectors(filename: &str) -> Vec<SolidityFunction> {
     get_selectors_from_reader(file)
 }
 
+/// Writes the Solidity interface (`<name>.sol`) and its ABI (`<name>.json`) generated from a
+/// precompile definition into the provided directory.
+pub fn write_interface_files(interface: &InterfaceDefinition, directory: impl AsRef<Path>) {
+    let directory = directory.as_ref();
+    for (extension, content) in
+        [("sol", interface.to_solidity()), ("json", interface.to_abi_json())]
+    {
+        let path = directory.join(format!("{}.{}", interface.name, extension));
+        std::fs::write(&path, content)
+            .unwrap_or_else(|e| panic!("failed writing file '{}': {}", path.display(), e));
+    }
+}
+
 /// Attempts to lookup a custom struct and returns its primitive signature
 fn try_lookup_custom_type(word: &str, custom_types: &HashMap<String, SolidityStruct>) -> String {
     match word.strip_suffix("[]") {
@@ -235,6 +250,53 @@ fn get_selectors_from_reader<R: Read>(reader: R) -> Vec<SolidityFunction> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{codegen::FunctionDefinition, modifier::FunctionModifier};
+
+    #[test]
+    fn test_generated_interface_is_parsed() {
+        let functions = vec![
+            FunctionDefinition::new(
+                "fnOneArg(address)",
+                compute_selector("fnOneArg(address)"),
+                FunctionModifier::View,
+            )
+            .input("owner", "address")
+            .output("uint256"),
+            FunctionDefinition::new(
+                "fnCustomArgs((uint8,bytes[]),bytes[],uint64)",
+                compute_selector("fnCustomArgs((uint8,bytes[]),bytes[],uint64)"),
+                FunctionModifier::NonPayable,
+            )
+            .input("location", "(uint8,bytes[])")
+            .input("data", "bytes[]")
+            .input("amount", "uint64"),
+            FunctionDefinition::new(
+                "fnCustomComposedArrayArg(((uint8,bytes[]),address[])[],uint64)",
+                compute_selector("fnCustomComposedArrayArg(((uint8,bytes[]),address[])[],uint64)"),
+                FunctionModifier::Payable,
+            )
+            .input("locations", "((uint8,bytes[]),address[])[]")
+            .input("amount", "uint64")
+            .output("(uint8,bytes[])"),
+        ];
+
+        let interface = functions
+            .iter()
+            .cloned()
+            .fold(InterfaceDefinition::new("ITest"), |interface, f| interface.function(f));
+        let solidity = interface.to_solidity();
+
+        let actual = get_selectors_from_reader(solidity.as_bytes())
+            .into_iter()
+            .map(|sol_fn| (sol_fn.docs_selector.clone(), sol_fn.signature()))
+            .collect::<Vec<_>>();
+        let expected = functions
+            .iter()
+            .map(|f| (format!("{:0>8x}", f.selector), f.signature.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(expected, actual);
+    }
 
     #[test]
     fn test_selectors_are_parsed() {
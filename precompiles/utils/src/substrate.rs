@@ -44,6 +44,18 @@ impl From<TryDispatchError> for PrecompileFailure {
     }
 }
 
+/// The calls of the runtime are required to provide their metadata only with the `evm-tracing`
+/// feature, which reports them by pallet and function names.
+#[cfg(feature = "evm-tracing")]
+pub trait CallMetadata: frame_support::traits::GetCallMetadata {}
+#[cfg(feature = "evm-tracing")]
+impl<T: frame_support::traits::GetCallMetadata> CallMetadata for T {}
+/// Without the `evm-tracing` feature, no metadata is required from the calls.
+#[cfg(not(feature = "evm-tracing"))]
+pub trait CallMetadata {}
+#[cfg(not(feature = "evm-tracing"))]
+impl<T> CallMetadata for T {}
+
 /// Helper functions requiring a Substrate runtime.
 /// This runtime must of course implement `pallet_evm::Config`.
 #[derive(Clone, Copy, Debug)]
@@ -52,11 +64,12 @@ pub struct RuntimeHelper<Runtime>(PhantomData<Runtime>);
 impl<Runtime> RuntimeHelper<Runtime>
 where
     Runtime: pallet_evm::Config,
-    Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo,
+    Runtime::Call: Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo + CallMetadata,
 {
     /// Try to dispatch a Substrate call.
     /// Return an error if there are not enough gas, or if the call fails.
     /// If successful returns the used gas using the Runtime GasWeightMapping.
+    /// The dispatched call is reported to the EVM tracing with the `evm-tracing` feature.
     pub fn try_dispatch<Call>(
         handle: &mut impl PrecompileHandle,
         origin: <Runtime::Call as Dispatchable>::Origin,
@@ -80,15 +93,35 @@ where
         // However while Substrate handle checking weight while not making the sender pay for it,
         // the EVM doesn't. It seems this safer to always record the costs to avoid unmetered
         // computations.
-        let post_dispatch_info =
-            call.dispatch(origin).map_err(|e| TryDispatchError::Substrate(e.error))?;
+        #[cfg(feature = "evm-tracing")]
+        let metadata = call.get_call_metadata();
+        let result = call.dispatch(origin);
+        #[cfg(feature = "evm-tracing")]
+        if let Err(e) = &result {
+            crate::tracing::dispatch(
+                metadata.pallet_name,
+                metadata.function_name,
+                None,
+                Some(alloc::format!("{:?}", e.error).into_bytes()),
+            );
+        }
+        let post_dispatch_info = result.map_err(|e| TryDispatchError::Substrate(e.error))?;
 
         let used_weight = post_dispatch_info.actual_weight;
 
         let used_gas =
             Runtime::GasWeightMapping::weight_to_gas(used_weight.unwrap_or(dispatch_info.weight));
 
-        handle.record_cost(used_gas).map_err(|e| TryDispatchError::Evm(e))?;
+        let recorded = handle.record_cost(used_gas);
+        // The dispatch is only reported as successful, with its cost, once the cost is recorded.
+        #[cfg(feature = "evm-tracing")]
+        crate::tracing::dispatch(
+            metadata.pallet_name,
+            metadata.function_name,
+            recorded.is_ok().then(|| used_gas),
+            recorded.as_ref().err().map(|e| alloc::format!("{:?}", e).into_bytes()),
+        );
+        recorded.map_err(TryDispatchError::Evm)?;
 
         Ok(post_dispatch_info)
     }
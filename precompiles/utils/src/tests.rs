@@ -14,7 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Moonbeam.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{prelude::*, revert::Backtrace};
+use crate::{
+    codegen::{ErrorDefinition, EventDefinition, FunctionDefinition, InterfaceDefinition},
+    prelude::*,
+    revert::Backtrace,
+};
 use frame_support::traits::ConstU32;
 use hex_literal::hex;
 use pallet_evm::Context;
@@ -862,6 +866,55 @@ fn write_dynamic_size_tuple() {
     assert_eq!(output, data);
 }
 
+#[test]
+fn write_read_static_fixed_array() {
+    let value = [U256::from(1u8), U256::from(2u8)];
+
+    let output = EvmDataWriter::new().write(value).build();
+
+    let data = hex!(
+        "0000000000000000000000000000000000000000000000000000000000000001
+		0000000000000000000000000000000000000000000000000000000000000002"
+    );
+    assert_eq!(output, data);
+
+    let mut reader = EvmDataReader::new(&output);
+    assert_eq!(reader.read::<[U256; 2]>().unwrap(), value);
+}
+
+#[test]
+fn write_read_dynamic_fixed_array() {
+    let value = [UnboundedBytes::from(vec![0x01]), UnboundedBytes::from(vec![0x02])];
+
+    let output = EvmDataWriter::new().write(value.clone()).build();
+
+    // bytes[2] encoded by web3
+    let data = hex!(
+        "0000000000000000000000000000000000000000000000000000000000000020
+		0000000000000000000000000000000000000000000000000000000000000040
+		0000000000000000000000000000000000000000000000000000000000000080
+		0000000000000000000000000000000000000000000000000000000000000001
+		0100000000000000000000000000000000000000000000000000000000000000
+		0000000000000000000000000000000000000000000000000000000000000001
+		0200000000000000000000000000000000000000000000000000000000000000"
+    );
+    assert_eq!(output, data);
+
+    let mut reader = EvmDataReader::new(&output);
+    assert_eq!(reader.read::<[UnboundedBytes; 2]>().unwrap(), value);
+}
+
+#[test]
+fn read_fixed_array_too_short() {
+    let data = EvmDataWriter::new().write(U256::from(1u8)).build();
+
+    let mut reader = EvmDataReader::new(&data);
+    assert_eq!(
+        reader.read::<[U256; 2]>(),
+        Err(Revert::new(RevertReason::read_out_of_bounds("uint256")).in_array(1))
+    );
+}
+
 #[test]
 fn error_location_formatting() {
     assert_eq!(
@@ -918,6 +971,12 @@ fn evm_data_solidity_types() {
     assert_eq!(Vec::<UnboundedBytes>::solidity_type(), "bytes[]");
     assert_eq!(Vec::<BoundedBytes<ConstU32<5>>>::solidity_type(), "bytes[]");
 
+    // Fixed-size arrays
+    assert_eq!(<[U256; 2]>::solidity_type(), "uint256[2]");
+    assert_eq!(<[UnboundedBytes; 3]>::solidity_type(), "bytes[3]");
+    assert_eq!(<[Vec<Address>; 2]>::solidity_type(), "address[][2]");
+    assert_eq!(Vec::<[bool; 2]>::solidity_type(), "bool[2][]");
+
     // Few tuples mixed with arrays
     assert_eq!(<(bool, Address)>::solidity_type(), "(bool,address)");
     assert_eq!(<(Vec<bool>, Address)>::solidity_type(), "(bool[],address)");
@@ -928,3 +987,252 @@ fn evm_data_solidity_types() {
     // Struct encode like tuples
     assert_eq!(MultiLocation::solidity_type(), "(uint8,bytes[])");
 }
+
+fn test_interface() -> InterfaceDefinition {
+    InterfaceDefinition::new("ITest")
+        .function(
+            FunctionDefinition::new("balanceOf(address)", 0x70a08231, FunctionModifier::View)
+                .input("owner", Address::solidity_type())
+                .output(U256::solidity_type()),
+        )
+        .function(
+            FunctionDefinition::new(
+                "transferMultiple((uint8,bytes[]),uint256[])",
+                0x12345678,
+                FunctionModifier::NonPayable,
+            )
+            .input("destination", MultiLocation::solidity_type())
+            .input("amounts", Vec::<U256>::solidity_type()),
+        )
+}
+
+#[test]
+fn generate_solidity_interface() {
+    assert_eq!(
+        test_interface().to_solidity(),
+        "// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.3;
+
+/// @title ITest
+/// @dev Generated from the `#[precompile]` definition.
+interface ITest {
+    struct Tuple0 {
+        uint8 field0;
+        bytes[] field1;
+    }
+
+    /// @custom:selector 70a08231
+    function balanceOf(address owner) external view returns (uint256);
+
+    /// @custom:selector 12345678
+    function transferMultiple(Tuple0 memory destination, uint256[] memory amounts) external;
+}
+"
+    );
+}
+
+#[test]
+fn generate_abi_json() {
+    let interface = InterfaceDefinition::new("ITest").function(
+        FunctionDefinition::new("foo((uint8,bytes[]))", 0x12345678, FunctionModifier::Payable)
+            .input("location", MultiLocation::solidity_type()),
+    );
+
+    assert_eq!(
+        interface.to_abi_json(),
+        r#"[
+  {
+    "inputs": [
+      {
+        "components": [
+          {
+            "internalType": "uint8",
+            "name": "field0",
+            "type": "uint8"
+          },
+          {
+            "internalType": "bytes[]",
+            "name": "field1",
+            "type": "bytes[]"
+          }
+        ],
+        "internalType": "struct ITest.Tuple0",
+        "name": "location",
+        "type": "tuple"
+      }
+    ],
+    "name": "foo",
+    "outputs": [],
+    "stateMutability": "payable",
+    "type": "function"
+  }
+]
+"#
+    );
+}
+
+crate::custom_error!(
+    struct PanicError("Panic(uint256)") {
+        code: U256,
+    }
+);
+
+crate::custom_error!(
+    struct InsufficientBalance("InsufficientBalance(uint256,uint256)") {
+        available: U256,
+        required_amount: U256,
+    }
+);
+
+crate::event!(
+    struct TransferEvent("Transfer(address,address,uint256)") {
+        #[indexed]
+        from: Address,
+        #[indexed]
+        to: Address,
+        value: U256,
+    }
+);
+
+crate::event!(
+    struct ThreeIndexedEvent("ThreeIndexed(address,address,uint256)") {
+        #[indexed]
+        from: Address,
+        #[indexed]
+        to: Address,
+        #[indexed]
+        value: U256,
+    }
+);
+
+#[test]
+fn custom_error_encoding() {
+    assert_eq!(PanicError::SELECTOR, 0x4e487b71);
+
+    let encoded = PanicError { code: 0x11.into() }.encode();
+    let expected = EvmDataWriter::new_with_selector(0x4e487b71u32).write(U256::from(0x11)).build();
+    assert_eq!(encoded, expected);
+
+    let failure: fp_evm::PrecompileFailure = PanicError { code: 0x11.into() }.into();
+    assert_eq!(
+        failure,
+        fp_evm::PrecompileFailure::Revert {
+            exit_status: fp_evm::ExitRevert::Reverted,
+            output: expected,
+        }
+    );
+}
+
+#[test]
+fn custom_error_definition() {
+    assert_eq!(
+        InsufficientBalance::definition(),
+        ErrorDefinition::new("InsufficientBalance")
+            .input("available", "uint256")
+            .input("requiredAmount", "uint256")
+    );
+}
+
+#[test]
+fn event_log() {
+    let from = H160::repeat_byte(0x11);
+    let to = H160::repeat_byte(0x22);
+    let log = TransferEvent { from: from.into(), to: to.into(), value: 42.into() }
+        .log(H160::repeat_byte(0xff));
+
+    assert_eq!(
+        TransferEvent::TOPIC0,
+        hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+    );
+    assert_eq!(log.address, H160::repeat_byte(0xff));
+    assert_eq!(log.topics, vec![TransferEvent::TOPIC0.into(), from.into(), to.into()]);
+    assert_eq!(log.data, EvmDataWriter::new().write(U256::from(42)).build());
+}
+
+#[test]
+fn event_log_with_three_indexed_fields() {
+    let log = ThreeIndexedEvent {
+        from: H160::repeat_byte(0x11).into(),
+        to: H160::repeat_byte(0x22).into(),
+        value: 42.into(),
+    }
+    .log(H160::repeat_byte(0xff));
+
+    assert_eq!(log.topics.len(), 4);
+    assert_eq!(log.topics[3], H256::from_low_u64_be(42));
+    assert!(log.data.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "a log has at most 4 topics")]
+fn event_log_with_too_many_topics() {
+    let builder = (0u64..4).fold(EventBuilder::new(TransferEvent::TOPIC0), |builder, i| {
+        builder.indexed(U256::from(i))
+    });
+    let _ = builder.into_log(H160::repeat_byte(0xff));
+}
+
+#[test]
+fn event_definition() {
+    assert_eq!(
+        TransferEvent::definition(),
+        EventDefinition::new("Transfer")
+            .input("from", "address", true)
+            .input("to", "address", true)
+            .input("value", "uint256", false)
+    );
+}
+
+#[test]
+fn generate_solidity_interface_with_events_and_errors() {
+    let interface = InterfaceDefinition::new("ITest")
+        .event(TransferEvent::definition())
+        .error(InsufficientBalance::definition());
+
+    assert_eq!(
+        interface.to_solidity(),
+        r#"// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.3;
+
+/// @title ITest
+/// @dev Generated from the `#[precompile]` definition.
+interface ITest {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+
+    error InsufficientBalance(uint256 available, uint256 requiredAmount);
+}
+"#
+    );
+}
+
+#[test]
+fn generate_abi_json_with_events_and_errors() {
+    let interface = InterfaceDefinition::new("ITest")
+        .event(EventDefinition::new("Approval").input("owner", "address", true))
+        .error(ErrorDefinition::new("Unauthorized"));
+
+    assert_eq!(
+        interface.to_abi_json(),
+        r#"[
+  {
+    "anonymous": false,
+    "inputs": [
+      {
+        "indexed": true,
+        "internalType": "address",
+        "name": "owner",
+        "type": "address"
+      }
+    ],
+    "name": "Approval",
+    "type": "event"
+  },
+  {
+    "inputs": [],
+    "name": "Unauthorized",
+    "type": "error"
+  }
+]
+"#
+    );
+}
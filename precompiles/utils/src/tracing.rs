@@ -0,0 +1,34 @@
+//! Reports the precompile calls and the Substrate calls they dispatch to the EVM tracing, proxied
+//! to the host like the events of the EVM.
+
+use crate::alloc::vec::Vec;
+use codec::Encode;
+use primitives_evm_ext::evm_ext;
+use primitives_evm_tracing_events::EvmEvent;
+use sp_core::H160;
+
+/// Reports that the current call is handled by the precompile at `code_address`.
+pub fn precompile(code_address: H160) {
+    evm_ext::evm_event(EvmEvent::Precompile { code_address }.encode());
+}
+
+/// Reports the Solidity signature of the function of the current precompile called.
+pub fn function(signature: &str) {
+    evm_ext::evm_event(
+        EvmEvent::PrecompileFunction { signature: signature.as_bytes().to_vec() }.encode(),
+    );
+}
+
+/// Reports a Substrate call dispatched by the current precompile, with the gas recorded for it,
+/// none if it failed.
+pub fn dispatch(pallet: &str, call: &str, gas_cost: Option<u64>, error: Option<Vec<u8>>) {
+    evm_ext::evm_event(
+        EvmEvent::PrecompileDispatch {
+            pallet: pallet.as_bytes().to_vec(),
+            call: call.as_bytes().to_vec(),
+            gas_cost,
+            error,
+        }
+        .encode(),
+    );
+}
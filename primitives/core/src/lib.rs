@@ -17,6 +17,9 @@ pub type Moment = u64;
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of an asset issued with the assets pallet.
+pub type AssetId = u128;
+
 /// A hash of some data used by the chain.
 pub type Hash = sp_core::H256;
 
@@ -59,6 +59,16 @@ pub trait EvmExt {
         }
     }
 
+    /// A runtime event deposited by the traced transaction, sent by the runtime to this host
+    /// function. runtime -> host.
+    fn substrate_event(&mut self, event: Vec<u8>) {
+        use codec::Decode;
+        use primitives_evm_tracing_events::{Event, SubstrateEvent};
+        if let Ok(event) = SubstrateEvent::decode(&mut &event[..]) {
+            Event::Substrate(event).emit();
+        }
+    }
+
     /// Allow the tracing module in the runtime to know how to filter Step event
     /// content, as cloning the entire data is expensive and most of the time
     /// not necessary.
@@ -72,4 +82,11 @@ pub trait EvmExt {
         use primitives_evm_tracing_events::Event;
         Event::CallListNew().emit();
     }
+
+    /// An event to create a new CallList for the Substrate extrinsic at `index` in the block,
+    /// which is not an Ethereum transaction.
+    fn call_list_new_extrinsic(&mut self, index: u32) {
+        use primitives_evm_tracing_events::Event;
+        Event::CallListNewExtrinsic(index).emit();
+    }
 }
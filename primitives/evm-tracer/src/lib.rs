@@ -9,6 +9,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Encode;
+use ethereum_types::{H160, H256};
 use evm::tracing::{using as evm_using, EventListener as EvmListener};
 use evm_gasometer::tracing::{using as gasometer_using, EventListener as GasometerListener};
 use evm_runtime::tracing::{using as runtime_using, EventListener as RuntimeListener};
@@ -39,11 +40,12 @@ impl<T: EvmListener> EvmListener for ListenerProxy<T> {
 
 pub struct EvmTracer {
     step_event_filter: StepEventFilter,
+    storage: Option<fn(H160, H256) -> H256>,
 }
 
 impl Default for EvmTracer {
     fn default() -> Self {
-        Self { step_event_filter: evm_ext::step_event_filter() }
+        Self { step_event_filter: evm_ext::step_event_filter(), storage: None }
     }
 }
 
@@ -52,11 +54,17 @@ impl EvmTracer {
         Self::default()
     }
 
+    /// Reads the storage slots of the contracts, to report the value overwritten by a
+    /// `RuntimeEvent::SStoreWithPrevious`. The event is emitted before the write is applied.
+    pub fn with_storage(mut self, storage: fn(H160, H256) -> H256) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
     /// Setup event listeners and execute provided closure.
     ///
-    /// Consume the tracer and return it alongside the return value of
-    /// the closure.
-    pub fn trace<R, F: FnOnce() -> R>(self, f: F) {
+    /// Consume the tracer and return the return value of the closure.
+    pub fn trace<R, F: FnOnce() -> R>(self, f: F) -> R {
         let wrapped = Rc::new(RefCell::new(self));
 
         let mut gasometer = ListenerProxy(Rc::clone(&wrapped));
@@ -69,12 +77,18 @@ impl EvmTracer {
         let f = || runtime_using(&mut runtime, f);
         let f = || gasometer_using(&mut gasometer, f);
         let f = || evm_using(&mut evm, f);
-        f();
+        f()
     }
 
     pub fn emit_new() {
         evm_ext::call_list_new();
     }
+
+    /// Starts the trace of the Substrate extrinsic at `index` in the block, which is not an
+    /// Ethereum transaction.
+    pub fn emit_new_extrinsic(index: u32) {
+        evm_ext::call_list_new_extrinsic(index);
+    }
 }
 
 impl EvmListener for EvmTracer {
@@ -98,7 +112,18 @@ impl GasometerListener for EvmTracer {
 impl RuntimeListener for EvmTracer {
     /// Proxies `evm_runtime::tracing::Event` to the host.
     fn event(&mut self, event: evm_runtime::tracing::Event) {
-        let event = RuntimeEvent::from_evm_event(event, self.step_event_filter);
+        let event =
+            match (RuntimeEvent::from_evm_event(event, self.step_event_filter), self.storage) {
+                (RuntimeEvent::SStore { address, index, value }, Some(storage)) => {
+                    RuntimeEvent::SStoreWithPrevious {
+                        address,
+                        index,
+                        value,
+                        previous: storage(address, index),
+                    }
+                },
+                (event, _) => event,
+            };
         let message = event.encode();
         evm_ext::runtime_event(message);
     }
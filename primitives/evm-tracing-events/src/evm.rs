@@ -110,6 +110,26 @@ pub enum EvmEvent {
         is_static: bool,
         context: super::Context,
     },
+    /// The current call is handled by the precompile at `code_address`. Not emitted by the EVM
+    /// but by the precompiles of the runtime, before executing.
+    Precompile {
+        code_address: H160,
+    },
+    /// A Substrate call dispatched by the precompile handling the current call, with the gas
+    /// recorded for its weight, none if it failed or its cost could not be recorded, and its
+    /// error.
+    PrecompileDispatch {
+        pallet: Vec<u8>,
+        call: Vec<u8>,
+        gas_cost: Option<u64>,
+        error: Option<Vec<u8>>,
+    },
+    /// The Solidity signature of the function called on the precompile handling the current
+    /// call, decoded from the selector of its input. Not emitted by the EVM either, but by the
+    /// precompiles generated by `#[precompile]`.
+    PrecompileFunction {
+        signature: Vec<u8>,
+    },
 }
 
 #[cfg(feature = "evm-tracing")]
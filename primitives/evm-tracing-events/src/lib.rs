@@ -18,8 +18,11 @@ extern crate alloc;
 pub mod evm;
 pub mod gasometer;
 pub mod runtime;
+pub mod substrate;
 
-pub use crate::{evm::EvmEvent, gasometer::GasometerEvent, runtime::RuntimeEvent};
+pub use crate::{
+    evm::EvmEvent, gasometer::GasometerEvent, runtime::RuntimeEvent, substrate::SubstrateEvent,
+};
 
 use ::evm::Opcode;
 use alloc::vec::Vec;
@@ -48,6 +51,10 @@ pub enum Event {
     Gasometer(GasometerEvent),
     Runtime(RuntimeEvent),
     CallListNew(),
+    Substrate(SubstrateEvent),
+    /// Like `CallListNew`, for a Substrate extrinsic of the block which is not an Ethereum
+    /// transaction but may enter the EVM, with its index in the block.
+    CallListNewExtrinsic(u32),
 }
 
 impl Event {
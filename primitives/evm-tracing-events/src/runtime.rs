@@ -73,6 +73,15 @@ pub enum RuntimeEvent {
         index: H256,
         value: H256,
     },
+    /// A `SStore` with the value overwritten, emitted instead when the tracer can read the storage
+    /// of the runtime. Added as a new variant, so that the events of the older runtimes still
+    /// decode.
+    SStoreWithPrevious {
+        address: H160,
+        index: H256,
+        value: H256,
+        previous: H256,
+    },
 }
 
 #[cfg(feature = "evm-tracing")]
@@ -110,3 +119,26 @@ impl RuntimeEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_the_sstore_of_the_older_runtimes() {
+        // `SStore` as encoded by the runtimes built before `SStoreWithPrevious` was added.
+        let mut encoded = vec![3u8];
+        encoded.extend([0x11; 20]);
+        encoded.extend([0x22; 32]);
+        encoded.extend([0x33; 32]);
+
+        assert_eq!(
+            RuntimeEvent::decode(&mut &encoded[..]),
+            Ok(RuntimeEvent::SStore {
+                address: H160::repeat_byte(0x11),
+                index: H256::repeat_byte(0x22),
+                value: H256::repeat_byte(0x33),
+            })
+        );
+    }
+}
@@ -0,0 +1,50 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use ethereum_types::{H160, U256};
+
+/// A runtime event deposited by a traced transaction. The events of `pallet_balances` describe
+/// the fee flows and the balance transfers, e.g. the fee withdrawn from the sender, its refund
+/// and the fee and the tip paid to the block author.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub enum SubstrateEvent {
+    Endowed {
+        account: H160,
+        free_balance: U256,
+    },
+    DustLost {
+        account: H160,
+        amount: U256,
+    },
+    Transfer {
+        from: H160,
+        to: H160,
+        amount: U256,
+    },
+    Reserved {
+        who: H160,
+        amount: U256,
+    },
+    Unreserved {
+        who: H160,
+        amount: U256,
+    },
+    Deposit {
+        who: H160,
+        amount: U256,
+    },
+    Withdraw {
+        who: H160,
+        amount: U256,
+    },
+    Slashed {
+        who: H160,
+        amount: U256,
+    },
+    /// Any other event, with the index of its pallet in the runtime and its SCALE encoding.
+    Other {
+        pallet_index: u8,
+        data: Vec<u8>,
+    },
+}
@@ -0,0 +1,99 @@
+use codec::{Decode, Encode};
+use ethereum_types::{H160, H256, U256};
+// Substrate
+use sp_runtime::{DispatchError, RuntimeDebug};
+use sp_std::vec::Vec;
+// Frontier
+use fp_evm::{CallInfo, CreateInfo, ExitReason};
+
+sp_api::decl_runtime_apis! {
+    /// `fp_rpc::EthereumRuntimeRPCApi` calls, executed on top of the overridden state and block.
+    pub trait EthCallRuntimeApi {
+        fn call(
+            from: H160,
+            to: H160,
+            data: Vec<u8>,
+            value: U256,
+            gas_limit: U256,
+            max_fee_per_gas: Option<U256>,
+            max_priority_fee_per_gas: Option<U256>,
+            nonce: Option<U256>,
+            estimate: bool,
+            access_list: Option<Vec<(H160, Vec<H256>)>>,
+            state_overrides: Vec<StateOverride>,
+            block_overrides: BlockOverrides,
+        ) -> Result<CallInfo, DispatchError>;
+
+        fn create(
+            from: H160,
+            data: Vec<u8>,
+            value: U256,
+            gas_limit: U256,
+            max_fee_per_gas: Option<U256>,
+            max_priority_fee_per_gas: Option<U256>,
+            nonce: Option<U256>,
+            estimate: bool,
+            access_list: Option<Vec<(H160, Vec<H256>)>>,
+            state_overrides: Vec<StateOverride>,
+            block_overrides: BlockOverrides,
+        ) -> Result<CreateInfo, DispatchError>;
+
+        /// Execute a call of an `eth_simulateV1` block. Its state changes are kept in the runtime
+        /// api overlay, so that the next calls are executed on top of them. The EVM events are
+        /// emitted to the client listener when `trace` is set.
+        fn simulate_call(
+            call: SimulateCall,
+            validate: bool,
+            trace: bool,
+            state_overrides: Vec<StateOverride>,
+            block_overrides: BlockOverrides,
+        ) -> Result<SimulatedCall, DispatchError>;
+    }
+}
+
+/// Override of the state of an account.
+#[derive(Clone, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct StateOverride {
+    pub address: H160,
+    pub balance: Option<U256>,
+    pub nonce: Option<U256>,
+    pub code: Option<Vec<u8>>,
+    /// Replace the whole storage of the account.
+    pub state: Option<Vec<(H256, H256)>>,
+    /// Replace the given storage slots of the account.
+    pub state_diff: Vec<(H256, H256)>,
+}
+
+/// Override of the block the call is executed in.
+#[derive(Clone, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct BlockOverrides {
+    pub number: Option<U256>,
+    /// Timestamp in seconds.
+    pub timestamp: Option<U256>,
+    pub coinbase: Option<H160>,
+    pub base_fee: Option<U256>,
+}
+
+/// A call of a simulated block, creating a contract when `to` is `None`.
+#[derive(Clone, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct SimulateCall {
+    pub from: H160,
+    pub to: Option<H160>,
+    pub data: Vec<u8>,
+    pub value: U256,
+    pub gas_limit: U256,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub nonce: Option<U256>,
+    pub access_list: Vec<(H160, Vec<H256>)>,
+}
+
+/// The outcome of a simulated call.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct SimulatedCall {
+    pub exit_reason: ExitReason,
+    /// The returned data, or the code of the created contract.
+    pub value: Vec<u8>,
+    pub used_gas: U256,
+    pub logs: Vec<ethereum::Log>,
+}
@@ -5,6 +5,7 @@ use sp_runtime::DispatchError;
 use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
+    #[api_version(2)]
     pub trait DebugRuntimeApi {
         fn trace_transaction(
             extrinsics: Vec<Block::Extrinsic>,
@@ -15,6 +16,10 @@ sp_api::decl_runtime_apis! {
             extrinsics: Vec<Block::Extrinsic>,
             known_transactions: Vec<H256>,
         ) -> Result<(), DispatchError>;
+
+        /// The index of the extrinsics of the block which entered the EVM without being Ethereum
+        /// transactions, read from the events of the block it is called at.
+        fn extrinsics_entering_evm() -> Vec<u32>;
     }
 }
 
@@ -2,9 +2,11 @@
 #![allow(clippy::unnecessary_mut_passed)]
 #![allow(clippy::too_many_arguments)]
 
+/// Runtime API for Geth `eth_call` overrides.
+pub mod call;
 /// Runtime API for Geth debug RPC.
 pub mod debug;
 /// Runtime API for Geth txpool RPC.
 pub mod txpool;
 
-pub use self::{debug::*, txpool::*};
+pub use self::{call::*, debug::*, txpool::*};
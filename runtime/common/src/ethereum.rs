@@ -34,3 +34,28 @@ where
             .expect("Encoded extrinsic is always valid")
     }
 }
+
+/// The `r` and `s` of the signature of a transaction sent by the dev rpc from the impersonated
+/// account `source`: `r` is the address of the sender, and `s` is `1`.
+#[cfg(feature = "manual-seal")]
+pub fn impersonated_signature(source: sp_core::H160) -> (sp_core::H256, sp_core::H256) {
+    (source.into(), sp_core::H256::from_low_u64_be(1))
+}
+
+/// The sender of a transaction signed with [`impersonated_signature`].
+///
+/// The manual-seal runtimes take it as the sender instead of recovering one from the signature,
+/// so any transaction of this form is accepted on the dev chains, the dev rpc only restricting the
+/// accounts it sends them from.
+#[cfg(feature = "manual-seal")]
+pub fn impersonated_source(transaction: &crate::EthereumTransaction) -> Option<sp_core::H160> {
+    use crate::EthereumTransaction;
+
+    let (r, s) = match transaction {
+        EthereumTransaction::Legacy(t) => (*t.signature.r(), *t.signature.s()),
+        EthereumTransaction::EIP2930(t) => (t.r, t.s),
+        EthereumTransaction::EIP1559(t) => (t.r, t.s),
+    };
+    let source = sp_core::H160::from(r);
+    (impersonated_signature(source) == (r, s)).then(|| source)
+}
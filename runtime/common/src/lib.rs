@@ -2,7 +2,10 @@
 
 pub mod ethereum;
 pub mod evm_config;
+pub mod overrides;
 pub mod pallets;
+#[cfg(feature = "evm-tracing")]
+pub mod tracing;
 
 // Substrate
 pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -63,6 +66,7 @@ where
     where
         I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
     {
-        <Self as FindAuthor<AccountId>>::find_author(digests).map(Into::into)
+        overrides::coinbase()
+            .or_else(|| <Self as FindAuthor<AccountId>>::find_author(digests).map(Into::into))
     }
 }
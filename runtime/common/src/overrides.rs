@@ -0,0 +1,151 @@
+//! State and block overrides of the `eth_call` runtime api.
+//!
+//! The overrides are written to the state of the runtime api call, which is discarded afterwards.
+
+// Substrate
+use sp_core::H160;
+use sp_runtime::{traits::UniqueSaturatedInto, DispatchError};
+use sp_std::vec::Vec;
+// Substrate FRAME
+use frame_support::traits::Currency;
+// Frontier
+use pallet_evm::{AddressMapping, ExitReason, Runner};
+// Local
+use primitives_core::{AccountId, Balance, BlockNumber, Index, Moment};
+pub use primitives_rpc::call::{BlockOverrides, SimulateCall, SimulatedCall, StateOverride};
+
+environmental::environmental!(coinbase_override: H160);
+
+/// The overridden coinbase of the current call, if any.
+pub fn coinbase() -> Option<H160> {
+    coinbase_override::with(|coinbase| *coinbase)
+}
+
+/// Apply the `state` and `block` overrides, then execute `call` with the overridden coinbase.
+pub fn with_overrides<Runtime, R>(
+    state: Vec<StateOverride>,
+    block: BlockOverrides,
+    call: impl FnOnce() -> R,
+) -> R
+where
+    Runtime: frame_system::Config<AccountId = AccountId, Index = Index, BlockNumber = BlockNumber>,
+    Runtime: pallet_timestamp::Config<Moment = Moment>,
+    Runtime: pallet_evm::Config + pallet_base_fee::Config,
+    <Runtime as pallet_evm::Config>::Currency: Currency<AccountId, Balance = Balance>,
+{
+    for account in state {
+        override_account::<Runtime>(account);
+    }
+
+    if let Some(number) = block.number {
+        frame_system::Pallet::<Runtime>::set_block_number(number.unique_saturated_into());
+    }
+    if let Some(timestamp) = block.timestamp {
+        let seconds: Moment = timestamp.unique_saturated_into();
+        pallet_timestamp::Now::<Runtime>::put(seconds.saturating_mul(1000));
+    }
+    if let Some(base_fee) = block.base_fee {
+        pallet_base_fee::BaseFeePerGas::<Runtime>::put(base_fee);
+    }
+
+    match block.coinbase {
+        Some(mut coinbase) => coinbase_override::using(&mut coinbase, call),
+        None => call(),
+    }
+}
+
+/// Execute a call of an `eth_simulateV1` block. The returned value of a create is the code of the
+/// created contract.
+pub fn simulate_call<Runtime>(
+    call: SimulateCall,
+    validate: bool,
+) -> Result<SimulatedCall, DispatchError>
+where
+    Runtime: pallet_evm::Config,
+{
+    let config = <Runtime as pallet_evm::Config>::config();
+    // The calls are free unless a fee is given, as for `eth_call`.
+    let is_transactional = false;
+    let gas_limit = call.gas_limit.unique_saturated_into();
+    match call.to {
+        Some(to) => <Runtime as pallet_evm::Config>::Runner::call(
+            call.from,
+            to,
+            call.data,
+            call.value,
+            gas_limit,
+            call.max_fee_per_gas,
+            call.max_priority_fee_per_gas,
+            call.nonce,
+            call.access_list,
+            is_transactional,
+            validate,
+            config,
+        )
+        .map(|info| SimulatedCall {
+            exit_reason: info.exit_reason,
+            value: info.value,
+            used_gas: info.used_gas,
+            logs: info.logs,
+        })
+        .map_err(|err| err.error.into()),
+        None => <Runtime as pallet_evm::Config>::Runner::create(
+            call.from,
+            call.data,
+            call.value,
+            gas_limit,
+            call.max_fee_per_gas,
+            call.max_priority_fee_per_gas,
+            call.nonce,
+            call.access_list,
+            is_transactional,
+            validate,
+            config,
+        )
+        .map(|info| SimulatedCall {
+            value: match info.exit_reason {
+                ExitReason::Succeed(_) => pallet_evm::AccountCodes::<Runtime>::get(info.value),
+                _ => Vec::new(),
+            },
+            exit_reason: info.exit_reason,
+            used_gas: info.used_gas,
+            logs: info.logs,
+        })
+        .map_err(|err| err.error.into()),
+    }
+}
+
+fn override_account<Runtime>(account: StateOverride)
+where
+    Runtime: frame_system::Config<AccountId = AccountId, Index = Index>,
+    Runtime: pallet_evm::Config,
+    <Runtime as pallet_evm::Config>::Currency: Currency<AccountId, Balance = Balance>,
+{
+    let account_id =
+        <Runtime as pallet_evm::Config>::AddressMapping::into_account_id(account.address);
+
+    if let Some(balance) = account.balance {
+        <Runtime as pallet_evm::Config>::Currency::make_free_balance_be(
+            &account_id,
+            balance.unique_saturated_into(),
+        );
+    }
+    if let Some(nonce) = account.nonce {
+        frame_system::Account::<Runtime>::mutate(&account_id, |info| {
+            info.nonce = nonce.unique_saturated_into();
+        });
+    }
+    if let Some(code) = account.code {
+        pallet_evm::AccountCodes::<Runtime>::insert(account.address, code);
+    }
+    if let Some(state) = account.state {
+        #[allow(deprecated)]
+        let _ = pallet_evm::AccountStorages::<Runtime>::remove_prefix(account.address, None);
+        for (index, value) in state {
+            pallet_evm::AccountStorages::<Runtime>::insert(account.address, index, value);
+        }
+    }
+    for (index, value) in account.state_diff {
+        pallet_evm::AccountStorages::<Runtime>::insert(account.address, index, value);
+    }
+}
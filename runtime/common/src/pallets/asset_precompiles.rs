@@ -0,0 +1,84 @@
+//! Creates the assets of `pallet_assets` along with the code of their ERC-20 precompile.
+//!
+//! Solidity checks an address has code before calling it, so a contract can only call the
+//! precompile of an asset if `pallet_evm` holds some code at its address. The runtimes filter out
+//! the `create` and `force_create` calls of `pallet_assets` for the ones of this pallet, which
+//! also insert a dummy code at the address of the precompile of the asset.
+
+use sp_core::H160;
+use sp_runtime::traits::{Convert, StaticLookup};
+use sp_std::{marker::PhantomData, prelude::*};
+
+pub use pallet::*;
+
+/// The code of the asset precompiles, `PUSH1 0 PUSH1 0 REVERT`. It is never executed, the
+/// precompile set handling the calls to the address.
+pub const ASSET_PRECOMPILE_CODE: [u8; 5] = [0x60, 0x00, 0x60, 0x00, 0xfd];
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_support::pallet_prelude::*;
+    use frame_system::pallet_prelude::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + pallet_assets::Config + pallet_evm::Config {
+        /// The address of the precompile of an asset.
+        type AssetAddress: Convert<Self::AssetId, H160>;
+    }
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(PhantomData<T>);
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Creates an asset with `pallet_assets::create`, and the code of its precompile.
+        #[pallet::weight(
+            <<T as pallet_assets::Config>::WeightInfo as pallet_assets::WeightInfo>::create()
+                .saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        )]
+        pub fn create(
+            origin: OriginFor<T>,
+            #[pallet::compact] id: T::AssetId,
+            admin: <T::Lookup as StaticLookup>::Source,
+            min_balance: T::Balance,
+        ) -> DispatchResult {
+            pallet_assets::Pallet::<T>::create(origin, id, admin, min_balance)?;
+            Self::insert_code(id);
+            Ok(())
+        }
+
+        /// Creates an asset with `pallet_assets::force_create`, and the code of its precompile.
+        #[pallet::weight(
+            <<T as pallet_assets::Config>::WeightInfo as pallet_assets::WeightInfo>::force_create()
+                .saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        )]
+        pub fn force_create(
+            origin: OriginFor<T>,
+            #[pallet::compact] id: T::AssetId,
+            owner: <T::Lookup as StaticLookup>::Source,
+            is_sufficient: bool,
+            #[pallet::compact] min_balance: T::Balance,
+        ) -> DispatchResult {
+            pallet_assets::Pallet::<T>::force_create(
+                origin,
+                id,
+                owner,
+                is_sufficient,
+                min_balance,
+            )?;
+            Self::insert_code(id);
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Inserts the code of the precompile of an asset, unless it already has one.
+    fn insert_code(id: T::AssetId) {
+        let address = T::AssetAddress::convert(id);
+        if !pallet_evm::AccountCodes::<T>::contains_key(address) {
+            pallet_evm::AccountCodes::<T>::insert(address, ASSET_PRECOMPILE_CODE.to_vec());
+        }
+    }
+}
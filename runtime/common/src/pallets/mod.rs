@@ -1,2 +1,3 @@
+pub mod asset_precompiles;
 pub mod authorities;
 pub mod privilege;
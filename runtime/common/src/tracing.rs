@@ -0,0 +1,102 @@
+//! Reports the runtime events deposited by a traced transaction and the storage of the contracts
+//! to the EVM tracing.
+
+use codec::Encode;
+use frame_support::traits::PalletInfoAccess;
+use frame_system::Phase;
+use primitives_core::{AccountId, Balance};
+use primitives_evm_ext::evm_ext;
+use primitives_evm_tracing_events::SubstrateEvent;
+use sp_core::{H160, H256, U256};
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+/// The value of the storage slot `index` of the contract at `address`, see
+/// `EvmTracer::with_storage`.
+pub fn account_storage<Runtime: pallet_evm::Config>(address: H160, index: H256) -> H256 {
+    pallet_evm::AccountStorages::<Runtime>::get(address, index)
+}
+
+/// The index of the extrinsics of the block which deposited an event of `pallet_evm` but none of
+/// `pallet_ethereum`, i.e. entered the EVM without being Ethereum transactions.
+pub fn extrinsics_entering_evm<Runtime>() -> Vec<u32>
+where
+    Runtime: pallet_evm::Config + pallet_ethereum::Config,
+{
+    let evm = <pallet_evm::Pallet<Runtime> as PalletInfoAccess>::index() as u8;
+    let ethereum = <pallet_ethereum::Pallet<Runtime> as PalletInfoAccess>::index() as u8;
+    let (mut entering, mut transactions) = (BTreeSet::new(), BTreeSet::new());
+    for record in frame_system::Pallet::<Runtime>::read_events_no_consensus() {
+        if let Phase::ApplyExtrinsic(index) = record.phase {
+            // The first byte of an event is the index of its pallet.
+            match record.event.using_encoded(|event| event.first().copied()) {
+                Some(pallet) if pallet == evm => {
+                    entering.insert(index);
+                },
+                Some(pallet) if pallet == ethereum => {
+                    transactions.insert(index);
+                },
+                _ => (),
+            }
+        }
+    }
+    entering.difference(&transactions).copied().collect()
+}
+
+/// Reports the events deposited by the extrinsic at `index` in the block, once applied.
+pub fn emit_substrate_events<Runtime>(index: u32)
+where
+    Runtime:
+        frame_system::Config<AccountId = AccountId> + pallet_balances::Config<Balance = Balance>,
+    <Runtime as frame_system::Config>::Event: TryInto<pallet_balances::Event<Runtime>>,
+{
+    for record in frame_system::Pallet::<Runtime>::read_events_no_consensus() {
+        if record.phase == Phase::ApplyExtrinsic(index) {
+            evm_ext::substrate_event(substrate_event::<Runtime>(record.event).encode());
+        }
+    }
+}
+
+fn substrate_event<Runtime>(event: <Runtime as frame_system::Config>::Event) -> SubstrateEvent
+where
+    Runtime:
+        frame_system::Config<AccountId = AccountId> + pallet_balances::Config<Balance = Balance>,
+    <Runtime as frame_system::Config>::Event: TryInto<pallet_balances::Event<Runtime>>,
+{
+    use pallet_balances::Event;
+
+    let address = |account: AccountId| H160::from(account);
+    let amount = |balance: Balance| U256::from(balance);
+    // The first byte of an event is the index of its pallet.
+    let encoded = event.encode();
+    match event.try_into() {
+        Ok(Event::Endowed { account, free_balance }) => SubstrateEvent::Endowed {
+            account: address(account),
+            free_balance: amount(free_balance),
+        },
+        Ok(Event::DustLost { account, amount: dust }) => {
+            SubstrateEvent::DustLost { account: address(account), amount: amount(dust) }
+        },
+        Ok(Event::Transfer { from, to, amount: value }) => {
+            SubstrateEvent::Transfer { from: address(from), to: address(to), amount: amount(value) }
+        },
+        Ok(Event::Reserved { who, amount: value }) => {
+            SubstrateEvent::Reserved { who: address(who), amount: amount(value) }
+        },
+        Ok(Event::Unreserved { who, amount: value }) => {
+            SubstrateEvent::Unreserved { who: address(who), amount: amount(value) }
+        },
+        Ok(Event::Deposit { who, amount: value }) => {
+            SubstrateEvent::Deposit { who: address(who), amount: amount(value) }
+        },
+        Ok(Event::Withdraw { who, amount: value }) => {
+            SubstrateEvent::Withdraw { who: address(who), amount: amount(value) }
+        },
+        Ok(Event::Slashed { who, amount: value }) => {
+            SubstrateEvent::Slashed { who: address(who), amount: amount(value) }
+        },
+        _ => SubstrateEvent::Other {
+            pallet_index: encoded.first().copied().unwrap_or_default(),
+            data: encoded.get(1..).unwrap_or_default().to_vec(),
+        },
+    }
+}
@@ -26,7 +26,7 @@ use sp_version::RuntimeVersion;
 // Substrate FRAME
 use frame_support::{
     construct_runtime, parameter_types,
-    traits::{ConstU16, ConstU32, EitherOfDiverse, KeyOwnerProofSystem},
+    traits::{ConstU16, ConstU32, Contains, EitherOfDiverse, KeyOwnerProofSystem},
     weights::{constants::RocksDbWeight, ConstantMultiplier},
 };
 use frame_system::EnsureRoot;
@@ -39,23 +39,28 @@ use pallet_transaction_payment::CurrencyAdapter;
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_ethereum::{Call as EthereumCall, Transaction as EthereumTransaction};
+pub use pallet_evm::Call as EVMCall;
+pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
 pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 pub use sp_finality_grandpa::AuthorityId as GrandpaId;
 // Local
-use crate::precompiles::EvaPrecompiles;
+use crate::precompiles::{AssetIdToAddress, EvaPrecompiles};
 use eva_runtime_constants::{currency, evm, fee, system, time};
 use runtime_common::{
     evm_config,
-    pallets::{authorities as pallet_authorities, privilege as pallet_privilege},
+    pallets::{
+        asset_precompiles as pallet_asset_precompiles, authorities as pallet_authorities,
+        privilege as pallet_privilege,
+    },
     CoinbaseAuthor, ToAuthor,
 };
 // re-exports
 pub use eva_runtime_constants as constants;
 pub use primitives_core as primitives;
 pub use primitives_core::{
-    AccountId, Address, Balance, Block as NodeBlock, BlockNumber, Hash, Header, Index, Moment,
-    Signature,
+    AccountId, Address, AssetId, Balance, Block as NodeBlock, BlockNumber, Hash, Header, Index,
+    Moment, Signature,
 };
 
 // To learn more about runtime versioning and what each of the following value means:
@@ -65,7 +70,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("armonia-eva"),
     impl_name: create_runtime_str!("armonia-eva"),
     authoring_version: 1,
-    spec_version: 1,
+    spec_version: 2,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
     transaction_version: 1,
@@ -94,9 +99,23 @@ parameter_types! {
     pub const SS58Prefix: u8 = 42;
 }
 
+/// Filters out the calls of `pallet_assets` creating an asset, replaced by the ones of
+/// `pallet_asset_precompiles` which also insert the code of its precompile.
+pub struct BaseFilter;
+impl Contains<Call> for BaseFilter {
+    fn contains(call: &Call) -> bool {
+        !matches!(
+            call,
+            Call::Assets(
+                pallet_assets::Call::create { .. } | pallet_assets::Call::force_create { .. }
+            )
+        )
+    }
+}
+
 impl frame_system::Config for Runtime {
     /// The basic call filter to use in dispatchable.
-    type BaseCallFilter = frame_support::traits::Everything;
+    type BaseCallFilter = BaseFilter;
     /// Block & extrinsics weights: base values and limits.
     type BlockWeights = BlockWeights;
     /// The maximum length of a block (in bytes).
@@ -229,6 +248,39 @@ impl pallet_transaction_payment::Config for Runtime {
     type FeeMultiplierUpdate = ();
 }
 
+parameter_types! {
+    // TODO. need to check this value.
+    pub const AssetDeposit: Balance = 100 * currency::DOLLARS;
+    pub const AssetAccountDeposit: Balance = currency::deposit(1, 16);
+    // Reserved from the owner by the ERC-20 `approve` of the assets precompiles, and returned once
+    // the allowance is spent or set back to zero.
+    pub const ApprovalDeposit: Balance = currency::deposit(1, 148);
+    pub const AssetsStringLimit: u32 = 50;
+    pub const MetadataDepositBase: Balance = currency::deposit(1, 68);
+    pub const MetadataDepositPerByte: Balance = currency::deposit(0, 1);
+}
+
+impl pallet_assets::Config for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type AssetId = AssetId;
+    type Currency = Balances;
+    type ForceOrigin = EnsureRootOrTwoThirdsTechnicalCommittee;
+    type AssetDeposit = AssetDeposit;
+    type AssetAccountDeposit = AssetAccountDeposit;
+    type MetadataDepositBase = MetadataDepositBase;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type ApprovalDeposit = ApprovalDeposit;
+    type StringLimit = AssetsStringLimit;
+    type Freezer = ();
+    type Extra = ();
+    type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_asset_precompiles::Config for Runtime {
+    type AssetAddress = AssetIdToAddress;
+}
+
 // ################################################################################################
 // Consensus.
 // ################################################################################################
@@ -422,6 +474,8 @@ construct_runtime!(
         // Monetary.
         Balances: pallet_balances = 10,
         TransactionPayment: pallet_transaction_payment = 11,
+        Assets: pallet_assets = 12,
+        AssetPrecompiles: pallet_asset_precompiles = 13,
 
         // Consensus.
         Aura: pallet_aura = 20,
@@ -489,7 +543,17 @@ impl fp_self_contained::SelfContainedCall for Call {
 
     fn check_self_contained(&self) -> Option<Result<Self::SignedInfo, TransactionValidityError>> {
         match self {
-            Call::Ethereum(call) => call.check_self_contained(),
+            Call::Ethereum(call) => {
+                // The dev rpc sends the transactions of the impersonated accounts unsigned.
+                #[cfg(feature = "manual-seal")]
+                if let pallet_ethereum::Call::transact { transaction } = call {
+                    if let Some(source) = runtime_common::ethereum::impersonated_source(transaction)
+                    {
+                        return Some(Ok(source))
+                    }
+                }
+                call.check_self_contained()
+            },
             _ => None,
         }
     }
@@ -550,6 +614,7 @@ mod benches {
         [frame_system, SystemBench::<Runtime>]
         [pallet_balances, Balances]
         [pallet_timestamp, Timestamp]
+        [pallet_evm_precompileset_assets_erc20, AssetsErc20Bench::<Runtime>]
     );
 }
 
@@ -698,6 +763,7 @@ impl_runtime_apis! {
             use frame_support::traits::StorageInfoTrait;
             use frame_system_benchmarking::Pallet as SystemBench;
             use baseline::Pallet as BaselineBench;
+            use pallet_evm_precompileset_assets_erc20::benchmarking::Pallet as AssetsErc20Bench;
 
             let mut list = Vec::<BenchmarkList>::new();
             list_benchmarks!(list, extra);
@@ -714,9 +780,11 @@ impl_runtime_apis! {
 
             use frame_system_benchmarking::Pallet as SystemBench;
             use baseline::Pallet as BaselineBench;
+            use pallet_evm_precompileset_assets_erc20::benchmarking::Pallet as AssetsErc20Bench;
 
             impl frame_system_benchmarking::Config for Runtime {}
             impl baseline::Config for Runtime {}
+            impl pallet_evm_precompileset_assets_erc20::benchmarking::Config for Runtime {}
 
             let whitelist: Vec<TrackedStorageKey> = vec![
                 // Block Number
@@ -914,6 +982,138 @@ impl_runtime_apis! {
         }
     }
 
+    impl primitives_rpc::call::EthCallRuntimeApi<Block> for Runtime {
+        fn call(
+            from: H160,
+            to: H160,
+            data: Vec<u8>,
+            value: U256,
+            gas_limit: U256,
+            max_fee_per_gas: Option<U256>,
+            max_priority_fee_per_gas: Option<U256>,
+            nonce: Option<U256>,
+            estimate: bool,
+            access_list: Option<Vec<(H160, Vec<H256>)>>,
+            state_overrides: Vec<primitives_rpc::call::StateOverride>,
+            block_overrides: primitives_rpc::call::BlockOverrides,
+        ) -> Result<pallet_evm::CallInfo, sp_runtime::DispatchError> {
+            runtime_common::overrides::with_overrides::<Runtime, _>(
+                state_overrides,
+                block_overrides,
+                || {
+                    let config = if estimate {
+                        let mut config = <Runtime as pallet_evm::Config>::config().clone();
+                        config.estimate = true;
+                        Some(config)
+                    } else {
+                        None
+                    };
+
+                    let is_transactional = false;
+                    let validate = true;
+                    let evm_config =
+                        config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config());
+                    <Runtime as pallet_evm::Config>::Runner::call(
+                        from,
+                        to,
+                        data,
+                        value,
+                        gas_limit.unique_saturated_into(),
+                        max_fee_per_gas,
+                        max_priority_fee_per_gas,
+                        nonce,
+                        access_list.unwrap_or_default(),
+                        is_transactional,
+                        validate,
+                        evm_config,
+                    ).map_err(|err| err.error.into())
+                },
+            )
+        }
+
+        fn create(
+            from: H160,
+            data: Vec<u8>,
+            value: U256,
+            gas_limit: U256,
+            max_fee_per_gas: Option<U256>,
+            max_priority_fee_per_gas: Option<U256>,
+            nonce: Option<U256>,
+            estimate: bool,
+            access_list: Option<Vec<(H160, Vec<H256>)>>,
+            state_overrides: Vec<primitives_rpc::call::StateOverride>,
+            block_overrides: primitives_rpc::call::BlockOverrides,
+        ) -> Result<pallet_evm::CreateInfo, sp_runtime::DispatchError> {
+            runtime_common::overrides::with_overrides::<Runtime, _>(
+                state_overrides,
+                block_overrides,
+                || {
+                    let config = if estimate {
+                        let mut config = <Runtime as pallet_evm::Config>::config().clone();
+                        config.estimate = true;
+                        Some(config)
+                    } else {
+                        None
+                    };
+
+                    let is_transactional = false;
+                    let validate = true;
+                    let evm_config =
+                        config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config());
+                    <Runtime as pallet_evm::Config>::Runner::create(
+                        from,
+                        data,
+                        value,
+                        gas_limit.unique_saturated_into(),
+                        max_fee_per_gas,
+                        max_priority_fee_per_gas,
+                        nonce,
+                        access_list.unwrap_or_default(),
+                        is_transactional,
+                        validate,
+                        evm_config,
+                    ).map_err(|err| err.error.into())
+                },
+            )
+        }
+
+        #[allow(unused)]
+        fn simulate_call(
+            call: primitives_rpc::call::SimulateCall,
+            validate: bool,
+            trace: bool,
+            state_overrides: Vec<primitives_rpc::call::StateOverride>,
+            block_overrides: primitives_rpc::call::BlockOverrides,
+        ) -> Result<primitives_rpc::call::SimulatedCall, sp_runtime::DispatchError> {
+            runtime_common::overrides::with_overrides::<Runtime, _>(
+                state_overrides,
+                block_overrides,
+                || {
+                    let simulate = || {
+                        runtime_common::overrides::simulate_call::<Runtime>(call, validate)
+                    };
+                    if trace {
+                        #[cfg(feature = "evm-tracing")]
+                        {
+                            use primitives_evm_tracer::EvmTracer;
+                            return EvmTracer::new()
+                                .with_storage(runtime_common::tracing::account_storage::<Runtime>)
+                                .trace(simulate);
+                        }
+
+                        #[cfg(not(feature = "evm-tracing"))]
+                        {
+                            return Err(sp_runtime::DispatchError::Other(
+                                "Missing `evm-tracing` feature flag.",
+                            ));
+                        }
+                    }
+                    simulate()
+                },
+            )
+        }
+    }
+
     // ############################################################################################
     // Tracing RPC
     // ############################################################################################
@@ -927,13 +1127,21 @@ impl_runtime_apis! {
             #[cfg(feature = "evm-tracing")]
             {
                 use primitives_evm_tracer::EvmTracer;
+                let tracer = || {
+                    EvmTracer::new()
+                        .with_storage(runtime_common::tracing::account_storage::<Runtime>)
+                };
                 // Apply the a subset of extrinsics: all the substrate-specific or ethereum
                 // transactions that preceded the requested transaction.
-                for ext in extrinsics.into_iter() {
+                for (index, ext) in extrinsics.into_iter().enumerate() {
                     let _ = match &ext.0.function {
                         Call::Ethereum(EthereumCall::transact { transaction }) => {
                             if transaction == traced_transaction {
-                                EvmTracer::new().trace(|| Executive::apply_extrinsic(ext));
+                                let _ = tracer().trace(|| Executive::apply_extrinsic(ext));
+                                // Along with the runtime events it deposited.
+                                runtime_common::tracing::emit_substrate_events::<Runtime>(
+                                    index as u32,
+                                );
                                 return Ok(());
                             } else {
                                 Executive::apply_extrinsic(ext)
@@ -961,23 +1169,32 @@ impl_runtime_apis! {
             #[cfg(feature = "evm-tracing")]
             {
                 use primitives_evm_tracer::EvmTracer;
+                let tracer = || {
+                    EvmTracer::new()
+                        .with_storage(runtime_common::tracing::account_storage::<Runtime>)
+                };
 
                 let mut config = <Runtime as pallet_evm::Config>::config().clone();
                 config.estimate = true;
-                // Apply all extrinsics. Ethereum extrinsics are traced.
-                for ext in extrinsics.into_iter() {
+                // Apply all extrinsics. Ethereum extrinsics are traced, as well as the other
+                // extrinsics in case they enter the EVM (e.g. `pallet_evm::call` through
+                // `Privilege::sudo`).
+                for (index, ext) in extrinsics.into_iter().enumerate() {
                     match &ext.0.function {
                         Call::Ethereum(EthereumCall::transact { transaction }) => {
                             if known_transactions.contains(&transaction.hash()) {
                                 // Each known extrinsic is a new call stack.
                                 EvmTracer::emit_new();
-                                EvmTracer::new().trace(|| Executive::apply_extrinsic(ext));
+                                let _ = tracer().trace(|| Executive::apply_extrinsic(ext));
                             } else {
                                 let _ = Executive::apply_extrinsic(ext);
                             }
                         }
                         _ => {
-                            let _ = Executive::apply_extrinsic(ext);
+                            // Labelled with its index, dropped by the client if it does not enter
+                            // the EVM.
+                            EvmTracer::emit_new_extrinsic(index as u32);
+                            let _ = tracer().trace(|| Executive::apply_extrinsic(ext));
                         }
                     };
                 }
@@ -989,6 +1206,18 @@ impl_runtime_apis! {
                 Err(sp_runtime::DispatchError::Other("Missing `evm-tracing` feature flag."))
             }
         }
+
+        fn extrinsics_entering_evm() -> Vec<u32> {
+            #[cfg(feature = "evm-tracing")]
+            {
+                runtime_common::tracing::extrinsics_entering_evm::<Runtime>()
+            }
+
+            #[cfg(not(feature = "evm-tracing"))]
+            {
+                Vec::new()
+            }
+        }
     }
 
     impl primitives_rpc::txpool::TxPoolRuntimeApi<Block> for Runtime {
@@ -1,14 +1,56 @@
+use frame_support::parameter_types;
 use pallet_evm_precompile_blake2::Blake2F;
 use pallet_evm_precompile_bn128::{Bn128Add, Bn128Mul, Bn128Pairing};
 use pallet_evm_precompile_modexp::Modexp;
 use pallet_evm_precompile_sha3fips::Sha3FIPS256;
 use pallet_evm_precompile_simple::{ECRecover, ECRecoverPublicKey, Identity, Ripemd160, Sha256};
+use pallet_evm_precompileset_assets_erc20::{AddressToAssetId, Erc20AssetsPrecompileSet};
+use sp_core::H160;
+use sp_runtime::traits::Convert;
 
 use precompile_utils::precompile_set::{
     AddressU64, AllowDelegateCall, ForbidRecursion, PrecompileAt, PrecompileSetBuilder,
-    PrecompilesInRangeInclusive,
+    PrecompileSetStartingWith, PrecompilesInRangeInclusive,
 };
 
+use crate::{AssetId, Runtime};
+
+/// The asset precompile address prefix. Addresses that match against this prefix will be routed
+/// to `Erc20AssetsPrecompileSet`, the remaining 16 bytes being the big-endian asset id.
+pub const ASSET_PRECOMPILE_ADDRESS_PREFIX: &[u8] = &[255u8; 4];
+
+parameter_types! {
+    pub AssetPrefix: &'static [u8] = ASSET_PRECOMPILE_ADDRESS_PREFIX;
+}
+
+impl AddressToAssetId<AssetId> for Runtime {
+    fn address_to_asset_id(address: H160) -> Option<AssetId> {
+        let (prefix, id) = address.as_bytes().split_at(ASSET_PRECOMPILE_ADDRESS_PREFIX.len());
+
+        if prefix != ASSET_PRECOMPILE_ADDRESS_PREFIX {
+            return None
+        }
+
+        Some(AssetId::from_be_bytes(id.try_into().ok()?))
+    }
+
+    fn asset_id_to_address(asset_id: AssetId) -> H160 {
+        let mut address = [0u8; 20];
+        address[..ASSET_PRECOMPILE_ADDRESS_PREFIX.len()]
+            .copy_from_slice(ASSET_PRECOMPILE_ADDRESS_PREFIX);
+        address[ASSET_PRECOMPILE_ADDRESS_PREFIX.len()..].copy_from_slice(&asset_id.to_be_bytes());
+        H160(address)
+    }
+}
+
+/// The address of the precompile of an asset, for `pallet_asset_precompiles`.
+pub struct AssetIdToAddress;
+impl Convert<AssetId, H160> for AssetIdToAddress {
+    fn convert(asset_id: AssetId) -> H160 {
+        Runtime::asset_id_to_address(asset_id)
+    }
+}
+
 pub type WallEPrecompiles<R> = PrecompileSetBuilder<
     R,
     (
@@ -34,5 +76,8 @@ pub type WallEPrecompiles<R> = PrecompileSetBuilder<
                 // Wall-e specific precompiles:
             ),
         >,
+        // Prefixed precompile sets:
+        // Assets of `pallet_assets` as ERC-20 tokens.
+        PrecompileSetStartingWith<AssetPrefix, Erc20AssetsPrecompileSet<R>>,
     ),
 >;
@@ -7,11 +7,10 @@ export const FIRST_CONTRACT_ADDRESS = "0xc01Ee7f10EA4aF4673cFff62710E1D7792aBa8f
 export const NODE_BINARY_NAME = "amax-eva";
 
 export const RUNTIME_SPEC_NAME = "armonia-wall-e";
-export const RUNTIME_SPEC_VERSION = 1;
+export const RUNTIME_SPEC_VERSION = 2;
 export const RUNTIME_IMPL_VERSION = 1;
 
 export const CHAIN_ID = 161; // wall-e chain id
-export const BLOCK_TIMESTAMP = 2; // 2 seconds per block
 export const BLOCK_HASH_COUNT = 2400;
 export const EXISTENTIAL_DEPOSIT = 0; // The minimum amount required to keep an account open
 export const BLOCK_GAS_LIMIT = 60000000; // 0.75 * 20000_0000_0000 / 25000
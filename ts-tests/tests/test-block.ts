@@ -1,7 +1,7 @@
 import { expect } from "chai";
 import { step } from "mocha-steps";
 
-import { BLOCK_TIMESTAMP, BLOCK_GAS_LIMIT } from "./config";
+import { BLOCK_GAS_LIMIT } from "./config";
 import { createAndFinalizeBlock, describeWithFrontier } from "./util";
 
 describeWithFrontier("Frontier RPC (Block)", (context) => {
@@ -64,8 +64,9 @@ describeWithFrontier("Frontier RPC (Block)", (context) => {
 	});
 
 	step("should have valid timestamp after block production", async function () {
+		// Timestamps of sealed blocks follow the wall-clock time.
 		const block = await context.web3.eth.getBlock("latest");
-		expect(block.timestamp).to.be.eq(BLOCK_TIMESTAMP);
+		expect(block.timestamp).to.be.closeTo(Math.floor(Date.now() / 1000), 60);
 	});
 
 	it("genesis block should be already available by hash", async function () {
@@ -110,7 +111,6 @@ describeWithFrontier("Frontier RPC (Block)", (context) => {
 			//parentHash: "0x04540257811b46d103d9896e7807040e7de5080e285841c5430d1a81588a0ce4",
 			receiptsRoot: "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
 			size: 507,
-			timestamp: BLOCK_TIMESTAMP,
 			totalDifficulty: "0",
 			//transactions: [],
 			transactionsRoot: "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
@@ -0,0 +1,57 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+
+import { describeWithFrontier, customRequest } from "./util";
+
+describeWithFrontier("Frontier RPC (Call overrides)", (context) => {
+	const TEST_ACCOUNT = "0x1111111111111111111111111111111111111111";
+	// Returns the storage slot 0: `mstore(0, sload(0)) return(0, 32)`
+	const TEST_CODE = "0x60005460005260206000f3";
+	// Returns the timestamp: `mstore(0, timestamp) return(0, 32)`
+	const TIMESTAMP_CODE = "0x4260005260206000f3";
+	const SLOT_VALUE = "0x000000000000000000000000000000000000000000000000000000000000002a";
+
+	step("eth_call applies the code and storage overrides", async function () {
+		const response = await customRequest(context.web3, "eth_call", [
+			{ to: TEST_ACCOUNT },
+			"latest",
+			{ [TEST_ACCOUNT]: { code: TEST_CODE, stateDiff: { "0x0000000000000000000000000000000000000000000000000000000000000000": SLOT_VALUE } } },
+		]);
+		expect(response.result).to.equal(SLOT_VALUE);
+
+		// The overrides are not persisted.
+		expect(await context.web3.eth.getCode(TEST_ACCOUNT)).to.equal("0x");
+	});
+
+	step("eth_call rejects both state and stateDiff", async function () {
+		const response = await customRequest(context.web3, "eth_call", [
+			{ to: TEST_ACCOUNT },
+			"latest",
+			{ [TEST_ACCOUNT]: { code: TEST_CODE, state: {}, stateDiff: {} } },
+		]);
+		expect(response.error).to.exist;
+	});
+
+	step("eth_call applies the block overrides", async function () {
+		const response = await customRequest(context.web3, "eth_call", [
+			{ to: TEST_ACCOUNT },
+			"latest",
+			{ [TEST_ACCOUNT]: { code: TIMESTAMP_CODE } },
+			{ time: "0x2a" },
+		]);
+		expect(response.result).to.equal(SLOT_VALUE);
+	});
+
+	step("eth_estimateGas applies the balance override", async function () {
+		const from = "0x2222222222222222222222222222222222222222";
+		const request = { from, to: TEST_ACCOUNT, value: "0x10" };
+		expect((await customRequest(context.web3, "eth_estimateGas", [request])).error).to.exist;
+
+		const response = await customRequest(context.web3, "eth_estimateGas", [
+			request,
+			"latest",
+			{ [from]: { balance: "0x56BC75E2D63100000" } },
+		]);
+		expect(response.result).to.equal("0x5208");
+	});
+});
@@ -0,0 +1,100 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+
+import { describeWithFrontier, customRequest } from "./util";
+
+describeWithFrontier("Frontier RPC (Dev)", (context) => {
+	const TEST_ACCOUNT = "0x1111111111111111111111111111111111111111";
+	// Returns the storage slot 0: `mstore(0, sload(0)) return(0, 32)`
+	const TEST_CODE = "0x60005460005260206000f3";
+
+	step("anvil_setBalance sets the balance", async function () {
+		this.timeout(15000);
+		await customRequest(context.web3, "anvil_setBalance", [TEST_ACCOUNT, "0x1234"]);
+		expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.equal("4660");
+	});
+
+	step("hardhat_setCode and hardhat_setStorageAt set the contract state", async function () {
+		this.timeout(15000);
+		await customRequest(context.web3, "hardhat_setCode", [TEST_ACCOUNT, TEST_CODE]);
+		await customRequest(context.web3, "hardhat_setStorageAt", [
+			TEST_ACCOUNT,
+			"0x0",
+			"0x000000000000000000000000000000000000000000000000000000000000002a",
+		]);
+
+		expect(await context.web3.eth.getCode(TEST_ACCOUNT)).to.equal(TEST_CODE);
+		expect(await context.web3.eth.getStorageAt(TEST_ACCOUNT, 0)).to.equal(
+			"0x000000000000000000000000000000000000000000000000000000000000002a"
+		);
+		expect(await context.web3.eth.call({ to: TEST_ACCOUNT })).to.equal(
+			"0x000000000000000000000000000000000000000000000000000000000000002a"
+		);
+	});
+
+	step("evm_setNextBlockTimestamp and evm_increaseTime shift the timestamp", async function () {
+		this.timeout(15000);
+		const timestamp = Math.floor(Date.now() / 1000) + 3600;
+		await customRequest(context.web3, "evm_setNextBlockTimestamp", ["0x" + timestamp.toString(16)]);
+		await customRequest(context.web3, "evm_mine", []);
+		expect((await context.web3.eth.getBlock("latest")).timestamp).to.equal(timestamp);
+
+		await customRequest(context.web3, "evm_increaseTime", ["0xe10"]);
+		await customRequest(context.web3, "evm_mine", []);
+		expect(Number((await context.web3.eth.getBlock("latest")).timestamp)).to.be.gte(timestamp + 3600);
+	});
+
+	step("evm_revert reverts to the snapshot", async function () {
+		this.timeout(15000);
+		const number = await context.web3.eth.getBlockNumber();
+		const id = (await customRequest(context.web3, "evm_snapshot", [])).result;
+
+		await customRequest(context.web3, "anvil_setBalance", [TEST_ACCOUNT, "0x0"]);
+		expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.equal("0");
+
+		expect((await customRequest(context.web3, "evm_revert", [id])).result).to.be.true;
+		expect(await context.web3.eth.getBlockNumber()).to.equal(number);
+		expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.equal("4660");
+
+		// The snapshot is consumed.
+		expect((await customRequest(context.web3, "evm_revert", [id])).result).to.be.false;
+	});
+
+	step("evm_revert keeps the Ethereum mapping in sync", async function () {
+		this.timeout(15000);
+		const id = (await customRequest(context.web3, "evm_snapshot", [])).result;
+		await customRequest(context.web3, "evm_mine", []);
+		const reverted = await context.web3.eth.getBlock("latest");
+		expect((await customRequest(context.web3, "evm_revert", [id])).result).to.be.true;
+
+		// The reverted block is not canonical anymore, the new one at its height is mapped. Its
+		// timestamp differs, so does its Ethereum hash.
+		const timestamp = Number(reverted.timestamp) + 10;
+		await customRequest(context.web3, "evm_mine", ["0x" + timestamp.toString(16)]);
+		const block = await context.web3.eth.getBlock("latest");
+		expect(block.number).to.equal(reverted.number);
+		expect(block.hash).to.not.equal(reverted.hash);
+		expect((await context.web3.eth.getBlock(block.hash)).number).to.equal(block.number);
+		expect(await context.web3.eth.getBlock(reverted.hash)).to.be.null;
+	});
+
+	step("eth_sendTransaction requires an impersonated account", async function () {
+		this.timeout(15000);
+		const from = "0x2222222222222222222222222222222222222222";
+		const to = "0x3333333333333333333333333333333333333333";
+		const transfer = { from, to, value: "0x10", gasPrice: "0x3B9ACA00", gas: "0x5208" };
+		await customRequest(context.web3, "anvil_setBalance", [from, "0x56BC75E2D63100000"]);
+		expect((await customRequest(context.web3, "eth_sendTransaction", [transfer])).error).to.exist;
+
+		await customRequest(context.web3, "anvil_impersonateAccount", [from]);
+		const hash = (await customRequest(context.web3, "eth_sendTransaction", [transfer])).result;
+		await customRequest(context.web3, "evm_mine", []);
+		const receipt = await context.web3.eth.getTransactionReceipt(hash);
+		expect(receipt.status).to.be.true;
+		expect(receipt.from).to.equal(from);
+		expect(await context.web3.eth.getBalance(to)).to.equal("16");
+
+		await customRequest(context.web3, "anvil_stopImpersonatingAccount", [from]);
+		expect((await customRequest(context.web3, "eth_sendTransaction", [transfer])).error).to.exist;
+	});
+});
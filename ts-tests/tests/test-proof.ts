@@ -0,0 +1,24 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+
+import { GENESIS_ACCOUNT } from "./config";
+import { describeWithFrontier, customRequest } from "./util";
+
+describeWithFrontier("Frontier RPC (Proof)", (context) => {
+	// `keccak256("")`
+	const EMPTY_CODE_HASH = "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+	step("eth_getProof returns the account with its proofs", async function () {
+		const response = await customRequest(context.web3, "eth_getProof", [GENESIS_ACCOUNT, ["0x0000000000000000000000000000000000000000000000000000000000000000"], "latest"]);
+		const proof = response.result;
+		const block = await context.web3.eth.getBlock("latest");
+
+		expect(proof.address).to.equal(GENESIS_ACCOUNT);
+		expect(BigInt(proof.balance).toString()).to.equal(await context.web3.eth.getBalance(GENESIS_ACCOUNT, block.number));
+		expect(proof.codeHash).to.equal(EMPTY_CODE_HASH);
+		expect(proof.storageHash).to.equal(proof.stateRoot);
+		expect(proof.accountProof).to.not.be.empty;
+		expect(proof.storageProof).to.have.length(1);
+		expect(proof.storageProof[0].proof).to.not.be.empty;
+	});
+});
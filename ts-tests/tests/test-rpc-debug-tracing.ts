@@ -78,4 +78,92 @@ describeWithFrontier("ETH RPC(Debug Tracing For ERC20)", (context) => {
 			value: "0x0",
 		});
 	});
+
+	step("Get the raw debug trace of a block", async function () {
+		const res = (await customRequest(context.web3, "debug_traceBlockByNumber", ["latest", {}])).result;
+
+		expect(res.length).to.eq(1);
+		expect(res[0].stepLogs.length).to.be.greaterThan(0);
+	});
+
+	step("Debug traces honour their timeout", async function () {
+		const block = await context.web3.eth.getBlock("latest");
+		const hash = block.transactions[0];
+
+		const expired = await customRequest(context.web3, "debug_traceTransaction", [hash, { timeout: "1ns" }]);
+		expect(expired.error.message).to.eq("execution timeout");
+
+		const invalid = await customRequest(context.web3, "debug_traceTransaction", [hash, { timeout: "10 parsecs" }]);
+		expect(invalid.error.message).to.contain("invalid timeout");
+
+		const traced = await customRequest(context.web3, "debug_traceTransaction", [hash, { timeout: "10s" }]);
+		expect(traced.result.stepLogs.length).to.be.greaterThan(0);
+	});
+
+	step("Break the gas of the call frames down", async function () {
+		const res = await context.web3["debug"].traceBlockByNumber("latest", {
+			tracer: "callTracer",
+			tracerConfig: { withGasBreakdown: true },
+		});
+
+		const breakdown = res[0].gasBreakdown;
+		expect(breakdown.refundCap).to.not.be.undefined;
+		expect(
+			Number(breakdown.intrinsicGas) + Number(breakdown.executionGas) + Number(breakdown.memoryGas)
+		).to.eq(Number(res[0].gasUsed));
+
+		const plain = await context.web3["debug"].traceBlockByNumber("latest", { tracer: "callTracer" });
+		expect(plain[0].gasBreakdown).to.be.undefined;
+	});
+
+	step("Report the storage accessed by the call frames", async function () {
+		const res = await context.web3["debug"].traceBlockByNumber("latest", {
+			tracer: "callTracer",
+			tracerConfig: { withStorage: true },
+		});
+
+		// The transfer updates the balances of the sender and the recipient.
+		const storage = res[0].storage;
+		expect(storage.address).to.eq(CONTRACT_ADDRESS.toLowerCase());
+		expect(Object.keys(storage.writes).length).to.eq(2);
+		for (const write of Object.values(storage.writes) as any[]) {
+			expect(write.from).to.match(/^0x[0-9a-f]{64}$/);
+			expect(write.to).to.match(/^0x[0-9a-f]{64}$/);
+		}
+	});
+
+	step("Report the precompile handling a call frame", async function () {
+		const PRECOMPILE_ADDRESS = "0x0000000000000000000000000000000000000400";
+		const tx = await context.web3.eth.accounts.signTransaction(
+			{
+				from: GENESIS_ACCOUNT,
+				to: PRECOMPILE_ADDRESS,
+				data: "0x12345678",
+				value: "0x00",
+				gasPrice: "0x3B9ACA00",
+				gas: "0x100000",
+			},
+			GENESIS_ACCOUNT_PRIVATE_KEY
+		);
+		await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+		await createAndFinalizeBlock(context.web3);
+
+		const res = await context.web3["debug"].traceBlockByNumber("latest", { tracer: "callTracer" });
+		// Not a precompile generated by `#[precompile]`, its functions are unknown.
+		expect(res[0].precompile).to.deep.eq({ address: PRECOMPILE_ADDRESS });
+	});
+
+	step("Report the runtime events deposited by a traced transaction", async function () {
+		const block = await context.web3.eth.getBlock("latest");
+		const res = await context.web3["debug"].traceTransaction(block.transactions[0], {
+			tracer: "callTracer",
+			tracerConfig: { withSubstrateEvents: true },
+		});
+
+		expect(res.trace.from).to.eq(GENESIS_ACCOUNT.toLowerCase());
+		// The fee is withdrawn from the sender.
+		const withdraw = res.substrateEvents.find((event) => event.event == "Withdraw");
+		expect(withdraw.who).to.eq(GENESIS_ACCOUNT.toLowerCase());
+		expect(Number(withdraw.amount)).to.be.greaterThan(0);
+	});
 });
@@ -1,10 +1,23 @@
 import { expect } from "chai";
 import { step } from "mocha-steps";
-import { createAndFinalizeBlock, describeWithFrontier } from "./util";
+import { createAndFinalizeBlock, customRequest, describeWithFrontier } from "./util";
 import { createErc20Context, erc20BalanceOf, erc20Transfer } from "./utils-erc20";
 
 import TestERC20 from "../build/contracts/TestERC20.json";
 
+// Strip the signatures of the pool content, checking they are set.
+function withoutSignatures(content) {
+	for (const transactions of Object.values(content)) {
+		for (const transaction of Object.values(transactions)) {
+			for (const field of ["v", "r", "s"]) {
+				expect(transaction[field]).to.match(/^0x[0-9a-f]+$/);
+				delete transaction[field];
+			}
+		}
+	}
+	return content;
+}
+
 describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 	const GENESIS_ACCOUNT = "0xf24FF3a9CF04c71Dbc94D0b566f7A27B94566cac";
 	const GENESIS_ACCOUNT_PRIVATE_KEY = "0x5fb92d6e98884f76de468fa3f6278f8807c48bebc13595d45af5bdc4da702133";
@@ -35,10 +48,10 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 
 		// console.log(JSON.stringify(pending, null, 2));
 
-		expect(pending.pending).to.be.eql({
+		expect(withoutSignatures(pending.pending)).to.be.eql({
 			"0xf24ff3a9cf04c71dbc94d0b566f7a27b94566cac": {
 				"0x1": {
-					blockHash: "0x0000000000000000000000000000000000000000000000000000000000000000",
+					blockHash: null,
 					blockNumber: null,
 					hash: "0x8cb6c154bddeda606be33282cbebbf7745c3a61a306f89bc31dd40ba6b24be7b",
 					transactionIndex: null,
@@ -49,9 +62,11 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 					gas: "0x500000",
 					gasPrice: "0x3b9aca00",
 					input: "0xa9059cbb000000000000000000000000111111111111111111111111111111111111111100000000000000000000000000000000000000000000000000000000000003e8",
+					type: "0x0",
+					chainId: "0xa1",
 				},
 				"0x3": {
-					blockHash: "0x0000000000000000000000000000000000000000000000000000000000000000",
+					blockHash: null,
 					blockNumber: null,
 					hash: "0x60609aa3ed063d850d987e6a9b7190d8b3d5c8457b40108133832b0546b3ff6c",
 					transactionIndex: null,
@@ -62,9 +77,11 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 					gas: "0x500000",
 					gasPrice: "0x3b9aca00",
 					input: "0xa9059cbb000000000000000000000000111111111111111111111111111111111111111100000000000000000000000000000000000000000000000000000000000003e8",
+					type: "0x0",
+					chainId: "0xa1",
 				},
 				"0x2": {
-					blockHash: "0x0000000000000000000000000000000000000000000000000000000000000000",
+					blockHash: null,
 					blockNumber: null,
 					hash: "0xab7a8b1f20c6ffbf3ad5cd4e783e5e413ed171f9bb3fa0a2a26a5cbbf86ecbea",
 					transactionIndex: null,
@@ -75,6 +92,8 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 					gas: "0x500000",
 					gasPrice: "0x3b9aca00",
 					input: "0xa9059cbb000000000000000000000000111111111111111111111111111111111111111100000000000000000000000000000000000000000000000000000000000003e8",
+					type: "0x0",
+					chainId: "0xa1",
 				},
 			},
 		});
@@ -106,11 +125,11 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 
 		// console.log(JSON.stringify(queued, null, 2));
 
-		expect(queued).to.be.eql({
+		expect({ pending: withoutSignatures(queued.pending), queued: withoutSignatures(queued.queued) }).to.be.eql({
 			pending: {
 				"0xf24ff3a9cf04c71dbc94d0b566f7a27b94566cac": {
 					"0x5": {
-						blockHash: "0x0000000000000000000000000000000000000000000000000000000000000000",
+						blockHash: null,
 						blockNumber: null,
 						hash: "0x6a7ae7ccb9fd01c6808b9c11911ed8680744a483f6053d45399b1cecacd3c5df",
 						transactionIndex: null,
@@ -121,9 +140,11 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 						gas: "0x500000",
 						gasPrice: "0x3b9aca00",
 						input: "0xa9059cbb000000000000000000000000111111111111111111111111111111111111111100000000000000000000000000000000000000000000000000000000000003e8",
+						type: "0x0",
+						chainId: "0xa1",
 					},
 					"0x4": {
-						blockHash: "0x0000000000000000000000000000000000000000000000000000000000000000",
+						blockHash: null,
 						blockNumber: null,
 						hash: "0x2acd398569e50a6a3e6ed803c72e2cd4f06a49d717beaaf94e0f1a582a101d7f",
 						transactionIndex: null,
@@ -134,13 +155,15 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 						gas: "0x500000",
 						gasPrice: "0x3b9aca00",
 						input: "0xa9059cbb000000000000000000000000111111111111111111111111111111111111111100000000000000000000000000000000000000000000000000000000000003e8",
+						type: "0x0",
+						chainId: "0xa1",
 					},
 				},
 			},
 			queued: {
 				"0xf24ff3a9cf04c71dbc94d0b566f7a27b94566cac": {
 					"0x7": {
-						blockHash: "0x0000000000000000000000000000000000000000000000000000000000000000",
+						blockHash: null,
 						blockNumber: null,
 						hash: "0x113fe4d35150ac3f0651f00ff29151c90500f8268544f6ddb18b0884a0d2604f",
 						transactionIndex: null,
@@ -151,6 +174,8 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 						gas: "0x500000",
 						gasPrice: "0x3b9aca00",
 						input: "0xa9059cbb000000000000000000000000111111111111111111111111111111111111111100000000000000000000000000000000000000000000000000000000000003e8",
+						type: "0x0",
+						chainId: "0xa1",
 					},
 				},
 			},
@@ -162,12 +187,12 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 
 		// console.log(JSON.stringify(queued_after, null, 2));
 
-		expect(queued_after).to.be.eql({
+		expect({ pending: queued_after.pending, queued: withoutSignatures(queued_after.queued) }).to.be.eql({
 			pending: {},
 			queued: {
 				"0xf24ff3a9cf04c71dbc94d0b566f7a27b94566cac": {
 					"0x7": {
-						blockHash: "0x0000000000000000000000000000000000000000000000000000000000000000",
+						blockHash: null,
 						blockNumber: null,
 						hash: "0x113fe4d35150ac3f0651f00ff29151c90500f8268544f6ddb18b0884a0d2604f",
 						transactionIndex: null,
@@ -178,9 +203,21 @@ describeWithFrontier("ETH RPC(Txpool For ERC20)", (context) => {
 						gas: "0x500000",
 						gasPrice: "0x3b9aca00",
 						input: "0xa9059cbb000000000000000000000000111111111111111111111111111111111111111100000000000000000000000000000000000000000000000000000000000003e8",
+						type: "0x0",
+						chainId: "0xa1",
 					},
 				},
 			},
 		});
 	});
+
+	step("Get txpool contentFrom rpc", async function () {
+		const content = (await customRequest(context.web3, "txpool_contentFrom", [GENESIS_ACCOUNT])).result;
+		expect(content.pending).to.deep.equal({});
+		expect(Object.keys(content.queued)).to.be.eql(["0x7"]);
+		expect(content.queued["0x7"].hash).to.equal("0x113fe4d35150ac3f0651f00ff29151c90500f8268544f6ddb18b0884a0d2604f");
+
+		const empty = (await customRequest(context.web3, "txpool_contentFrom", [TEST_ACCOUNT])).result;
+		expect(empty).to.deep.equal({ pending: {}, queued: {} });
+	});
 });
@@ -0,0 +1,69 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+
+import { GENESIS_ACCOUNT } from "./config";
+import { describeWithFrontier, customRequest } from "./util";
+
+describeWithFrontier("Frontier RPC (Simulate)", (context) => {
+	const TEST_ACCOUNT = "0x1111111111111111111111111111111111111111";
+	const RECIPIENT = "0x2222222222222222222222222222222222222222";
+	// Returns the storage slot 0: `mstore(0, sload(0)) return(0, 32)`
+	const TEST_CODE = "0x60005460005260206000f3";
+	const SLOT_VALUE = "0x000000000000000000000000000000000000000000000000000000000000002a";
+	const TRANSFER_TOPIC = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+	step("eth_simulateV1 executes the calls of each block on top of the previous ones", async function () {
+		const response = await customRequest(context.web3, "eth_simulateV1", [
+			{
+				blockStateCalls: [
+					{
+						stateOverrides: {
+							[TEST_ACCOUNT]: {
+								code: TEST_CODE,
+								stateDiff: { "0x0000000000000000000000000000000000000000000000000000000000000000": SLOT_VALUE },
+							},
+						},
+						calls: [{ from: GENESIS_ACCOUNT, to: RECIPIENT, value: "0x10" }],
+					},
+					{ blockOverrides: { time: "0xffffffff" }, calls: [{ from: GENESIS_ACCOUNT, to: TEST_ACCOUNT }] },
+				],
+				traceTransfers: true,
+			},
+			"latest",
+		]);
+		const [first, second] = response.result;
+		const number = await context.web3.eth.getBlockNumber();
+		expect(Number(first.number)).to.equal(number + 1);
+		expect(Number(second.number)).to.equal(number + 2);
+		expect(second.parentHash).to.equal(first.hash);
+		expect(second.timestamp).to.equal("0xffffffff");
+
+		expect(first.calls[0].status).to.equal("0x1");
+		expect(first.calls[0].logs).to.have.length(1);
+		expect(first.calls[0].logs[0].topics[0]).to.equal(TRANSFER_TOPIC);
+		expect(second.calls[0].returnData).to.equal(SLOT_VALUE);
+
+		// The simulation is not persisted.
+		expect(await context.web3.eth.getBalance(RECIPIENT)).to.equal("0");
+	});
+
+	step("eth_simulateV1 returns the revert data of a failed call", async function () {
+		// Reverts with an empty message: `revert(0, 0)`
+		const revertCode = "0x60006000fd";
+		const response = await customRequest(context.web3, "eth_simulateV1", [
+			{
+				blockStateCalls: [
+					{
+						stateOverrides: { [TEST_ACCOUNT]: { code: revertCode } },
+						calls: [{ from: GENESIS_ACCOUNT, to: TEST_ACCOUNT }],
+					},
+				],
+			},
+			"latest",
+		]);
+		const call = response.result[0].calls[0];
+		expect(call.status).to.equal("0x0");
+		expect(call.error.code).to.equal(3);
+		expect(call.error.message).to.equal("execution reverted");
+	});
+});
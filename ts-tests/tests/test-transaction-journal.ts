@@ -0,0 +1,43 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY } from "./config";
+import { createAndFinalizeBlock, describeWithFrontier, customRequest } from "./util";
+
+describeWithFrontier(
+	"Frontier RPC (Transaction Journal)",
+	(context) => {
+		const TEST_ACCOUNT = "0x1111111111111111111111111111111111111111";
+
+		step("eth_sendRawTransaction submits the transaction through the journal", async function () {
+			this.timeout(15000);
+			const tx = await context.web3.eth.accounts.signTransaction(
+				{
+					from: GENESIS_ACCOUNT,
+					to: TEST_ACCOUNT,
+					value: "0x200",
+					gasPrice: "0x3B9ACA00",
+					gas: "0x100000",
+				},
+				GENESIS_ACCOUNT_PRIVATE_KEY
+			);
+
+			const response = await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+			expect(response.result).to.be.eq(tx.transactionHash);
+			expect(await context.web3.eth.getTransactionCount(GENESIS_ACCOUNT, "pending")).to.eq(1);
+
+			await createAndFinalizeBlock(context.web3);
+			const receipt = await context.web3.eth.getTransactionReceipt(tx.transactionHash);
+			expect(receipt.status).to.be.true;
+			expect(await context.web3.eth.getBalance(TEST_ACCOUNT)).to.eq("512");
+		});
+
+		step("eth_sendRawTransaction rejects an invalid transaction", async function () {
+			this.timeout(15000);
+			const response = await customRequest(context.web3, "eth_sendRawTransaction", ["0x1234"]);
+			expect(response.error).to.exist;
+		});
+	},
+	undefined,
+	["--txpool-journal"]
+);
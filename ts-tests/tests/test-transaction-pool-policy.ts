@@ -0,0 +1,70 @@
+import { expect } from "chai";
+import { step } from "mocha-steps";
+
+import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY } from "./config";
+import { createAndFinalizeBlock, describeWithFrontier, customRequest } from "./util";
+
+const TEST_ACCOUNT = "0x1111111111111111111111111111111111111111";
+
+async function sendTransaction(context, nonce: number, gasPrice: string) {
+	const tx = await context.web3.eth.accounts.signTransaction(
+		{
+			from: GENESIS_ACCOUNT,
+			to: TEST_ACCOUNT,
+			value: "0x200",
+			gasPrice: gasPrice,
+			gas: "0x100000",
+			nonce: nonce,
+		},
+		GENESIS_ACCOUNT_PRIVATE_KEY
+	);
+
+	return await customRequest(context.web3, "eth_sendRawTransaction", [tx.rawTransaction]);
+}
+
+describeWithFrontier("Frontier RPC (Pool Policy Price Bump)", (context) => {
+	step("should only replace a transaction bumping its gasPrice by 10%", async function () {
+		this.timeout(15000);
+		expect((await sendTransaction(context, 0, "0x3B9ACA00")).error).to.be.undefined;
+		// 1_050_000_000, a 5% bump.
+		expect((await sendTransaction(context, 0, "0x3E95BA80")).error).to.not.be.undefined;
+		// 1_100_000_000, a 10% bump.
+		expect((await sendTransaction(context, 0, "0x4190AB00")).error).to.be.undefined;
+		// 1_150_000_000, a 4.5% bump over the replacement.
+		expect((await sendTransaction(context, 0, "0x448B9B80")).error).to.not.be.undefined;
+
+		await createAndFinalizeBlock(context.web3);
+		const block = await context.web3.eth.getBlock("latest", true);
+		expect(block.transactions.length).to.be.eq(1);
+		expect(block.transactions[0].gasPrice).to.be.eq("1100000000");
+	});
+});
+
+describeWithFrontier(
+	"Frontier RPC (Pool Policy Account Queue)",
+	(context) => {
+		step("should cap the future transactions of an account", async function () {
+			this.timeout(15000);
+			expect((await sendTransaction(context, 1, "0x3B9ACA00")).error).to.be.undefined;
+			expect((await sendTransaction(context, 2, "0x3B9ACA00")).error).to.be.undefined;
+			expect((await sendTransaction(context, 3, "0x3B9ACA00")).error).to.not.be.undefined;
+			// The missing nonce is not a future transaction.
+			expect((await sendTransaction(context, 0, "0x3B9ACA00")).error).to.be.undefined;
+
+			await createAndFinalizeBlock(context.web3);
+			const block = await context.web3.eth.getBlock("latest", true);
+			expect(block.transactions.length).to.be.eq(3);
+		});
+
+		step("should accept the rejected transaction once the queue is drained", async function () {
+			this.timeout(15000);
+			expect((await sendTransaction(context, 3, "0x3B9ACA00")).error).to.be.undefined;
+
+			await createAndFinalizeBlock(context.web3);
+			const block = await context.web3.eth.getBlock("latest", true);
+			expect(block.transactions.length).to.be.eq(1);
+		});
+	},
+	undefined,
+	["--txpool-account-queue=2"]
+);
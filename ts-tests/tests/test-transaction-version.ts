@@ -3,7 +3,7 @@ import { expect } from "chai";
 import { step } from "mocha-steps";
 
 import { GENESIS_ACCOUNT, GENESIS_ACCOUNT_PRIVATE_KEY, CHAIN_ID } from "./config";
-import { createAndFinalizeBlock, describeWithFrontier } from "./util";
+import { createAndFinalizeBlock, customRequest, describeWithFrontier } from "./util";
 
 // We use ethers library in this test as apparently web3js's types are not fully EIP-1559 compliant yet.
 describeWithFrontier("Frontier RPC (Transaction Version)", (context) => {
@@ -85,6 +85,17 @@ describeWithFrontier("Frontier RPC (Transaction Version)", (context) => {
 			chainId: CHAIN_ID,
 		};
 		const txHash = (await sendTransaction(context, tx)).hash;
+
+		const pooled = (await customRequest(context.web3, "txpool_contentFrom", [GENESIS_ACCOUNT])).result.pending["0x2"];
+		expect(pooled.hash).to.be.eq(txHash);
+		expect(pooled.type).to.be.eq("0x2");
+		expect(pooled.to).to.be.null;
+		expect(pooled.maxFeePerGas).to.be.eq("0x3b9aca00");
+		expect(pooled.maxPriorityFeePerGas).to.be.eq("0x1");
+		expect(pooled.chainId).to.be.eq("0x" + CHAIN_ID.toString(16));
+		expect(pooled.accessList).to.be.eql([]);
+		expect(pooled).to.include.all.keys("v", "r", "s");
+
 		await createAndFinalizeBlock(context.web3);
 		const latest = await context.web3.eth.getBlock("latest");
 		expect(latest.transactions.length).to.be.eq(1);
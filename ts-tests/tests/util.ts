@@ -58,7 +58,7 @@ export async function createAndFinalizeBlockNowait(web3: Web3) {
 	}
 }
 
-export async function startFrontierNode(provider?: string): Promise<{
+export async function startFrontierNode(provider?: string, extraArgs: string[] = []): Promise<{
 	web3: Web3;
 	binary: ChildProcess;
 	ethersjs: ethers.providers.JsonRpcProvider;
@@ -88,6 +88,7 @@ export async function startFrontierNode(provider?: string): Promise<{
 		`--ethapi=txpool`,
 		`--ethapi=debug`,
 		`--tmp`,
+		...extraArgs,
 	];
 	const binary = spawn(cmd, args);
 
@@ -148,7 +149,12 @@ export async function startFrontierNode(provider?: string): Promise<{
 	return { web3, binary, ethersjs };
 }
 
-export function describeWithFrontier(title: string, cb: (context: { web3: Web3 }) => void, provider?: string) {
+export function describeWithFrontier(
+	title: string,
+	cb: (context: { web3: Web3 }) => void,
+	provider?: string,
+	extraArgs?: string[]
+) {
 	describe(title, () => {
 		let context: {
 			web3: Web3;
@@ -159,7 +165,7 @@ export function describeWithFrontier(title: string, cb: (context: { web3: Web3 }
 		// Making sure the Frontier node has started
 		before("Starting Frontier Test Node", async function () {
 			this.timeout(SPAWNING_TIME);
-			let init = await startFrontierNode(provider);
+			let init = await startFrontierNode(provider, extraArgs);
 			init.web3 = extendTrace(init.web3);
 			context.web3 = init.web3;
 			context.ethersjs = init.ethersjs;